@@ -0,0 +1,356 @@
+// src-tauri/src/benchmark.rs
+// Engine benchmarks: a fixed set of Jieqi positions searched to a depth or for a move time,
+// measuring nodes, speed, time-to-depth and how soon the best move settles. Results are kept
+// per engine binary and machine, so builds can be compared on the same hardware.
+use crate::match_runner::{self, EngineOptionValue, MatchEngine, MatchEngineSpec};
+use crate::uci::{self, UciMessage};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Time allowed for `uciok`/`readyok` while setting the engine up.
+const SETUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time a stopped search gets to send its `bestmove`.
+const STOP_GRACE: Duration = Duration::from_secs(1);
+
+/// Longest single wait, so a stop request is noticed quickly.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Results kept per engine and machine; older runs are dropped first.
+const MAX_STORED_RUNS: usize = 50;
+
+/// Positions searched when a benchmark names none: the start position, then positions from
+/// the opening to the endgame with dark and revealed pieces in varying proportions.
+pub const BENCH_POSITIONS: &[&str] = &[
+    "xxxxkxxxx/9/1x5x1/x1x1x1x1x/9/9/X1X1X1X1X/1X5X1/9/XXXXKXXXX w A2B2N2R2C2P5a2b2n2r2c2p5 - 0 1",
+    "x1xxkx3/5b2r/c6xc/x1x1xax1x/9/P5B2/2X1X2BX/C1P6/8R/XX1XKXXX1 w R1r1N2n2b1A2a1C1P3p5 - 12 7",
+    "1xx1kxxx1/p8/1x3c1x1/x1x1xn2x/8R/9/X1X1X1X1n/B8/5R2P/XX1XKXXX1 w r2N2B1b2A2a2C1c1P4p4 C1 0 11",
+    "x2xk4/3rn2P1/p1p6/x1x1p1x1p/4a3b/1pP5P/X4AX2/2N6/2R1K3R/1XXX2X1X w r1N1n1B2b1A1a1C1c1P2 - 24 16",
+    "1rx2xxx1/4p4/2p1k3b/xac5x/6n2/3R5/X3X3X/P2KB3B/N3A2cN/XXXX4C w R1r1n1b1A1C1P4p3 - 17 21",
+    "3xkxb2/5r2r/2p1p1a2/x3xN3/R1c1c3a/P8/4X3X/B1P1R4/5K1P1/3XAX3 w n1b1A1C2P1p2 N1B1P1 5 26",
+    "2x2xNx1/2c1k3r/1a7/P1x3xa1/3Ap2n1/3c1A3/X5X1n/NC6P/6Rb1/X4K2R w r1B1b1C1P1p3 B1P2p1 4 33",
+    "2x2x3/2R1nk3/5a3/6x1x/2P6/pPB6/4X3X/1n6A/P1N1K4/2p2X1X1 w R1N1b1a1C1P1p2 r1C1c1p1 17 41",
+];
+
+/// How long each position is searched.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BenchmarkLimit {
+    Depth { depth: u32 },
+    MoveTime { movetime_ms: u64 },
+}
+
+impl BenchmarkLimit {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            BenchmarkLimit::Depth { depth: 0 } => Err("The benchmark depth must be at least 1.".to_string()),
+            BenchmarkLimit::MoveTime { movetime_ms: 0 } => Err("The benchmark move time must be at least 1 ms.".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    fn go_command(&self) -> String {
+        match self {
+            BenchmarkLimit::Depth { depth } => format!("go depth {}", depth),
+            BenchmarkLimit::MoveTime { movetime_ms } => format!("go movetime {}", movetime_ms),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BenchmarkConfig {
+    pub engine: MatchEngineSpec,
+    pub limit: BenchmarkLimit,
+    /// FENs to search; `BENCH_POSITIONS` when empty.
+    #[serde(default)]
+    pub positions: Vec<String>,
+}
+
+impl BenchmarkConfig {
+    pub fn positions(&self) -> Vec<String> {
+        if self.positions.is_empty() {
+            BENCH_POSITIONS.iter().map(|fen| fen.to_string()).collect()
+        } else {
+            self.positions.clone()
+        }
+    }
+}
+
+/// When the search first completed a depth.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct DepthTime {
+    pub depth: u32,
+    pub time_ms: u64,
+    pub nodes: u64,
+}
+
+/// Measurements of one position.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PositionResult {
+    pub fen: String,
+    pub bestmove: String,
+    /// Deepest depth reported.
+    pub depth: u32,
+    pub nodes: u64,
+    /// Wall time from `go` to `bestmove`.
+    pub time_ms: u64,
+    pub nps: u64,
+    pub depth_times: Vec<DepthTime>,
+    /// How often the first move of the main line changed from one depth to the next.
+    pub best_move_changes: u32,
+    /// Depth from which the main line started with the final best move, if it ever did.
+    pub stable_from_depth: Option<u32>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct BenchmarkTotals {
+    pub nodes: u64,
+    pub time_ms: u64,
+    pub nps: u64,
+    pub best_move_changes: u32,
+    /// Positions whose best move was already found at half the final depth or earlier.
+    pub stable_positions: usize,
+}
+
+/// The machine a benchmark ran on, as far as it matters for speed.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct MachineInfo {
+    pub hostname: String,
+    pub os: String,
+    pub arch: String,
+    pub cpu: Option<String>,
+    pub threads: usize,
+}
+
+impl MachineInfo {
+    pub fn current() -> Self {
+        let hostname = std::env::var("HOSTNAME")
+            .or_else(|_| std::env::var("COMPUTERNAME"))
+            .ok()
+            .or_else(|| fs::read_to_string("/etc/hostname").ok())
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "unknown".to_string());
+        // Only Linux and Android name the CPU in a file we can read without extra crates
+        let cpu = fs::read_to_string("/proc/cpuinfo").ok().and_then(|info| {
+            info.lines()
+                .find_map(|line| line.strip_prefix("model name").or_else(|| line.strip_prefix("Hardware")))
+                .and_then(|rest| rest.split_once(':'))
+                .map(|(_, name)| name.trim().to_string())
+        });
+        MachineInfo {
+            hostname,
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            cpu,
+            threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+
+    /// Key results are stored under.
+    pub fn key(&self) -> String {
+        format!("{}/{}-{}", self.hostname, self.os, self.arch)
+    }
+}
+
+/// One benchmark run.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BenchmarkResult {
+    pub engine_name: String,
+    /// Hex SHA-256 of the engine binary, as in the engine registry.
+    pub engine_hash: String,
+    pub machine: MachineInfo,
+    pub limit: BenchmarkLimit,
+    pub options: Vec<EngineOptionValue>,
+    /// Unix time of the run, in seconds.
+    pub run_at: u64,
+    pub positions: Vec<PositionResult>,
+    pub totals: BenchmarkTotals,
+}
+
+/// The benchmark file: runs by engine hash, then by machine key, oldest first.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct BenchmarkStore {
+    pub results: BTreeMap<String, BTreeMap<String, Vec<BenchmarkResult>>>,
+}
+
+impl BenchmarkStore {
+    /// Read the store, or start an empty one when the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| format!("Invalid benchmark file: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(BenchmarkStore::default()),
+            Err(e) => Err(format!("Failed to read benchmark file: {}", e)),
+        }
+    }
+
+    /// Write through a temporary file so a crash never leaves half a store behind.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, content).map_err(|e| format!("Failed to write benchmark file: {}", e))?;
+        fs::rename(&temp, path).map_err(|e| format!("Failed to write benchmark file: {}", e))
+    }
+
+    pub fn add(&mut self, result: BenchmarkResult) {
+        let runs = self
+            .results
+            .entry(result.engine_hash.clone())
+            .or_default()
+            .entry(result.machine.key())
+            .or_default();
+        runs.push(result);
+        if runs.len() > MAX_STORED_RUNS {
+            runs.drain(..runs.len() - MAX_STORED_RUNS);
+        }
+    }
+
+    /// Add a run to the store at `path`.
+    pub fn record(path: &Path, result: BenchmarkResult) -> Result<(), String> {
+        let mut store = BenchmarkStore::load(path)?;
+        store.add(result);
+        store.save(path)
+    }
+
+    /// Runs of one engine binary, or of all engines, on every machine, oldest first.
+    pub fn runs(&self, engine_hash: Option<&str>) -> Vec<BenchmarkResult> {
+        let mut runs: Vec<BenchmarkResult> = self
+            .results
+            .iter()
+            .filter(|(hash, _)| engine_hash.is_none_or(|h| h == hash.as_str()))
+            .flat_map(|(_, machines)| machines.values().flatten().cloned())
+            .collect();
+        runs.sort_by_key(|run| run.run_at);
+        runs
+    }
+}
+
+/// Stop flag of the benchmark in progress, if any.
+#[derive(Default)]
+pub struct BenchmarkState {
+    pub stop: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+/// Set the engine up and search every position in turn. `engine_hash` identifies the binary
+/// in the result; `on_position` receives each position's measurements as soon as they are
+/// known, and `stop` aborts the run.
+pub fn run(
+    engine: &mut dyn MatchEngine,
+    config: &BenchmarkConfig,
+    engine_hash: String,
+    stop: &AtomicBool,
+    on_position: &mut dyn FnMut(usize, &PositionResult),
+) -> Result<BenchmarkResult, String> {
+    config.limit.validate()?;
+    let run_at = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    engine.send("uci")?;
+    match_runner::wait_for(engine, "uciok", SETUP_TIMEOUT)?;
+    for option in &config.engine.options {
+        engine.send(&format!("setoption name {} value {}", option.name, option.value))?;
+    }
+
+    let mut positions = Vec::new();
+    for (index, fen) in config.positions().iter().enumerate() {
+        // A fresh game per position, so hash contents from the previous one do not help
+        engine.send("ucinewgame")?;
+        engine.send("isready")?;
+        match_runner::wait_for(engine, "readyok", SETUP_TIMEOUT)?;
+        engine.send(&format!("position fen {}", fen))?;
+        let result = search(engine, fen, config.limit, stop)?;
+        on_position(index, &result);
+        positions.push(result);
+    }
+
+    let mut totals = BenchmarkTotals::default();
+    for result in &positions {
+        totals.nodes += result.nodes;
+        totals.time_ms += result.time_ms;
+        totals.best_move_changes += result.best_move_changes;
+        if result.stable_from_depth.is_some_and(|d| d * 2 <= result.depth) {
+            totals.stable_positions += 1;
+        }
+    }
+    totals.nps = nps(totals.nodes, totals.time_ms);
+    Ok(BenchmarkResult {
+        engine_name: config.engine.name.clone(),
+        engine_hash,
+        machine: MachineInfo::current(),
+        limit: config.limit,
+        options: config.engine.options.clone(),
+        run_at,
+        positions,
+        totals,
+    })
+}
+
+fn search(engine: &mut dyn MatchEngine, fen: &str, limit: BenchmarkLimit, stop: &AtomicBool) -> Result<PositionResult, String> {
+    let started = Instant::now();
+    engine.send(&limit.go_command())?;
+
+    let mut nodes = 0;
+    let mut depth_times: Vec<DepthTime> = Vec::new();
+    // First move of the main line at each depth, in depth order
+    let mut depth_moves: Vec<(u32, String)> = Vec::new();
+    let bestmove = loop {
+        if stop.load(Ordering::Relaxed) {
+            engine.send("stop")?;
+            let _ = match_runner::wait_for(engine, "bestmove", STOP_GRACE);
+            return Err("Benchmark stopped.".to_string());
+        }
+        let Some(line) = engine.recv(POLL_INTERVAL)? else {
+            continue;
+        };
+        match uci::parse_line(&line) {
+            Some(UciMessage::Info(info)) if info.multipv.unwrap_or(1) == 1 => {
+                nodes = info.nodes.unwrap_or(nodes);
+                let (Some(depth), Some(first)) = (info.depth, info.pv.first()) else {
+                    continue;
+                };
+                if depth_times.last().is_none_or(|last| depth > last.depth) {
+                    depth_times.push(DepthTime {
+                        depth,
+                        time_ms: started.elapsed().as_millis() as u64,
+                        nodes,
+                    });
+                }
+                match depth_moves.last_mut() {
+                    Some((last, mv)) if *last == depth => *mv = first.clone(),
+                    Some((last, _)) if *last > depth => {}
+                    _ => depth_moves.push((depth, first.clone())),
+                }
+            }
+            Some(UciMessage::BestMove(best)) => break best.bestmove,
+            _ => {}
+        }
+    };
+    let time_ms = started.elapsed().as_millis() as u64;
+
+    let best_move_changes = depth_moves.windows(2).filter(|pair| pair[0].1 != pair[1].1).count() as u32;
+    // Compare the squares only, as the reveal suffix is not part of the search's choice
+    let same_move = |mv: &str| mv.get(..4) == bestmove.get(..4);
+    let stable_from_depth = depth_moves
+        .iter()
+        .rposition(|(_, mv)| !same_move(mv))
+        .map_or(depth_moves.first(), |i| depth_moves.get(i + 1))
+        .map(|(depth, _)| *depth);
+    Ok(PositionResult {
+        fen: fen.to_string(),
+        bestmove,
+        depth: depth_times.last().map_or(0, |d| d.depth),
+        nodes,
+        time_ms,
+        nps: nps(nodes, time_ms),
+        depth_times,
+        best_move_changes,
+        stable_from_depth,
+    })
+}
+
+fn nps(nodes: u64, time_ms: u64) -> u64 {
+    nodes * 1000 / time_ms.max(1)
+}
@@ -0,0 +1,755 @@
+// src-tauri/src/bin/jieqibox-cli.rs
+// Headless entry point for batch jobs: engine matches, opening book maintenance,
+// notation checks, FEN and move checks, perft, reveal odds, game analysis and engine benchmarks, without starting the window.
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use jieqibox_lib::benchmark::{self, BenchmarkConfig, BenchmarkLimit, BenchmarkStore};
+use jieqibox_lib::encoding::EngineEncoding;
+use jieqibox_lib::engine_bridge::{self, ServedEngine};
+use jieqibox_lib::engine_discovery::{self, DiscoveryConfig};
+use jieqibox_lib::engine_registry;
+use jieqibox_lib::fen::Fen;
+use jieqibox_lib::hidden_pool;
+use jieqibox_lib::match_runner::{
+    self, EngineOptionValue, MatchConfig, MatchEngine, MatchEngineSpec, MatchEvent, MatchRunner, ProcessEngine,
+};
+use jieqibox_lib::notation::GameNotation;
+use jieqibox_lib::opening_book::{JieqiOpeningBook, OpeningBookEntry};
+use jieqibox_lib::perft::{self, RevealMode};
+use jieqibox_lib::reveal_odds::{self, RoleOdds};
+use jieqibox_lib::rules::{self, Board, PositionSummary};
+use jieqibox_lib::spawn_options::SpawnOptions;
+use jieqibox_lib::sprt::SprtDecision;
+use jieqibox_lib::ucci::EngineDialect;
+use jieqibox_lib::uci::{self, UciMessage};
+use std::fs;
+use std::net::TcpListener;
+use std::path::Path;
+use std::process::ExitCode;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Same file the desktop app uses, relative to the working directory.
+const DEFAULT_BOOK_PATH: &str = "jieqi_openings.jb";
+
+/// Same file the desktop app records probed engines in.
+const DEFAULT_REGISTRY_PATH: &str = "engine_registry.json";
+
+/// Same file the desktop app stores benchmark results in.
+const DEFAULT_BENCHMARK_PATH: &str = "benchmarks.json";
+
+#[derive(Parser)]
+#[command(name = "jieqibox-cli", version, about = "Run JieqiBox jobs without a window")]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Play an engine match described by a JSON match config
+    Match {
+        config: String,
+        /// Print every match event as a JSON line
+        #[arg(long)]
+        json: bool,
+    },
+    /// Query or import the opening book
+    Book {
+        #[command(subcommand)]
+        command: BookCommand,
+        #[arg(long, default_value = DEFAULT_BOOK_PATH, global = true)]
+        db: String,
+    },
+    /// Check or convert JSON game notation files
+    Notation {
+        #[command(subcommand)]
+        command: NotationCommand,
+    },
+    /// Check a FEN, listing every problem found in it, or rebuild its hidden pool
+    Fen {
+        fen: String,
+        /// Print the FEN with the hidden pool the rest of the position admits instead
+        #[arg(long)]
+        rebuild_pool: bool,
+        /// Revealed pieces that were captured, as a pool field such as `R1p2`, for `--rebuild-pool`
+        #[arg(long, requires = "rebuild_pool")]
+        captured_revealed: Option<String>,
+    },
+    /// List the legal moves of a position, or check one move
+    Moves {
+        /// Position to look at; the start position when omitted
+        #[arg(long, default_value = rules::START_FEN)]
+        fen: String,
+        /// Only check this move, failing when it is illegal
+        #[arg(long = "check")]
+        check: Option<String>,
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Count move paths from a position, or check the generator against reference counts
+    Perft {
+        /// Position to count from; the start position when omitted
+        #[arg(long, default_value = rules::START_FEN)]
+        fen: String,
+        /// Plies to look ahead
+        #[arg(long, default_value_t = 2)]
+        depth: u32,
+        /// Count a flip as one move instead of one per identity left in the hidden pool
+        #[arg(long)]
+        single_reveal: bool,
+        /// Run the reference positions up to `--depth` plies instead, failing on a mismatch
+        #[arg(long)]
+        reference: bool,
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show what dark pieces may turn out to be, and what captures involving them reveal
+    Odds {
+        /// Position to look at; the start position when omitted
+        #[arg(long, default_value = rules::START_FEN)]
+        fen: String,
+        /// Only show this capture
+        #[arg(long = "move")]
+        uci_move: Option<String>,
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Serve an engine over TCP to JieqiBox instances on other machines
+    Serve {
+        #[command(flatten)]
+        process: ProcessArgs,
+        /// Address to listen on; use 0.0.0.0 to accept connections from other machines
+        #[arg(long, default_value = "127.0.0.1:7650")]
+        listen: String,
+        /// Token clients must present
+        #[arg(long, env = "JIEQIBOX_BRIDGE_TOKEN", hide_env_values = true)]
+        token: String,
+    },
+    /// Analyse every move of a game with an engine
+    Analyse {
+        file: String,
+        #[command(flatten)]
+        engine: EngineArgs,
+        #[arg(long, conflicts_with = "movetime")]
+        depth: Option<u32>,
+        #[arg(long, default_value_t = 1000)]
+        movetime: u64,
+    },
+    /// Find engines in directories and probe them
+    Discover {
+        /// Directory to scan instead of the default ones (repeatable)
+        #[arg(long = "dir")]
+        dirs: Vec<String>,
+        /// Levels of subdirectories to scan
+        #[arg(long, default_value_t = 1)]
+        depth: u32,
+        /// Handshake timeout per engine, in milliseconds
+        #[arg(long, default_value_t = 2000)]
+        timeout: u64,
+        /// Engine registry to read and update
+        #[arg(long, default_value = DEFAULT_REGISTRY_PATH)]
+        registry: String,
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Benchmark an engine on a fixed set of positions and store the result
+    Bench {
+        #[command(flatten)]
+        engine: EngineArgs,
+        #[arg(long, conflicts_with = "movetime")]
+        depth: Option<u32>,
+        #[arg(long, default_value_t = 5000)]
+        movetime: u64,
+        /// Position to search instead of the built-in set (repeatable)
+        #[arg(long = "fen")]
+        fens: Vec<String>,
+        /// Results file to add the run to
+        #[arg(long, default_value = DEFAULT_BENCHMARK_PATH)]
+        store: String,
+        /// Do not store the run
+        #[arg(long)]
+        no_store: bool,
+        /// Print the result as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// An engine started from the command line and talked to directly.
+#[derive(Args)]
+struct EngineArgs {
+    #[command(flatten)]
+    process: ProcessArgs,
+    /// Engine option as NAME=VALUE (repeatable)
+    #[arg(long = "option")]
+    options: Vec<String>,
+    /// Engine I/O encoding: system, utf8, gbk, gb18030, big5 or auto
+    #[arg(long, default_value = "system", value_parser = parse_encoding)]
+    encoding: EngineEncoding,
+    /// Engine protocol: native (UCI) or ucci
+    #[arg(long, default_value = "native", value_parser = parse_dialect)]
+    dialect: EngineDialect,
+}
+
+/// How to start an engine process.
+#[derive(Args)]
+struct ProcessArgs {
+    #[arg(long = "engine")]
+    path: String,
+    /// Argument passed to the engine (repeatable)
+    #[arg(long = "arg", allow_hyphen_values = true)]
+    args: Vec<String>,
+    /// Environment variable as NAME=VALUE (repeatable)
+    #[arg(long = "env")]
+    env: Vec<String>,
+    /// Working directory instead of the engine's own directory
+    #[arg(long)]
+    cwd: Option<String>,
+    /// Nice level (Linux)
+    #[arg(long, allow_negative_numbers = true)]
+    nice: Option<i32>,
+    /// Comma-separated CPUs to pin the engine to (Linux)
+    #[arg(long, value_delimiter = ',')]
+    cpus: Option<Vec<usize>>,
+}
+
+impl ProcessArgs {
+    fn to_spawn_options(&self) -> Result<SpawnOptions, String> {
+        let mut env = std::collections::BTreeMap::new();
+        for var in &self.env {
+            let (name, value) = var.split_once('=').ok_or_else(|| format!("Variable '{}' is not NAME=VALUE.", var))?;
+            env.insert(name.to_string(), value.to_string());
+        }
+        Ok(SpawnOptions {
+            env,
+            cwd: self.cwd.clone(),
+            nice: self.nice,
+            cpu_affinity: self.cpus.clone(),
+        })
+    }
+}
+
+#[derive(Subcommand)]
+enum BookCommand {
+    /// List the book moves for a position
+    Query { fen: String },
+    /// Import entries from a JSON export
+    Import { file: String },
+}
+
+#[derive(Subcommand)]
+enum NotationCommand {
+    /// Replay a game and check every move and recorded position
+    Validate { file: String },
+    /// Convert a game to another format
+    Convert {
+        file: String,
+        #[arg(long, value_enum, default_value_t = NotationFormat::Text)]
+        to: NotationFormat,
+        /// Write to this file instead of stdout
+        #[arg(long)]
+        output: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum NotationFormat {
+    /// PGN-style tag pairs and numbered moves
+    Text,
+    /// JieqiBox JSON with every FEN in the new field layout
+    Json,
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Commands::Match { config, json } => run_match(&config, json),
+        Commands::Book { command, db } => match command {
+            BookCommand::Query { fen } => book_query(&db, &fen),
+            BookCommand::Import { file } => book_import(&db, &file),
+        },
+        Commands::Notation { command } => match command {
+            NotationCommand::Validate { file } => notation_validate(&file),
+            NotationCommand::Convert { file, to, output } => notation_convert(&file, to, output.as_deref()),
+        },
+        Commands::Fen {
+            fen,
+            rebuild_pool,
+            captured_revealed,
+        } => {
+            if rebuild_pool {
+                hidden_pool::reconstruct(&fen, captured_revealed.as_deref()).map(|result| println!("{}", result.fen))
+            } else {
+                check_fen(&fen)
+            }
+        }
+        Commands::Moves { fen, check, json } => moves(&fen, check.as_deref(), json),
+        Commands::Perft {
+            fen,
+            depth,
+            single_reveal,
+            reference,
+            json,
+        } => {
+            if reference {
+                perft_reference(depth)
+            } else {
+                run_perft(&fen, depth, single_reveal, json)
+            }
+        }
+        Commands::Odds { fen, uci_move, json } => odds(&fen, uci_move.as_deref(), json),
+        Commands::Serve { process, listen, token } => serve(&process, &listen, token),
+        Commands::Analyse {
+            file,
+            engine,
+            depth,
+            movetime,
+        } => analyse(&file, &engine, depth, movetime),
+        Commands::Discover {
+            dirs,
+            depth,
+            timeout,
+            registry,
+            json,
+        } => discover(
+            DiscoveryConfig {
+                dirs,
+                depth,
+                timeout_ms: timeout,
+            },
+            &registry,
+            json,
+        ),
+        Commands::Bench {
+            engine,
+            depth,
+            movetime,
+            fens,
+            store,
+            no_store,
+            json,
+        } => bench(&engine, depth, movetime, fens, (!no_store).then_some(store.as_str()), json),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_encoding(value: &str) -> Result<EngineEncoding, String> {
+    parse_setting(value, "encoding")
+}
+
+fn parse_dialect(value: &str) -> Result<EngineDialect, String> {
+    parse_setting(value, "dialect")
+}
+
+/// Settings are accepted by the same lowercase names the match config uses.
+fn parse_setting<T: serde::de::DeserializeOwned>(value: &str, what: &str) -> Result<T, String> {
+    serde_json::from_value(serde_json::Value::String(value.to_lowercase())).map_err(|_| format!("unknown {} '{}'", what, value))
+}
+
+fn read_file(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))
+}
+
+fn run_match(config_path: &str, json: bool) -> Result<(), String> {
+    let config: MatchConfig = serde_json::from_str(&read_file(config_path)?).map_err(|e| format!("Invalid match config: {}", e))?;
+    let spawn = |spec: &MatchEngineSpec| ProcessEngine::spawn(&spec.path, &spec.args, spec.encoding, spec.dialect, &spec.spawn_options);
+    let engines: [Box<dyn MatchEngine>; 2] = [Box::new(spawn(&config.engines[0])?), Box::new(spawn(&config.engines[1])?)];
+    let names = [config.engines[0].name.clone(), config.engines[1].name.clone()];
+
+    let mut runner = MatchRunner::new(config, engines, Arc::new(AtomicBool::new(false)));
+    let score = runner.run(&mut |event| {
+        if json {
+            println!("{}", serde_json::json!({ "event": event.event_name(), "data": event }));
+            return;
+        }
+        match event {
+            MatchEvent::Move(_) => {}
+            MatchEvent::Game(game) => println!(
+                "Game {} (pair {}): {} vs {}: {:?} ({}, {} plies)",
+                game.game,
+                game.pair,
+                game.red,
+                game.black,
+                game.outcome,
+                game.reason,
+                game.moves.len()
+            ),
+            MatchEvent::Score(score) => {
+                println!(
+                    "Score of {} vs {}: +{} ={} -{}  ptnml {:?}",
+                    names[0], names[1], score.wins, score.draws, score.losses, score.ptnml
+                );
+                if let Some(sprt) = score.sprt {
+                    println!("LLR: {:.2} ({:.2}, {:.2})", sprt.llr, sprt.lower_bound, sprt.upper_bound);
+                }
+            }
+        }
+    })?;
+    if !json {
+        println!("Finished after {} games.", score.games);
+        match score.sprt.and_then(|sprt| sprt.decision) {
+            Some(SprtDecision::AcceptH0) => println!("SPRT: H0 accepted"),
+            Some(SprtDecision::AcceptH1) => println!("SPRT: H1 accepted"),
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+fn book_query(db: &str, fen: &str) -> Result<(), String> {
+    let book = JieqiOpeningBook::new(db).map_err(|e| e.to_string())?;
+    let moves = book.query_moves(fen).map_err(|e| e.to_string())?;
+    if moves.is_empty() {
+        println!("No book moves.");
+    }
+    for m in moves {
+        println!(
+            "{}\tpriority {}\t+{} ={} -{}\t{}\t{}",
+            m.uci_move,
+            m.priority,
+            m.wins,
+            m.draws,
+            m.losses,
+            if m.allowed { "allowed" } else { "disallowed" },
+            m.comment
+        );
+    }
+    Ok(())
+}
+
+fn book_import(db: &str, file: &str) -> Result<(), String> {
+    let entries: Vec<OpeningBookEntry> = serde_json::from_str(&read_file(file)?).map_err(|e| e.to_string())?;
+    let book = JieqiOpeningBook::new(db).map_err(|e| e.to_string())?;
+    let (imported, errors) = book.import_entries(entries);
+    for error in &errors {
+        eprintln!("{}", error);
+    }
+    println!("Imported {} moves, {} failed.", imported, errors.len());
+    Ok(())
+}
+
+fn notation_validate(file: &str) -> Result<(), String> {
+    let notation = GameNotation::from_json(&read_file(file)?)?;
+    let moves = notation.replay()?;
+    println!("{}: {} moves, all legal and consistent.", file, moves.len());
+    Ok(())
+}
+
+fn notation_convert(file: &str, to: NotationFormat, output: Option<&str>) -> Result<(), String> {
+    let mut notation = GameNotation::from_json(&read_file(file)?)?;
+    let converted = match to {
+        NotationFormat::Text => notation.to_text()?,
+        NotationFormat::Json => {
+            notation.normalize_fens()?;
+            notation.to_json()? + "\n"
+        }
+    };
+    match output {
+        Some(path) => fs::write(path, converted).map_err(|e| format!("Failed to write {}: {}", path, e)),
+        None => {
+            print!("{}", converted);
+            Ok(())
+        }
+    }
+}
+
+fn check_fen(fen: &str) -> Result<(), String> {
+    let problems = Fen::parse(fen)?.problems();
+    if problems.is_empty() {
+        println!("The FEN is valid.");
+        return Ok(());
+    }
+    for problem in &problems {
+        println!("{}", problem);
+    }
+    Err(format!("{} problem(s) found.", problems.len()))
+}
+
+fn moves(fen: &str, check: Option<&str>, json: bool) -> Result<(), String> {
+    if let Some(uci) = check {
+        rules::validate_move(fen, uci)?;
+        println!("{} is legal.", uci);
+        return Ok(());
+    }
+    let summary = PositionSummary::of(&Board::from_fen(fen)?);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&summary).map_err(|e| e.to_string())?);
+        return Ok(());
+    }
+    println!(
+        "{} to move{}, {} legal moves:",
+        summary.side_to_move.name(),
+        if summary.in_check { ", in check" } else { "" },
+        summary.legal_moves.len()
+    );
+    println!("{}", summary.legal_moves.join(" "));
+    Ok(())
+}
+
+fn run_perft(fen: &str, depth: u32, single_reveal: bool, json: bool) -> Result<(), String> {
+    let mode = if single_reveal { RevealMode::Single } else { RevealMode::Expand };
+    let result = perft::perft(fen, depth, mode)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result).map_err(|e| e.to_string())?);
+        return Ok(());
+    }
+    for entry in &result.moves {
+        println!("{}: {}", entry.uci_move, entry.counts.nodes);
+    }
+    let totals = result.totals;
+    println!(
+        "depth {}: {} nodes ({} reveals, {} plain, {} captures, {} checks) in {} ms",
+        depth, totals.nodes, totals.reveals, totals.plain, totals.captures, totals.checks, result.time_ms
+    );
+    Ok(())
+}
+
+fn odds(fen: &str, uci_move: Option<&str>, json: bool) -> Result<(), String> {
+    let result = reveal_odds::reveal_odds(fen, uci_move)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result).map_err(|e| e.to_string())?);
+        return Ok(());
+    }
+    let format_odds = |odds: &[RoleOdds]| {
+        odds.iter()
+            .map(|o| format!("{} {:.1}%", o.piece, o.probability * 100.0))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    for side in &result.sides {
+        println!(
+            "{}: {} dark, {} in the pool: {}",
+            side.side.name(),
+            side.dark_pieces,
+            side.pool_size,
+            format_odds(&side.odds)
+        );
+    }
+    for capture in &result.captures {
+        let mut parts = Vec::new();
+        if let Some(odds) = &capture.mover {
+            parts.push(format!("mover {}", format_odds(odds)));
+        }
+        if let Some(odds) = &capture.captured {
+            parts.push(format!("captured {}", format_odds(odds)));
+        }
+        let reveals = if parts.is_empty() { "nothing dark".to_string() } else { parts.join("; ") };
+        println!("{}: {}", capture.uci_move, reveals);
+    }
+    Ok(())
+}
+
+fn perft_reference(max_depth: u32) -> Result<(), String> {
+    let mut failed = 0;
+    for (reference, found) in perft::check_references(max_depth) {
+        let mode = match reference.mode {
+            RevealMode::Expand => "expand",
+            RevealMode::Single => "single",
+        };
+        match found {
+            Ok(nodes) if nodes == reference.nodes => {
+                println!("ok    {} ({}, depth {}): {}", reference.name, mode, reference.depth, nodes);
+            }
+            Ok(nodes) => {
+                failed += 1;
+                println!(
+                    "FAIL  {} ({}, depth {}): expected {}, got {}",
+                    reference.name, mode, reference.depth, reference.nodes, nodes
+                );
+            }
+            Err(e) => {
+                failed += 1;
+                println!("FAIL  {} ({}, depth {}): {}", reference.name, mode, reference.depth, e);
+            }
+        }
+    }
+    if failed > 0 {
+        return Err(format!("{} reference position(s) did not match.", failed));
+    }
+    Ok(())
+}
+
+fn serve(process: &ProcessArgs, listen: &str, token: String) -> Result<(), String> {
+    let engine = ServedEngine {
+        path: process.path.clone(),
+        args: process.args.clone(),
+        options: process.to_spawn_options()?,
+    };
+    let listener = TcpListener::bind(listen).map_err(|e| format!("Failed to listen on {}: {}", listen, e))?;
+    let address = listener.local_addr().map_err(|e| e.to_string())?;
+    eprintln!("Serving {} on {}", engine.path, address);
+    engine_bridge::serve(listener, token, engine, Arc::new(|line| eprintln!("{}", line)))
+}
+
+fn analyse(file: &str, spec: &EngineArgs, depth: Option<u32>, movetime: u64) -> Result<(), String> {
+    const SETUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+    let notation = GameNotation::from_json(&read_file(file)?)?;
+    let moves = notation.replay()?;
+    let mut engine = ProcessEngine::spawn(
+        &spec.process.path,
+        &spec.process.args,
+        spec.encoding,
+        spec.dialect,
+        &spec.process.to_spawn_options()?,
+    )?;
+    engine.send("uci")?;
+    match_runner::wait_for(&mut engine, "uciok", SETUP_TIMEOUT)?;
+    for option in &spec.options {
+        let (name, value) = option.split_once('=').ok_or_else(|| format!("Option '{}' is not NAME=VALUE.", option))?;
+        engine.send(&format!("setoption name {} value {}", name, value))?;
+    }
+    engine.send("isready")?;
+    match_runner::wait_for(&mut engine, "readyok", SETUP_TIMEOUT)?;
+
+    let go = match depth {
+        Some(depth) => format!("go depth {}", depth),
+        None => format!("go movetime {}", movetime),
+    };
+    for mv in moves {
+        engine.send(&format!("position fen {}", mv.fen_before))?;
+        engine.send(&go)?;
+        let mut last_info = None;
+        let best = loop {
+            // No deadline: depth searches take as long as they take
+            let Some(line) = engine.recv(Duration::from_secs(1))? else {
+                continue;
+            };
+            match uci::parse_line(&line) {
+                Some(UciMessage::Info(info)) if info.multipv.unwrap_or(1) == 1 && (info.score_cp.is_some() || info.mate.is_some()) => {
+                    last_info = Some(info)
+                }
+                Some(UciMessage::BestMove(best)) => break best.bestmove,
+                _ => {}
+            }
+        };
+        let score = match &last_info {
+            Some(info) => match (info.score_cp, info.mate) {
+                (_, Some(mate)) => format!("mate {}", mate),
+                (Some(cp), _) => format!("cp {}", cp),
+                _ => "-".to_string(),
+            },
+            None => "-".to_string(),
+        };
+        let played = &mv.uci_move[..4];
+        println!(
+            "{}\t{}\tbest {}{}\t{}\tdepth {}",
+            mv.index + 1,
+            mv.uci_move,
+            best,
+            if best.get(..4) == Some(played) { "" } else { " (differs)" },
+            score,
+            last_info.and_then(|i| i.depth).map_or("-".to_string(), |d| d.to_string())
+        );
+    }
+    Ok(())
+}
+
+fn discover(config: DiscoveryConfig, registry: &str, json: bool) -> Result<(), String> {
+    let result = engine_discovery::discover(Path::new(registry), &config)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result).map_err(|e| e.to_string())?);
+        return Ok(());
+    }
+    println!("Scanned {}", result.dirs.join(", "));
+    for candidate in &result.candidates {
+        println!("{}\t{:?}\t{}", candidate.name, candidate.profile.protocol, candidate.path);
+        for network in &candidate.networks {
+            println!("\tnetwork {}", network);
+        }
+        if let Some(option) = &candidate.network_option {
+            println!("\tset {} = {}", option.name, option.value);
+        }
+    }
+    for skipped in &result.skipped {
+        println!("skipped {}: {}", skipped.path, skipped.reason);
+    }
+    println!("Found {} engines.", result.candidates.len());
+    Ok(())
+}
+
+fn bench(spec: &EngineArgs, depth: Option<u32>, movetime: u64, positions: Vec<String>, store: Option<&str>, json: bool) -> Result<(), String> {
+    let limit = match depth {
+        Some(depth) => BenchmarkLimit::Depth { depth },
+        None => BenchmarkLimit::MoveTime { movetime_ms: movetime },
+    };
+    let mut options = Vec::new();
+    for option in &spec.options {
+        let (name, value) = option.split_once('=').ok_or_else(|| format!("Option '{}' is not NAME=VALUE.", option))?;
+        options.push(EngineOptionValue {
+            name: name.to_string(),
+            value: value.to_string(),
+        });
+    }
+    let name = Path::new(&spec.process.path)
+        .file_name()
+        .map_or_else(|| spec.process.path.clone(), |n| n.to_string_lossy().into_owned());
+    let config = BenchmarkConfig {
+        engine: MatchEngineSpec {
+            name,
+            path: spec.process.path.clone(),
+            args: spec.process.args.clone(),
+            options,
+            encoding: spec.encoding,
+            spawn_options: spec.process.to_spawn_options()?,
+            dialect: spec.dialect,
+        },
+        limit,
+        positions,
+    };
+    let hash = engine_registry::hash_file(Path::new(&config.engine.path))?;
+    let mut engine = ProcessEngine::spawn(
+        &config.engine.path,
+        &config.engine.args,
+        config.engine.encoding,
+        config.engine.dialect,
+        &config.engine.spawn_options,
+    )?;
+
+    let result = benchmark::run(&mut engine, &config, hash, &AtomicBool::new(false), &mut |index, position| {
+        if json {
+            return;
+        }
+        let depths: Vec<String> = position.depth_times.iter().map(|d| format!("{}:{}", d.depth, d.time_ms)).collect();
+        println!(
+            "{}\tbest {}\tdepth {}\tnodes {}\t{} ms\t{} nps\tchanges {}\tstable from {}\tdepth:ms {}",
+            index + 1,
+            position.bestmove,
+            position.depth,
+            position.nodes,
+            position.time_ms,
+            position.nps,
+            position.best_move_changes,
+            position.stable_from_depth.map_or("-".to_string(), |d| d.to_string()),
+            depths.join(" ")
+        );
+    })?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&result).map_err(|e| e.to_string())?);
+    } else {
+        let totals = &result.totals;
+        println!(
+            "Total: {} positions, {} nodes, {} ms, {} nps, {} best move changes, {} stable by half depth",
+            result.positions.len(),
+            totals.nodes,
+            totals.time_ms,
+            totals.nps,
+            totals.best_move_changes,
+            totals.stable_positions
+        );
+    }
+    if let Some(store) = store {
+        BenchmarkStore::record(Path::new(store), result)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,524 @@
+// src-tauri/src/clock.rs
+// Game clocks. Time is measured here with monotonic instants and the clock is driven by a
+// background task, so it stays exact while the webview is hidden and its timers throttled;
+// the frontend only draws the `clock-tick` snapshots and reacts to `clock-flag`.
+use crate::rules::Side;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How often a running clock is checked and a `clock-tick` is emitted.
+const TICK: Duration = Duration::from_millis(100);
+
+/// How a game is timed.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ClockControl {
+    SuddenDeath { base_ms: u64 },
+    /// `increment_ms` is added after every move.
+    Fischer { base_ms: u64, increment_ms: u64 },
+    /// `base_ms` is added again after every `moves` moves of a side.
+    MovesPerSession {
+        base_ms: u64,
+        moves: u32,
+        #[serde(default)]
+        increment_ms: u64,
+    },
+    /// Once the main time is used up, every move must be made within `period_ms`; a period
+    /// that is overrun is lost, and overrunning the last one loses the game.
+    ByoYomi { base_ms: u64, period_ms: u64, periods: u32 },
+    /// A fixed time for every move.
+    MoveTime { movetime_ms: u64 },
+    /// Untimed: the engine searches to a fixed depth.
+    Depth { depth: u32 },
+    /// Untimed: the engine searches a fixed number of nodes.
+    Nodes { nodes: u64 },
+}
+
+impl ClockControl {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            ClockControl::MovesPerSession { moves: 0, .. } => Err("A session needs at least one move.".to_string()),
+            ClockControl::ByoYomi { period_ms: 0, periods, .. } if periods > 0 => {
+                Err("Byo-yomi periods must be longer than 0 ms.".to_string())
+            }
+            ClockControl::MoveTime { movetime_ms: 0 } => Err("The move time must be longer than 0 ms.".to_string()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn is_timed(&self) -> bool {
+        !matches!(self, ClockControl::Depth { .. } | ClockControl::Nodes { .. })
+    }
+
+    /// Time a side starts with: its main time, or the time of one move.
+    fn initial_ms(&self) -> i64 {
+        match *self {
+            ClockControl::SuddenDeath { base_ms }
+            | ClockControl::Fischer { base_ms, .. }
+            | ClockControl::MovesPerSession { base_ms, .. }
+            | ClockControl::ByoYomi { base_ms, .. } => base_ms as i64,
+            ClockControl::MoveTime { movetime_ms } => movetime_ms as i64,
+            ClockControl::Depth { .. } | ClockControl::Nodes { .. } => 0,
+        }
+    }
+}
+
+/// Time left on one side of the clock.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct SideClock {
+    /// Main time, or the current period once in byo-yomi. Negative after a flag fall.
+    pub time_ms: i64,
+    /// Byo-yomi periods left, including the current one.
+    pub periods: u32,
+    pub in_byoyomi: bool,
+    /// Moves completed.
+    pub moves: u32,
+}
+
+/// Snapshot of a clock, the payload of `clock-tick`.
+#[derive(Clone, Debug, Serialize)]
+pub struct ClockState {
+    pub control: ClockControl,
+    pub red: SideClock,
+    pub black: SideClock,
+    pub to_move: Side,
+    pub running: bool,
+    /// Side that ran out of time, if any; the clock stops when a flag falls.
+    pub flagged: Option<Side>,
+}
+
+/// Payload of `clock-flag`, emitted once when a side runs out of time.
+#[derive(Clone, Debug, Serialize)]
+pub struct ClockFlag {
+    pub side: Side,
+}
+
+/// A two-sided game clock.
+#[derive(Clone, Debug)]
+pub struct GameClock {
+    control: ClockControl,
+    /// Overrun tolerated before a flag falls, for engines answering over a pipe.
+    margin_ms: i64,
+    /// Per side (`Side::index`), as of the last press or pause.
+    sides: [SideClock; 2],
+    /// Moves made in the current session of a moves-per-session control.
+    session_moves: [u32; 2],
+    to_move: Side,
+    running_since: Option<Instant>,
+    flagged: Option<Side>,
+}
+
+impl GameClock {
+    /// A stopped clock with `to_move` to play first.
+    pub fn new(control: ClockControl, to_move: Side, margin_ms: u64) -> Self {
+        let periods = match control {
+            ClockControl::ByoYomi { periods, .. } => periods,
+            _ => 0,
+        };
+        let side = SideClock {
+            time_ms: control.initial_ms(),
+            periods,
+            in_byoyomi: false,
+            moves: 0,
+        };
+        GameClock {
+            control,
+            margin_ms: margin_ms as i64,
+            sides: [side; 2],
+            session_moves: [0; 2],
+            to_move,
+            running_since: None,
+            flagged: None,
+        }
+    }
+
+    pub fn control(&self) -> &ClockControl {
+        &self.control
+    }
+
+    pub fn to_move(&self) -> Side {
+        self.to_move
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    pub fn flagged(&self) -> Option<Side> {
+        self.flagged
+    }
+
+    /// Start (or resume) the clock of the side to move.
+    pub fn start(&mut self, now: Instant) {
+        if self.flagged.is_none() && self.control.is_timed() && self.running_since.is_none() {
+            self.running_since = Some(now);
+        }
+    }
+
+    /// Stop the clock, keeping the time used so far by the side to move.
+    pub fn pause(&mut self, now: Instant) {
+        if let Some(since) = self.running_since.take() {
+            let side = self.to_move.index();
+            self.sides[side] = self.spend(self.sides[side], elapsed_ms(since, now));
+        }
+    }
+
+    /// The side to move has moved: charge its time, add what the control gives back and
+    /// switch sides. Returns how long the move took, or `None` if the side was not to move
+    /// or its flag fell (see `flagged`).
+    pub fn press(&mut self, side: Side, now: Instant) -> Option<u64> {
+        if side != self.to_move || self.flagged.is_some() {
+            return None;
+        }
+        let index = side.index();
+        let elapsed = self.running_since.map_or(0, |since| elapsed_ms(since, now));
+        let mut clock = self.spend(self.sides[index], elapsed);
+        if self.is_flag(&clock) {
+            self.sides[index] = clock;
+            self.flag(side);
+            return None;
+        }
+
+        clock.moves += 1;
+        match self.control {
+            ClockControl::Fischer { increment_ms, .. } => clock.time_ms += increment_ms as i64,
+            ClockControl::MovesPerSession { base_ms, moves, increment_ms } => {
+                clock.time_ms += increment_ms as i64;
+                self.session_moves[index] += 1;
+                if self.session_moves[index] >= moves {
+                    self.session_moves[index] = 0;
+                    clock.time_ms += base_ms as i64;
+                }
+            }
+            ClockControl::ByoYomi { period_ms, .. } if clock.in_byoyomi => clock.time_ms = period_ms as i64,
+            ClockControl::MoveTime { movetime_ms } => clock.time_ms = movetime_ms as i64,
+            _ => {}
+        }
+        self.sides[index] = clock;
+        self.to_move = side.opponent();
+        if self.running_since.is_some() {
+            self.running_since = Some(now);
+        }
+        Some(elapsed)
+    }
+
+    /// Drop the flag of the side to move if its time has run out. Returns the flagged side.
+    pub fn check(&mut self, now: Instant) -> Option<Side> {
+        if self.flagged.is_none() {
+            if let Some(since) = self.running_since {
+                let clock = self.spend(self.sides[self.to_move.index()], elapsed_ms(since, now));
+                if self.is_flag(&clock) {
+                    self.sides[self.to_move.index()] = clock;
+                    self.flag(self.to_move);
+                }
+            }
+        }
+        self.flagged
+    }
+
+    /// Time the side to move has before its flag falls, margin included; `None` when untimed.
+    pub fn time_to_flag(&self, now: Instant) -> Option<i64> {
+        if !self.control.is_timed() {
+            return None;
+        }
+        let clock = self.current(self.to_move, now);
+        let mut left = clock.time_ms + self.margin_ms;
+        if let ClockControl::ByoYomi { period_ms, .. } = self.control {
+            let spare = if clock.in_byoyomi { clock.periods.saturating_sub(1) } else { clock.periods };
+            left += spare as i64 * period_ms as i64;
+        }
+        Some(left.max(0))
+    }
+
+    /// Search limits for the side to move, as the arguments of a UCI `go` command.
+    pub fn go_params(&self, now: Instant) -> String {
+        let time = |side: Side| self.current(side, now).time_ms.max(0);
+        match self.control {
+            ClockControl::Depth { depth } => format!("depth {}", depth),
+            ClockControl::Nodes { nodes } => format!("nodes {}", nodes),
+            ClockControl::MoveTime { movetime_ms } => format!("movetime {}", movetime_ms),
+            ClockControl::SuddenDeath { .. } => format!("wtime {} btime {}", time(Side::Red), time(Side::Black)),
+            ClockControl::Fischer { increment_ms, .. } => format!(
+                "wtime {} btime {} winc {} binc {}",
+                time(Side::Red),
+                time(Side::Black),
+                increment_ms,
+                increment_ms
+            ),
+            ClockControl::MovesPerSession { moves, increment_ms, .. } => {
+                let mut params = format!("wtime {} btime {}", time(Side::Red), time(Side::Black));
+                if increment_ms > 0 {
+                    params.push_str(&format!(" winc {} binc {}", increment_ms, increment_ms));
+                }
+                let left = moves - self.session_moves[self.to_move.index()];
+                params.push_str(&format!(" movestogo {}", left));
+                params
+            }
+            // The main time alone, so a period is held in reserve; in byo-yomi, one period per move
+            ClockControl::ByoYomi { .. } => {
+                let mut params = format!("wtime {} btime {}", time(Side::Red), time(Side::Black));
+                if self.current(self.to_move, now).in_byoyomi {
+                    params.push_str(" movestogo 1");
+                }
+                params
+            }
+        }
+    }
+
+    pub fn state(&self, now: Instant) -> ClockState {
+        ClockState {
+            control: self.control.clone(),
+            red: self.current(Side::Red, now),
+            black: self.current(Side::Black, now),
+            to_move: self.to_move,
+            running: self.is_running(),
+            flagged: self.flagged,
+        }
+    }
+
+    /// A side's clock as of `now`, counting the running move.
+    fn current(&self, side: Side, now: Instant) -> SideClock {
+        let clock = self.sides[side.index()];
+        match self.running_since {
+            Some(since) if side == self.to_move => self.spend(clock, elapsed_ms(since, now)),
+            _ => clock,
+        }
+    }
+
+    /// Charge `elapsed` to a side's clock, moving into and through byo-yomi periods.
+    fn spend(&self, mut clock: SideClock, elapsed: u64) -> SideClock {
+        clock.time_ms -= elapsed as i64;
+        if let ClockControl::ByoYomi { period_ms, .. } = self.control {
+            while clock.time_ms < 0 && clock.periods > 0 {
+                if clock.in_byoyomi {
+                    clock.periods -= 1;
+                    if clock.periods == 0 {
+                        break;
+                    }
+                }
+                clock.in_byoyomi = true;
+                clock.time_ms += period_ms as i64;
+            }
+        }
+        clock
+    }
+
+    fn is_flag(&self, clock: &SideClock) -> bool {
+        self.control.is_timed() && clock.time_ms + self.margin_ms < 0
+    }
+
+    fn flag(&mut self, side: Side) {
+        self.flagged = Some(side);
+        self.running_since = None;
+    }
+}
+
+fn elapsed_ms(since: Instant, now: Instant) -> u64 {
+    now.saturating_duration_since(since).as_millis() as u64
+}
+
+/// Engine session whose `bestmove` replies press the clock for `side`, so engine moves are
+/// timed when the engine answers rather than when the frontend gets around to playing them.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ClockEngine {
+    pub session_id: String,
+    pub side: Side,
+}
+
+/// The clock of the game on the board, shared by the commands and the ticker.
+#[derive(Default)]
+pub struct ClockService {
+    clock: Mutex<Option<(GameClock, Option<ClockEngine>)>>,
+    /// Bumped for every new clock so the ticker of a replaced clock stops.
+    generation: AtomicU64,
+}
+
+impl ClockService {
+    /// Replace the clock with a new one and start it.
+    pub fn start(
+        &self,
+        app: &AppHandle,
+        control: ClockControl,
+        to_move: Side,
+        engine: Option<ClockEngine>,
+    ) -> Result<ClockState, String> {
+        control.validate()?;
+        let now = Instant::now();
+        let mut clock = GameClock::new(control, to_move, 0);
+        clock.start(now);
+        let state = clock.state(now);
+        *self.clock.lock().unwrap() = Some((clock, engine));
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        tauri::async_runtime::spawn(tick(app.clone(), generation));
+        Ok(state)
+    }
+
+    pub fn stop(&self) {
+        *self.clock.lock().unwrap() = None;
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Run `f` on the clock, if there is one, and return its state afterwards.
+    pub fn update(&self, f: impl FnOnce(&mut GameClock, Instant)) -> Option<ClockState> {
+        let now = Instant::now();
+        let mut guard = self.clock.lock().unwrap();
+        let (clock, _) = guard.as_mut()?;
+        f(clock, now);
+        Some(clock.state(now))
+    }
+
+    pub fn go_params(&self) -> Option<String> {
+        let guard = self.clock.lock().unwrap();
+        guard.as_ref().map(|(clock, _)| clock.go_params(Instant::now()))
+    }
+
+    /// An engine session replied with `bestmove` at `at`; press the clock if the session
+    /// plays the side to move.
+    pub fn engine_moved(&self, app: &AppHandle, session_id: &str, at: Instant) {
+        let mut guard = self.clock.lock().unwrap();
+        let Some((clock, Some(engine))) = guard.as_mut() else {
+            return;
+        };
+        if engine.session_id == session_id && clock.is_running() && clock.press(engine.side, at).is_some() {
+            let _ = app.emit("clock-tick", clock.state(Instant::now()));
+        }
+    }
+}
+
+/// Emit `clock-tick` while the clock of `generation` runs, and `clock-flag` when it falls.
+async fn tick(app: AppHandle, generation: u64) {
+    loop {
+        tokio::time::sleep(TICK).await;
+        let service = app.state::<ClockService>();
+        if service.generation.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        let now = Instant::now();
+        let mut guard = service.clock.lock().unwrap();
+        let Some((clock, _)) = guard.as_mut() else {
+            return;
+        };
+        // A flag can also fall on a press, which stops the clock before the check here
+        if clock.is_running() {
+            clock.check(now);
+        } else if clock.flagged().is_none() {
+            continue;
+        }
+        let state = clock.state(now);
+        drop(guard);
+        let flagged = state.flagged;
+        let _ = app.emit("clock-tick", state);
+        if let Some(side) = flagged {
+            let _ = app.emit("clock-flag", ClockFlag { side });
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A started clock and a function giving the instant `ms` after its start.
+    fn started(control: ClockControl, margin_ms: u64) -> (GameClock, impl Fn(u64) -> Instant) {
+        let start = Instant::now();
+        let mut clock = GameClock::new(control, Side::Red, margin_ms);
+        clock.start(start);
+        (clock, move |ms| start + Duration::from_millis(ms))
+    }
+
+    #[test]
+    fn fischer_adds_the_increment_on_press() {
+        let (mut clock, at) = started(ClockControl::Fischer { base_ms: 60_000, increment_ms: 1_000 }, 0);
+        assert_eq!(clock.press(Side::Red, at(5_000)), Some(5_000));
+        assert_eq!(clock.press(Side::Red, at(6_000)), None);
+        assert_eq!(clock.to_move(), Side::Black);
+        let state = clock.state(at(7_000));
+        assert_eq!((state.red.time_ms, state.red.moves), (56_000, 1));
+        assert_eq!(state.black.time_ms, 58_000);
+        assert_eq!(clock.go_params(at(7_000)), "wtime 56000 btime 58000 winc 1000 binc 1000");
+    }
+
+    #[test]
+    fn moves_per_session_adds_the_base_time_at_the_boundary() {
+        let control = ClockControl::MovesPerSession { base_ms: 10_000, moves: 2, increment_ms: 0 };
+        let (mut clock, at) = started(control, 0);
+        assert_eq!(clock.go_params(at(0)), "wtime 10000 btime 10000 movestogo 2");
+        clock.press(Side::Red, at(1_000));
+        clock.press(Side::Black, at(2_000));
+        assert_eq!(clock.go_params(at(2_000)), "wtime 9000 btime 9000 movestogo 1");
+        clock.press(Side::Red, at(4_000));
+        assert_eq!(clock.state(at(4_000)).red.time_ms, 17_000);
+        clock.press(Side::Black, at(4_500));
+        assert_eq!(clock.go_params(at(4_500)), "wtime 17000 btime 18500 movestogo 2");
+    }
+
+    #[test]
+    fn byo_yomi_uses_and_resets_periods() {
+        let control = ClockControl::ByoYomi { base_ms: 1_000, period_ms: 500, periods: 2 };
+        let (mut clock, at) = started(control, 0);
+        assert_eq!(clock.time_to_flag(at(0)), Some(2_000));
+        assert_eq!(clock.go_params(at(0)), "wtime 1000 btime 1000");
+
+        // Into byo-yomi, with the period refilled after the move
+        clock.press(Side::Red, at(1_200));
+        let red = clock.state(at(1_200)).red;
+        assert_eq!((red.time_ms, red.periods, red.in_byoyomi), (500, 2, true));
+        clock.press(Side::Black, at(1_300));
+        assert_eq!(clock.go_params(at(1_300)), "wtime 500 btime 900 movestogo 1");
+        assert_eq!(clock.time_to_flag(at(1_300)), Some(1_000));
+
+        // Overrunning a period loses it
+        clock.press(Side::Red, at(2_000));
+        let red = clock.state(at(2_000)).red;
+        assert_eq!((red.time_ms, red.periods), (500, 1));
+        clock.press(Side::Black, at(2_000));
+        assert_eq!(clock.press(Side::Red, at(2_600)), None);
+        assert_eq!(clock.flagged(), Some(Side::Red));
+    }
+
+    #[test]
+    fn flag_falls_after_the_margin() {
+        let (mut clock, at) = started(ClockControl::SuddenDeath { base_ms: 1_000 }, 100);
+        assert_eq!(clock.check(at(1_050)), None);
+        assert_eq!(clock.time_to_flag(at(1_050)), Some(50));
+        assert_eq!(clock.check(at(1_101)), Some(Side::Red));
+        assert!(!clock.is_running());
+        assert_eq!(clock.state(at(2_000)).red.time_ms, -101);
+        assert_eq!(clock.time_to_flag(at(2_000)), Some(0));
+        assert_eq!(clock.press(Side::Red, at(2_000)), None);
+
+        let (mut clock, at) = started(ClockControl::SuddenDeath { base_ms: 1_000 }, 0);
+        assert_eq!(clock.check(at(1_001)), Some(Side::Red));
+    }
+
+    #[test]
+    fn go_params_per_control() {
+        let go = |control: ClockControl| {
+            let (clock, at) = started(control, 0);
+            clock.go_params(at(250))
+        };
+        assert_eq!(go(ClockControl::SuddenDeath { base_ms: 5_000 }), "wtime 4750 btime 5000");
+        assert_eq!(go(ClockControl::Fischer { base_ms: 5_000, increment_ms: 100 }), "wtime 4750 btime 5000 winc 100 binc 100");
+        assert_eq!(
+            go(ClockControl::MovesPerSession { base_ms: 5_000, moves: 40, increment_ms: 100 }),
+            "wtime 4750 btime 5000 winc 100 binc 100 movestogo 40"
+        );
+        assert_eq!(go(ClockControl::ByoYomi { base_ms: 5_000, period_ms: 1_000, periods: 3 }), "wtime 4750 btime 5000");
+        assert_eq!(go(ClockControl::MoveTime { movetime_ms: 3_000 }), "movetime 3000");
+        assert_eq!(go(ClockControl::Depth { depth: 12 }), "depth 12");
+        assert_eq!(go(ClockControl::Nodes { nodes: 100_000 }), "nodes 100000");
+    }
+
+    #[test]
+    fn untimed_controls_never_run_or_flag() {
+        let (mut clock, at) = started(ClockControl::Depth { depth: 12 }, 0);
+        assert!(!clock.is_running());
+        assert_eq!(clock.time_to_flag(at(60_000)), None);
+        assert_eq!(clock.check(at(60_000)), None);
+        assert_eq!(clock.press(Side::Red, at(60_000)), Some(0));
+    }
+}
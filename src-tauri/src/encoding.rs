@@ -0,0 +1,173 @@
+// src-tauri/src/encoding.rs
+// Text encoding of an engine's pipes. Chinese engines commonly print GBK or Big5 rather
+// than UTF-8, whatever the platform, so the encoding is a per-engine setting.
+use encoding_rs::{Encoding, BIG5, GB18030, GBK, UTF_8};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EngineEncoding {
+    /// GBK on Windows, UTF-8 elsewhere, as before the setting existed.
+    #[default]
+    System,
+    Utf8,
+    Gbk,
+    Gb18030,
+    Big5,
+    /// Decided by the first output that is not plain ASCII.
+    Auto,
+}
+
+impl EngineEncoding {
+    fn fixed(self) -> Option<&'static Encoding> {
+        match self {
+            EngineEncoding::System if cfg!(target_os = "windows") => Some(GBK),
+            EngineEncoding::System | EngineEncoding::Utf8 => Some(UTF_8),
+            EngineEncoding::Gbk => Some(GBK),
+            EngineEncoding::Gb18030 => Some(GB18030),
+            EngineEncoding::Big5 => Some(BIG5),
+            EngineEncoding::Auto => None,
+        }
+    }
+}
+
+/// Encoder and decoder for one engine process. With `Auto`, the encoding is settled by the
+/// first line that is not plain ASCII and kept for the rest of the process's life.
+pub struct EngineCodec {
+    detected: Mutex<Option<&'static Encoding>>,
+}
+
+impl EngineCodec {
+    pub fn new(setting: EngineEncoding) -> Self {
+        EngineCodec {
+            detected: Mutex::new(setting.fixed()),
+        }
+    }
+
+    pub fn decode(&self, bytes: &[u8]) -> String {
+        let encoding = match self.current() {
+            Some(encoding) => encoding,
+            None if bytes.is_ascii() => return String::from_utf8_lossy(bytes).into_owned(),
+            None => {
+                let encoding = detect(bytes);
+                *self.detected.lock().unwrap() = Some(encoding);
+                encoding
+            }
+        };
+        let (text, ..) = encoding.decode(bytes);
+        text.into_owned()
+    }
+
+    /// Encode a command. Until `Auto` has settled on an encoding, commands go out as UTF-8.
+    pub fn encode(&self, text: &str) -> Vec<u8> {
+        let (bytes, ..) = self.current().unwrap_or(UTF_8).encode(text);
+        bytes.into_owned()
+    }
+
+    fn current(&self) -> Option<&'static Encoding> {
+        *self.detected.lock().unwrap()
+    }
+}
+
+/// Pick an encoding for `bytes`: a BOM wins, then UTF-8, whose byte patterns rarely occur by
+/// chance in other encodings. GB18030 decodes nearly every Big5 sequence without errors as
+/// well, so those two are compared by how many common Chinese characters each decoding
+/// yields, with ties going to GB18030.
+fn detect(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if UTF_8.decode_without_bom_handling_and_without_replacement(bytes).is_some() {
+        return UTF_8;
+    }
+    let score = |encoding: &'static Encoding| {
+        encoding
+            .decode_without_bom_handling_and_without_replacement(bytes)
+            .map(|text| text.chars().filter(|&c| is_common_hanzi(c)).count())
+    };
+    match (score(GB18030), score(BIG5)) {
+        (Some(gb), Some(big5)) if big5 > gb => BIG5,
+        (None, Some(_)) => BIG5,
+        _ => GB18030,
+    }
+}
+
+/// Whether `c` is a frequently used character: in GB2312's first level (simplified) or Big5's
+/// first level (traditional). Text decoded with the wrong one of the two mostly lands in
+/// symbols and rare characters instead.
+fn is_common_hanzi(c: char) -> bool {
+    let mut buf = [0u8; 4];
+    let text = c.encode_utf8(&mut buf);
+    let code = |encoding: &'static Encoding| match encoding.encode(text) {
+        (bytes, _, false) if bytes.len() == 2 => Some((bytes[0], bytes[1])),
+        _ => None,
+    };
+    code(GBK).is_some_and(|(lead, trail)| (0xB0..=0xD7).contains(&lead) && trail >= 0xA1)
+        || code(BIG5).is_some_and(|(lead, trail)| (0xA440..=0xC67E).contains(&u16::from_be_bytes([lead, trail])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auto_decode(bytes: &[u8]) -> (String, Option<&'static Encoding>) {
+        let codec = EngineCodec::new(EngineEncoding::Auto);
+        let text = codec.decode(bytes);
+        (text, codec.current())
+    }
+
+    #[test]
+    fn detects_utf8() {
+        assert_eq!(auto_decode("info string 搜索深度".as_bytes()), ("info string 搜索深度".to_string(), Some(UTF_8)));
+    }
+
+    #[test]
+    fn detects_gbk() {
+        for text in ["象棋引擎", "搜索深度", "红方胜", "info string 紅方勝"] {
+            let (bytes, ..) = GBK.encode(text);
+            assert_eq!(auto_decode(&bytes), (text.to_string(), Some(GB18030)), "{}", text);
+        }
+    }
+
+    #[test]
+    fn detects_big5() {
+        for text in ["象棋引擎", "搜索深度", "紅方勝", "info string 將帥"] {
+            let (bytes, ..) = BIG5.encode(text);
+            assert_eq!(auto_decode(&bytes), (text.to_string(), Some(BIG5)), "{}", text);
+        }
+    }
+
+    #[test]
+    fn bom_wins() {
+        let mut bytes = vec![0xEF, 0xBB, 0xBF];
+        bytes.extend("引擎".as_bytes());
+        assert_eq!(auto_decode(&bytes), ("引擎".to_string(), Some(UTF_8)));
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend("id".encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(detect(&bytes), encoding_rs::UTF_16LE);
+    }
+
+    #[test]
+    fn ascii_does_not_settle_auto() {
+        let codec = EngineCodec::new(EngineEncoding::Auto);
+        assert_eq!(codec.decode(b"id name Pikafish"), "id name Pikafish");
+        assert_eq!(codec.current(), None);
+        assert_eq!(codec.encode("setoption name 棋谱 value 1"), "setoption name 棋谱 value 1".as_bytes());
+    }
+
+    #[test]
+    fn encode_follows_detected_encoding() {
+        let codec = EngineCodec::new(EngineEncoding::Auto);
+        let (big5, ..) = BIG5.encode("紅方勝");
+        codec.decode(&big5);
+        assert_eq!(codec.encode("紅方勝"), big5.into_owned());
+
+        let codec = EngineCodec::new(EngineEncoding::Auto);
+        let (gbk, ..) = GBK.encode("红方胜");
+        codec.decode(&gbk);
+        assert_eq!(codec.encode("红方胜"), gbk.into_owned());
+
+        assert_eq!(EngineCodec::new(EngineEncoding::Big5).encode("將"), BIG5.encode("將").0.into_owned());
+    }
+}
@@ -0,0 +1,791 @@
+// src-tauri/src/engine.rs
+use crate::clock::ClockService;
+use crate::encoding::{EngineCodec, EngineEncoding};
+use crate::engine_bridge::{RemoteChild, RemoteEngine};
+use crate::fair_play::{self, FairPlayConfig};
+use crate::output_batch::{BatchConfig, OutputBatch};
+use crate::spawn_options::SpawnOptions;
+use crate::transcript::{Direction, Transcript, TranscriptConfig};
+use crate::ucci::{EngineDialect, UcciAdapter};
+use crate::uci::{self, UciEvent};
+use crate::watchdog::{self, WatchdogConfig};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::async_runtime::{self, Receiver};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+
+/// Number of stderr lines kept for the `engine-exited` report.
+const STDERR_TAIL_LINES: usize = 20;
+
+/// How an engine was started and configured, kept so the watchdog can start it again the same way.
+#[derive(Clone)]
+pub struct EngineLaunch {
+    pub path: String,
+    pub args: Vec<String>,
+    /// Last protocol handshake sent (`uci`, `jai`), replayed first after a restart.
+    pub handshake: Option<String>,
+    /// Last `setoption` command per option name, in the order they were sent.
+    pub options: Vec<(String, String)>,
+    /// Restarts since the engine last answered a health check.
+    pub restarts: u32,
+    pub encoding: EngineEncoding,
+    pub spawn: SpawnOptions,
+    /// Connect to an engine served over TCP instead of starting `path`.
+    pub remote: Option<RemoteEngine>,
+    pub dialect: EngineDialect,
+}
+
+impl EngineLaunch {
+    pub fn new(path: String, args: Vec<String>) -> Self {
+        EngineLaunch {
+            path,
+            args,
+            handshake: None,
+            options: Vec::new(),
+            restarts: 0,
+            encoding: EngineEncoding::default(),
+            spawn: SpawnOptions::default(),
+            remote: None,
+            dialect: EngineDialect::default(),
+        }
+    }
+}
+
+/// Protocol state of a running engine, followed from the commands sent to it and the lines it writes back.
+pub struct EngineHealth {
+    pub started: Instant,
+    pub last_activity: Instant,
+    /// The handshake was acknowledged (`uciok`, `jaiok`); no health checks are sent before that.
+    pub handshake_done: bool,
+    /// A `go` or a JAI match game is in progress.
+    pub busy: bool,
+    /// Unanswered `isready` commands, oldest first, flagged when sent by the watchdog.
+    pub pending_ready: VecDeque<(Instant, bool)>,
+    /// A restarted engine is replaying its handshake; its output is hidden until `uciok`/`jaiok`.
+    pub replaying: bool,
+}
+
+impl EngineHealth {
+    fn new() -> Self {
+        let now = Instant::now();
+        EngineHealth {
+            started: now,
+            last_activity: now,
+            handshake_done: false,
+            busy: false,
+            pending_ready: VecDeque::new(),
+            replaying: false,
+        }
+    }
+}
+
+/// The process behind a session: started locally, or served by a bridge over TCP.
+pub enum EngineChild {
+    Local(CommandChild),
+    Remote(RemoteChild),
+}
+
+impl EngineChild {
+    /// Local process id, or a made-up id for a remote engine.
+    pub fn pid(&self) -> u32 {
+        match self {
+            EngineChild::Local(child) => child.pid(),
+            EngineChild::Remote(child) => child.id(),
+        }
+    }
+
+    pub fn write(&mut self, buf: &[u8]) -> Result<(), String> {
+        match self {
+            EngineChild::Local(child) => child.write(buf).map_err(|e| e.to_string()),
+            EngineChild::Remote(child) => child.write(buf),
+        }
+    }
+
+    pub fn kill(self) -> Result<(), String> {
+        match self {
+            EngineChild::Local(child) => child.kill().map_err(|e| e.to_string()),
+            EngineChild::Remote(child) => child.kill(),
+        }
+    }
+}
+
+/// A running engine process owned by one session.
+pub struct EngineSession {
+    /// Identifies this particular process, so a late exit of a replaced
+    /// process cannot clear the slot of its successor.
+    pub pid: u32,
+    pub child: EngineChild,
+    pub launch: EngineLaunch,
+    pub health: EngineHealth,
+    /// Receives a copy of every stdout line forwarded to the frontend (used by the match runner).
+    pub tap: Option<Sender<String>>,
+    pub transcript: Option<Arc<Transcript>>,
+    /// Shared with the listener, so commands are encoded the way the output was detected.
+    pub codec: Arc<EngineCodec>,
+    /// Translates to and from UCCI for engines that speak it; everything above sees UCI.
+    pub adapter: Option<UcciAdapter>,
+}
+
+impl EngineSession {
+    /// Send a command from the frontend, recording what a restart needs to replay.
+    pub fn send(&mut self, command: &str) -> Result<(), String> {
+        let command = command.trim();
+        let mut tokens = command.split_whitespace();
+        match tokens.next() {
+            Some("uci") | Some("jai") => {
+                self.launch.handshake = Some(command.to_string());
+                self.health.handshake_done = false;
+            }
+            Some("setoption") => {
+                if let Some(name) = option_name(command) {
+                    self.launch.options.retain(|(n, _)| *n != name);
+                    self.launch.options.push((name, command.to_string()));
+                }
+            }
+            Some("go") | Some("startmatch") => self.health.busy = true,
+            Some("stop") => self.health.busy = false,
+            Some("isready") => self.health.pending_ready.push_back((Instant::now(), false)),
+            _ => {}
+        }
+        self.write_line(command)
+    }
+
+    /// Send an `isready` health check whose `readyok` is not forwarded to the frontend.
+    pub fn ping(&mut self) -> Result<(), String> {
+        self.health.pending_ready.push_back((Instant::now(), true));
+        self.write_line("isready")
+    }
+
+    /// Bring a restarted engine back to where the frontend left it:
+    /// handshake first, then (once it is acknowledged) the recorded options.
+    pub fn replay(&mut self) -> Result<(), String> {
+        match self.launch.handshake.clone() {
+            Some(handshake) => {
+                self.health.replaying = true;
+                self.write_line(&handshake)
+            }
+            None => self.replay_options(),
+        }
+    }
+
+    fn replay_options(&mut self) -> Result<(), String> {
+        for (_, command) in self.launch.options.clone() {
+            self.write_line(&command)?;
+        }
+        self.ping()
+    }
+
+    /// Update the protocol state from one stdout line.
+    /// Returns false for lines that must not reach the frontend.
+    fn observe(&mut self, line: &str) -> bool {
+        self.health.last_activity = Instant::now();
+        match line.split_whitespace().next() {
+            Some("uciok") | Some("jaiok") => {
+                self.health.handshake_done = true;
+                if self.health.replaying {
+                    self.health.replaying = false;
+                    let _ = self.replay_options();
+                    return false;
+                }
+            }
+            Some("readyok") => {
+                if let Some((_, true)) = self.health.pending_ready.pop_front() {
+                    self.launch.restarts = 0;
+                    return false;
+                }
+            }
+            Some("bestmove") => self.health.busy = false,
+            // A JAI match writes no `bestmove`: each game ends with `info result`, and the
+            // engine is idle from then until the next game starts (or for good after the last)
+            Some("info") if self.launch.handshake.as_deref() == Some("jai") => match line.split_whitespace().nth(1) {
+                Some("result") => self.health.busy = false,
+                Some("game") | Some("fen") | Some("move") => self.health.busy = true,
+                _ => {}
+            },
+            _ => {}
+        }
+        !self.health.replaying
+    }
+
+    fn write_line(&mut self, command: &str) -> Result<(), String> {
+        self.health.last_activity = Instant::now();
+        let lines = match &mut self.adapter {
+            Some(adapter) => adapter.to_engine(command),
+            None => vec![command.to_string()],
+        };
+        for line in lines {
+            if let Some(transcript) = &self.transcript {
+                transcript.log(Direction::ToEngine, &line);
+            }
+            self.child
+                .write(&self.codec.encode(&format!("{}\n", line)))
+                .map_err(|e| format!("Failed to write to engine: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Output line as the frontend should see it, translated from the engine's dialect.
+    fn translate(&mut self, line: String) -> Option<String> {
+        match &mut self.adapter {
+            Some(adapter) => adapter.from_engine(&line),
+            None => Some(line),
+        }
+    }
+}
+
+/// Name of the option set by a `setoption name <name> [value <value>]` command, lowercased
+/// because option names are case-insensitive.
+fn option_name(command: &str) -> Option<String> {
+    let tokens: Vec<&str> = command.split_whitespace().collect();
+    let start = tokens.iter().position(|t| *t == "name")? + 1;
+    let end = tokens.iter().position(|t| *t == "value").unwrap_or(tokens.len());
+    (start < end).then(|| tokens[start..end].join(" ").to_lowercase())
+}
+
+/// Registry of engine sessions keyed by the id the frontend chose for them.
+/// Several engines can run side by side as long as their ids differ.
+#[derive(Default)]
+pub struct EngineSessions {
+    sessions: Mutex<HashMap<String, EngineSession>>,
+    /// Watchdog settings per session id; they outlive the processes of the session until it
+    /// is closed (see `forget`).
+    watchdogs: Mutex<HashMap<String, WatchdogConfig>>,
+    /// Transcript settings per session id, applied whenever the session starts a process until
+    /// it is closed.
+    transcripts: Mutex<HashMap<String, TranscriptConfig>>,
+    /// Output batching settings per session id, read whenever a batch starts, until the session
+    /// is closed.
+    batching: Mutex<HashMap<String, BatchConfig>>,
+    /// Sessions whose `position` commands go through the fair-play filter, until they are closed.
+    fair_play: Mutex<HashMap<String, FairPlayConfig>>,
+}
+
+impl EngineSessions {
+    /// Store a session, returning the one it replaced (if any) so the caller can kill it.
+    pub fn insert(&self, session_id: &str, session: EngineSession) -> Option<EngineSession> {
+        self.sessions.lock().unwrap().insert(session_id.to_string(), session)
+    }
+
+    /// Remove a session from the registry without touching its process.
+    pub fn remove(&self, session_id: &str) -> Option<EngineSession> {
+        self.sessions.lock().unwrap().remove(session_id)
+    }
+
+    /// Remove a session only if it still holds the process with the given pid.
+    /// Returns `None` when the process was already killed or replaced.
+    pub fn remove_if_current(&self, session_id: &str, pid: u32) -> Option<EngineSession> {
+        let mut sessions = self.sessions.lock().unwrap();
+        if sessions.get(session_id).is_some_and(|session| session.pid == pid) {
+            sessions.remove(session_id)
+        } else {
+            None
+        }
+    }
+
+    /// Run `f` on a session if it still holds the process with the given pid.
+    pub fn with_current<R>(&self, session_id: &str, pid: u32, f: impl FnOnce(&mut EngineSession) -> R) -> Option<R> {
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions
+            .get_mut(session_id)
+            .filter(|session| session.pid == pid)
+            .map(f)
+    }
+
+    /// Send a command line to a session's engine. With fair play on, a `position` command is
+    /// rewritten to what the engine's side may know, or rejected when it cannot be checked.
+    pub fn send(&self, session_id: &str, command: &str) -> Result<(), String> {
+        let command = match self.fair_play(session_id) {
+            Some(config) if command.trim_start().starts_with("position") => fair_play::filter_position(command, config)?,
+            _ => command.to_string(),
+        };
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| format!("Engine session '{}' is not running.", session_id))?;
+        session.send(&command)
+    }
+
+    /// Copy the stdout lines of a running session into `tap` from now on.
+    pub fn set_tap(&self, session_id: &str, tap: Sender<String>) -> Result<(), String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .get_mut(session_id)
+            .ok_or_else(|| format!("Engine session '{}' is not running.", session_id))?;
+        session.tap = Some(tap);
+        Ok(())
+    }
+
+    pub fn watchdog(&self, session_id: &str) -> WatchdogConfig {
+        self.watchdogs.lock().unwrap().get(session_id).copied().unwrap_or_default()
+    }
+
+    pub fn set_watchdog(&self, session_id: &str, config: WatchdogConfig) {
+        self.watchdogs.lock().unwrap().insert(session_id.to_string(), config);
+    }
+
+    /// Drop the settings of a session that was closed or whose engine exited on its own.
+    /// They are set again when the session starts its next engine.
+    pub fn forget(&self, session_id: &str) {
+        self.watchdogs.lock().unwrap().remove(session_id);
+        self.transcripts.lock().unwrap().remove(session_id);
+        self.batching.lock().unwrap().remove(session_id);
+        self.fair_play.lock().unwrap().remove(session_id);
+    }
+
+    pub fn batching(&self, session_id: &str) -> BatchConfig {
+        self.batching.lock().unwrap().get(session_id).copied().unwrap_or_default()
+    }
+
+    pub fn set_batching(&self, session_id: &str, config: BatchConfig) {
+        self.batching.lock().unwrap().insert(session_id.to_string(), config);
+    }
+
+    pub fn fair_play(&self, session_id: &str) -> Option<FairPlayConfig> {
+        self.fair_play.lock().unwrap().get(session_id).copied()
+    }
+
+    /// Turn the fair-play filter of a session on (`Some`) or off.
+    pub fn set_fair_play(&self, session_id: &str, config: Option<FairPlayConfig>) {
+        let mut fair_play = self.fair_play.lock().unwrap();
+        match config {
+            Some(config) => fair_play.insert(session_id.to_string(), config),
+            None => fair_play.remove(session_id),
+        };
+    }
+
+    pub fn transcript(&self, session_id: &str) -> Option<TranscriptConfig> {
+        self.transcripts.lock().unwrap().get(session_id).cloned()
+    }
+
+    /// Turn transcripts of a session on (`Some`) or off. Takes effect at the next spawn.
+    pub fn set_transcript(&self, session_id: &str, config: Option<TranscriptConfig>) {
+        let mut transcripts = self.transcripts.lock().unwrap();
+        match config {
+            Some(config) => transcripts.insert(session_id.to_string(), config),
+            None => transcripts.remove(session_id),
+        };
+    }
+
+    /// Keep the lines of a process that should reach the frontend, translated to UCI, updating
+    /// its protocol state.
+    /// Output of a process that is no longer current is passed through untouched.
+    fn filter_stdout(&self, session_id: &str, pid: u32, lines: Vec<String>) -> Vec<String> {
+        let mut sessions = self.sessions.lock().unwrap();
+        match sessions.get_mut(session_id).filter(|session| session.pid == pid) {
+            Some(session) => {
+                let lines: Vec<String> = lines
+                    .into_iter()
+                    .filter_map(|line| session.translate(line).filter(|line| session.observe(line)))
+                    .collect();
+                if let Some(tap) = &session.tap {
+                    for line in &lines {
+                        let _ = tap.send(line.clone());
+                    }
+                }
+                lines
+            }
+            None => lines,
+        }
+    }
+}
+
+/// Start an engine process for a session and begin forwarding its output.
+/// With `replay`, the recorded handshake and options are sent to it right away.
+pub fn spawn_session(app: &AppHandle, session_id: &str, launch: EngineLaunch, replay: bool) -> Result<(), String> {
+    let transcript = match app.state::<EngineSessions>().transcript(session_id).map(|c| Transcript::open(session_id, c)) {
+        Some(Ok(transcript)) => Some(Arc::new(transcript)),
+        Some(Err(e)) => {
+            let _ = app.emit("engine-output", EngineOutput::debug(session_id, format!("Transcript disabled: {}", e)));
+            None
+        }
+        None => None,
+    };
+    let spawned = match &launch.remote {
+        Some(remote) => {
+            if let Some(transcript) = &transcript {
+                let action = if replay { "reconnect" } else { "connect" };
+                transcript.log(Direction::Lifecycle, &format!("{} {}", action, remote.address));
+            }
+            RemoteChild::connect(remote).map(|(rx, child)| (rx, EngineChild::Remote(child)))
+        }
+        None => spawn_local(app, &launch, replay, transcript.as_deref()).map(|(rx, child)| (rx, EngineChild::Local(child))),
+    };
+    let (rx, child) = match spawned {
+        Ok(spawned) => spawned,
+        Err(e) => {
+            if let Some(transcript) = &transcript {
+                transcript.log(Direction::Lifecycle, &e);
+            }
+            return Err(e);
+        }
+    };
+
+    let pid = child.pid();
+    let codec = Arc::new(EngineCodec::new(launch.encoding));
+    if let Some(transcript) = &transcript {
+        transcript.log(Direction::Lifecycle, &format!("started pid {}", pid));
+    }
+    let adapter = launch.dialect.adapter();
+    let mut session = EngineSession {
+        pid,
+        child,
+        launch,
+        health: EngineHealth::new(),
+        tap: None,
+        transcript: transcript.clone(),
+        codec: codec.clone(),
+        adapter,
+    };
+    if replay {
+        if let Err(e) = session.replay() {
+            // Nothing listens to the new process yet, so it must not outlive this call
+            if let Some(transcript) = &transcript {
+                transcript.log(Direction::Lifecycle, &format!("replay failed: {}", e));
+            }
+            let _ = session.child.kill();
+            if let Some(previous) = app.state::<EngineSessions>().remove(session_id) {
+                let _ = previous.child.kill();
+            }
+            return Err(e);
+        }
+    }
+    if let Some(previous) = app.state::<EngineSessions>().insert(session_id, session) {
+        let _ = previous.child.kill();
+    }
+
+    async_runtime::spawn(listen_to_engine(app.clone(), session_id.to_string(), pid, rx, transcript, codec));
+    async_runtime::spawn(watchdog::watch_engine(app.clone(), session_id.to_string(), pid));
+    Ok(())
+}
+
+/// Start the engine process of a local launch.
+fn spawn_local(
+    app: &AppHandle,
+    launch: &EngineLaunch,
+    replay: bool,
+    transcript: Option<&Transcript>,
+) -> Result<(Receiver<CommandEvent>, CommandChild), String> {
+    // The engine's working directory is its parent directory unless overridden
+    let engine_dir = launch.spawn.working_dir(&launch.path)?;
+    if let Some(transcript) = transcript {
+        transcript.log(
+            Direction::Lifecycle,
+            &format!(
+                "{} {} args {:?} cwd {} env {:?} nice {:?} cpus {:?}",
+                if replay { "restart" } else { "spawn" },
+                launch.path,
+                launch.args,
+                engine_dir,
+                launch.spawn.env,
+                launch.spawn.nice,
+                launch.spawn.cpu_affinity
+            ),
+        );
+    }
+
+    // Output is read raw and framed into lines by the listener,
+    // so lines split across reads are reassembled before reaching the frontend.
+    let (rx, child) = app
+        .shell()
+        .command(&launch.path)
+        .args(&launch.args)
+        .envs(&launch.spawn.env)
+        .current_dir(engine_dir)
+        .set_raw_out(true)
+        .spawn()
+        .map_err(|e| format!("Failed to spawn engine: {}", e))?;
+    if let Err(e) = launch.spawn.apply_to_process(child.pid()) {
+        let _ = child.kill();
+        return Err(e);
+    }
+    Ok((rx, child))
+}
+
+/// Where a batch of `engine-output` lines came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EngineStream {
+    Stdout,
+    Stderr,
+    /// Diagnostics produced by JieqiBox itself rather than by the engine.
+    Debug,
+}
+
+/// Payload of the `engine-output` event: a batch of complete lines from one stream.
+/// `session_id` is `None` for diagnostics that do not belong to a session (e.g. Android engine scans).
+#[derive(Clone, Serialize)]
+pub struct EngineOutput {
+    pub session_id: Option<String>,
+    pub stream: EngineStream,
+    pub lines: Vec<String>,
+}
+
+impl EngineOutput {
+    pub fn new(session_id: &str, stream: EngineStream, lines: Vec<String>) -> Self {
+        EngineOutput {
+            session_id: Some(session_id.to_string()),
+            stream,
+            lines,
+        }
+    }
+
+    pub fn debug(session_id: &str, line: impl Into<String>) -> Self {
+        Self::new(session_id, EngineStream::Debug, vec![line.into()])
+    }
+
+    #[cfg(target_os = "android")]
+    pub fn broadcast(line: impl Into<String>) -> Self {
+        EngineOutput {
+            session_id: None,
+            stream: EngineStream::Debug,
+            lines: vec![line.into()],
+        }
+    }
+}
+
+/// Payload of the `engine-exited` event, sent once per process after its pipes have drained.
+#[derive(Clone, Serialize)]
+pub struct EngineExited {
+    pub session_id: String,
+    pub code: Option<i32>,
+    pub signal: Option<i32>,
+    /// Set when the exit status could not be collected.
+    pub error: Option<String>,
+    /// True when JieqiBox killed or replaced the process itself.
+    pub killed: bool,
+    /// Last lines the engine wrote to stderr, oldest first.
+    pub stderr_tail: Vec<String>,
+}
+
+/// Longest partial line kept while waiting for its terminator. An engine that
+/// writes more than this without a newline gets the data flushed as a line anyway.
+const MAX_PENDING_LINE: usize = 64 * 1024;
+
+/// Reassembles complete lines from the raw chunks read off one engine pipe.
+/// Lines are split on `\n`; a trailing `\r` is dropped so CRLF engines look like LF ones.
+#[derive(Default)]
+pub struct LineFramer {
+    pending: Vec<u8>,
+}
+
+impl LineFramer {
+    /// Feed a chunk and return every line it completed, without terminators.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<Vec<u8>> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        for (i, &byte) in chunk.iter().enumerate() {
+            if byte == b'\n' {
+                self.pending.extend_from_slice(&chunk[start..i]);
+                lines.push(self.take());
+                start = i + 1;
+            }
+        }
+        self.pending.extend_from_slice(&chunk[start..]);
+        if self.pending.len() > MAX_PENDING_LINE {
+            lines.push(self.take());
+        }
+        lines
+    }
+
+    /// Return the unterminated remainder once the pipe has closed.
+    pub fn finish(&mut self) -> Option<Vec<u8>> {
+        if self.pending.is_empty() {
+            None
+        } else {
+            Some(self.take())
+        }
+    }
+
+    fn take(&mut self) -> Vec<u8> {
+        let mut line = std::mem::take(&mut self.pending);
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        line
+    }
+}
+
+/// Forward a session's process events to the frontend until the process exits.
+/// Each stream keeps its own line buffer. Stderr reads are emitted as they arrive; stdout
+/// lines are gathered into batches (see `output_batch`).
+/// Once the process is gone its session slot is freed and `engine-exited` is emitted.
+/// Every line, filtered or not, goes to the transcript when there is one.
+pub async fn listen_to_engine(
+    app: AppHandle,
+    session_id: String,
+    pid: u32,
+    mut rx: Receiver<CommandEvent>,
+    transcript: Option<Arc<Transcript>>,
+    codec: Arc<EngineCodec>,
+) {
+    let log = |direction: Direction, lines: &[String]| {
+        if let Some(transcript) = &transcript {
+            transcript.log_all(direction, lines);
+        }
+    };
+    let sessions = app.state::<EngineSessions>();
+    let mut stdout = LineFramer::default();
+    let mut stderr = LineFramer::default();
+    let mut batch = OutputBatch::default();
+    let mut stderr_tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
+    let mut status = None;
+    let mut error = None;
+
+    loop {
+        let event = match batch.deadline() {
+            Some(deadline) => match tokio::time::timeout_at(deadline.into(), rx.recv()).await {
+                Ok(event) => event,
+                Err(_) => {
+                    emit_lines(&app, &session_id, EngineStream::Stdout, batch.take());
+                    continue;
+                }
+            },
+            None => rx.recv().await,
+        };
+        let Some(event) = event else {
+            break;
+        };
+        match event {
+            CommandEvent::Stdout(buf) => {
+                let received = Instant::now();
+                let lines = decode_lines(&codec, stdout.push(&buf));
+                log(Direction::Stdout, &lines);
+                let lines = sessions.filter_stdout(&session_id, pid, lines);
+                if lines.iter().any(|line| line.starts_with("bestmove")) {
+                    app.state::<ClockService>().engine_moved(&app, &session_id, received);
+                }
+                if batch.push(lines, sessions.batching(&session_id)) {
+                    emit_lines(&app, &session_id, EngineStream::Stdout, batch.take());
+                }
+            }
+            CommandEvent::Stderr(buf) => {
+                let lines = decode_lines(&codec, stderr.push(&buf));
+                log(Direction::Stderr, &lines);
+                remember_stderr(&mut stderr_tail, &lines);
+                emit_lines(&app, &session_id, EngineStream::Stderr, lines);
+            }
+            CommandEvent::Terminated(payload) => status = Some(payload),
+            CommandEvent::Error(e) => error = Some(e),
+            _ => {}
+        }
+    }
+
+    // Flush the pending batch and lines the engine left unterminated when its pipes closed
+    let mut lines = batch.take();
+    if let Some(line) = stdout.finish() {
+        let unterminated = decode_lines(&codec, vec![line]);
+        log(Direction::Stdout, &unterminated);
+        lines.extend(unterminated);
+    }
+    emit_lines(&app, &session_id, EngineStream::Stdout, lines);
+    if let Some(line) = stderr.finish() {
+        let lines = decode_lines(&codec, vec![line]);
+        log(Direction::Stderr, &lines);
+        remember_stderr(&mut stderr_tail, &lines);
+        emit_lines(&app, &session_id, EngineStream::Stderr, lines);
+    }
+
+    let killed = sessions.remove_if_current(&session_id, pid).is_none();
+    if !killed {
+        sessions.forget(&session_id);
+    }
+    if let Some(transcript) = &transcript {
+        let exit = match (&status, &error) {
+            (_, Some(e)) => format!("error {}", e),
+            (Some(s), _) => match (s.code, s.signal) {
+                (Some(code), _) => format!("code {}", code),
+                (None, Some(signal)) => format!("signal {}", signal),
+                (None, None) => "unknown status".to_string(),
+            },
+            (None, None) => "unknown status".to_string(),
+        };
+        let by = if killed { " (killed by JieqiBox)" } else { "" };
+        transcript.log(Direction::Lifecycle, &format!("exited pid {}: {}{}", pid, exit, by));
+    }
+    let _ = app.emit("engine-exited", EngineExited {
+        session_id,
+        code: status.as_ref().and_then(|s| s.code),
+        signal: status.as_ref().and_then(|s| s.signal),
+        error,
+        killed,
+        stderr_tail: stderr_tail.into(),
+    });
+}
+
+fn decode_lines(codec: &EngineCodec, lines: Vec<Vec<u8>>) -> Vec<String> {
+    lines.iter().map(|line| codec.decode(line)).collect()
+}
+
+fn remember_stderr(tail: &mut VecDeque<String>, lines: &[String]) {
+    for line in lines {
+        if tail.len() == STDERR_TAIL_LINES {
+            tail.pop_front();
+        }
+        tail.push_back(line.clone());
+    }
+}
+
+/// Emit a batch of lines as one `engine-output` event.
+/// UCI messages on stdout are additionally emitted as typed events (`engine-info`, `engine-bestmove`, ...).
+fn emit_lines(app: &AppHandle, session_id: &str, stream: EngineStream, lines: Vec<String>) {
+    if lines.is_empty() {
+        return;
+    }
+    if stream == EngineStream::Stdout {
+        for line in &lines {
+            if let Some(message) = uci::parse_line(line) {
+                let _ = app.emit(message.event_name(), UciEvent { session_id, raw: line, message: &message });
+            }
+        }
+    }
+    let _ = app.emit("engine-output", EngineOutput::new(session_id, stream, lines));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(lines: Vec<Vec<u8>>) -> Vec<String> {
+        lines.into_iter().map(|line| String::from_utf8(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn framer_strips_crlf() {
+        let mut framer = LineFramer::default();
+        assert_eq!(text(framer.push(b"uciok\r\nreadyok\n")), ["uciok", "readyok"]);
+        assert_eq!(framer.finish(), None);
+    }
+
+    #[test]
+    fn framer_joins_partial_chunks() {
+        let mut framer = LineFramer::default();
+        assert!(framer.push(b"info dep").is_empty());
+        assert!(framer.push(b"th 5 pv h2e2\r").is_empty());
+        assert_eq!(text(framer.push(b"\nbestmo")), ["info depth 5 pv h2e2"]);
+        assert_eq!(text(framer.push(b"ve h2e2\n")), ["bestmove h2e2"]);
+        assert_eq!(framer.finish(), None);
+    }
+
+    #[test]
+    fn framer_returns_unterminated_rest_on_finish() {
+        let mut framer = LineFramer::default();
+        assert!(framer.push(b"bestmove a0a1").is_empty());
+        assert_eq!(framer.finish(), Some(b"bestmove a0a1".to_vec()));
+        assert_eq!(framer.finish(), None);
+    }
+
+    #[test]
+    fn framer_splits_overlong_lines() {
+        let mut framer = LineFramer::default();
+        let long = vec![b'x'; MAX_PENDING_LINE + 1];
+        let lines = framer.push(&long);
+        assert_eq!(lines, [long]);
+        assert!(framer.push(b"x").is_empty());
+        assert_eq!(text(framer.push(b"\n")), ["x"]);
+    }
+}
@@ -0,0 +1,315 @@
+// src-tauri/src/engine_bridge.rs
+// Engines over TCP: a server that exposes a local engine to authenticated clients, and the
+// client side that makes such a remote engine look like a locally spawned process.
+//
+// Protocol: the client's first line is `AUTH <token>`. The server answers `OK` and from then
+// on the connection carries the engine's stdin (client to server) and stdout (server to
+// client) byte for byte; any other first line gets `ERR <reason>` and the connection is
+// closed. Every connection gets its own engine process, and closing either end ends the other.
+use crate::spawn_options::SpawnOptions;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tauri::async_runtime::{self, Receiver};
+use tauri_plugin_shell::process::{CommandEvent, TerminatedPayload};
+
+/// How long either side waits for the other's handshake line.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest handshake line accepted, so a stray client cannot make the server buffer forever.
+const MAX_HANDSHAKE_LINE: usize = 1024;
+
+/// Remote engines have no local process id; they get ids from this counter instead,
+/// far above the pid range of the operating systems we run on.
+static NEXT_REMOTE_ID: AtomicU32 = AtomicU32::new(1 << 31);
+
+/// Where a remote engine is served and the token it expects.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RemoteEngine {
+    /// `host:port` of a `jieqibox-cli serve` instance.
+    pub address: String,
+    pub token: String,
+}
+
+/// The engine a server starts for each connection.
+#[derive(Clone, Debug)]
+pub struct ServedEngine {
+    pub path: String,
+    pub args: Vec<String>,
+    pub options: SpawnOptions,
+}
+
+/// Accept connections on `listener` until it fails, serving each on its own thread.
+/// `log` receives one line per connection event.
+pub fn serve(
+    listener: TcpListener,
+    token: String,
+    engine: ServedEngine,
+    log: Arc<dyn Fn(String) + Send + Sync>,
+) -> Result<(), String> {
+    if token.is_empty() {
+        return Err("The bridge token must not be empty.".to_string());
+    }
+    let token = Arc::new(token);
+    let engine = Arc::new(engine);
+    for stream in listener.incoming() {
+        let stream = stream.map_err(|e| format!("Failed to accept a connection: {}", e))?;
+        let (token, engine, log) = (token.clone(), engine.clone(), log.clone());
+        thread::spawn(move || {
+            let peer = stream.peer_addr().map_or_else(|_| "unknown peer".to_string(), |a| a.to_string());
+            log(format!("{}: connected", peer));
+            match handle_client(stream, &token, &engine) {
+                Ok(()) => log(format!("{}: closed", peer)),
+                Err(e) => log(format!("{}: {}", peer, e)),
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle_client(mut stream: TcpStream, token: &str, engine: &ServedEngine) -> Result<(), String> {
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).map_err(|e| e.to_string())?;
+    let line = read_handshake_line(&mut stream)?;
+    let authorized = line.strip_prefix("AUTH ").is_some_and(|t| tokens_match(t, token));
+    if !authorized {
+        let _ = stream.write_all(b"ERR invalid token\n");
+        return Err("rejected: invalid token".to_string());
+    }
+    stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+    stream.set_nodelay(true).map_err(|e| e.to_string())?;
+
+    let mut command = Command::new(&engine.path);
+    command
+        .args(&engine.args)
+        .envs(&engine.options.env)
+        .current_dir(engine.options.working_dir(&engine.path)?)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null());
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            let _ = stream.write_all(b"ERR engine failed to start\n");
+            return Err(format!("Failed to spawn engine: {}", e));
+        }
+    };
+    if let Err(e) = engine.options.apply_to_process(child.id()) {
+        let _ = child.kill();
+        let _ = child.wait();
+        let _ = stream.write_all(b"ERR engine failed to start\n");
+        return Err(e);
+    }
+    stream.write_all(b"OK\n").map_err(|e| e.to_string())?;
+
+    let mut stdin = child.stdin.take().ok_or("Failed to open engine stdin")?;
+    let mut stdout = child.stdout.take().ok_or("Failed to open engine stdout")?;
+    let mut to_client = stream.try_clone().map_err(|e| e.to_string())?;
+    let pump = thread::spawn(move || {
+        relay(&mut stdout, &mut to_client);
+        // The engine is gone: end the connection, which also ends the copy below
+        let _ = to_client.shutdown(Shutdown::Both);
+    });
+    relay(&mut stream, &mut stdin);
+    drop(stdin);
+    let _ = child.kill();
+    let status = child.wait().map_err(|e| e.to_string())?;
+    let _ = pump.join();
+    // Killed engines have no exit code; anything else should have exited cleanly
+    if status.success() || status.code().is_none() {
+        Ok(())
+    } else {
+        Err(format!("engine exited with {}", status))
+    }
+}
+
+/// Copy until either side closes. A plain read/write loop rather than `io::copy`, whose
+/// splice fast path between pipes and sockets is not available on every kernel.
+fn relay(from: &mut impl Read, to: &mut impl Write) {
+    let mut buf = [0u8; 4096];
+    loop {
+        match from.read(&mut buf) {
+            Ok(0) | Err(_) => return,
+            Ok(n) => {
+                if to.write_all(&buf[..n]).and_then(|_| to.flush()).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+/// Compare without stopping at the first difference, so timing does not leak the token.
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len() && given.bytes().zip(expected.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+/// Read one `\n`-terminated line byte by byte, leaving everything after it in the stream.
+fn read_handshake_line(stream: &mut TcpStream) -> Result<String, String> {
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+    loop {
+        match stream.read(&mut byte) {
+            Ok(0) => return Err("connection closed during handshake".to_string()),
+            Ok(_) if byte[0] == b'\n' => break,
+            Ok(_) if line.len() >= MAX_HANDSHAKE_LINE => return Err("handshake line too long".to_string()),
+            Ok(_) => line.push(byte[0]),
+            Err(e) => return Err(format!("handshake failed: {}", e)),
+        }
+    }
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    Ok(String::from_utf8_lossy(&line).into_owned())
+}
+
+/// Client end of a bridge connection, used in place of a `CommandChild`.
+pub struct RemoteChild {
+    stream: TcpStream,
+    id: u32,
+}
+
+impl RemoteChild {
+    /// Connect and authenticate. Like a spawned command, the returned receiver yields the
+    /// engine's output as `Stdout` events and ends with `Terminated` once the connection closes.
+    pub fn connect(remote: &RemoteEngine) -> Result<(Receiver<CommandEvent>, RemoteChild), String> {
+        let addresses = remote
+            .address
+            .to_socket_addrs()
+            .map_err(|e| format!("Invalid remote engine address '{}': {}", remote.address, e))?;
+        let mut last_error = format!("'{}' did not resolve to any address", remote.address);
+        let mut stream = None;
+        for address in addresses {
+            match TcpStream::connect_timeout(&address, HANDSHAKE_TIMEOUT) {
+                Ok(s) => {
+                    stream = Some(s);
+                    break;
+                }
+                Err(e) => last_error = e.to_string(),
+            }
+        }
+        let mut stream =
+            stream.ok_or_else(|| format!("Failed to connect to remote engine {}: {}", remote.address, last_error))?;
+
+        stream.set_nodelay(true).map_err(|e| e.to_string())?;
+        stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)).map_err(|e| e.to_string())?;
+        stream
+            .write_all(format!("AUTH {}\n", remote.token).as_bytes())
+            .map_err(|e| format!("Failed to send to remote engine: {}", e))?;
+        let reply = read_handshake_line(&mut stream).map_err(|e| format!("Remote engine {}: {}", remote.address, e))?;
+        if reply != "OK" {
+            let reason = reply.strip_prefix("ERR ").unwrap_or(&reply);
+            return Err(format!("Remote engine {} refused the connection: {}", remote.address, reason));
+        }
+        stream.set_read_timeout(None).map_err(|e| e.to_string())?;
+
+        let (tx, rx) = async_runtime::channel(64);
+        let mut reader = stream.try_clone().map_err(|e| e.to_string())?;
+        thread::spawn(move || {
+            let mut buf = [0u8; 4096];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => {
+                        if tx.blocking_send(CommandEvent::Stdout(buf[..n].to_vec())).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        let _ = tx.blocking_send(CommandEvent::Error(format!("Remote engine connection lost: {}", e)));
+                        break;
+                    }
+                }
+            }
+            // The exit status of the remote process does not travel over the bridge
+            let _ = tx.blocking_send(CommandEvent::Terminated(TerminatedPayload { code: None, signal: None }));
+        });
+
+        let id = NEXT_REMOTE_ID.fetch_add(1, Ordering::Relaxed);
+        Ok((rx, RemoteChild { stream, id }))
+    }
+
+    pub fn write(&mut self, buf: &[u8]) -> Result<(), String> {
+        self.stream.write_all(buf).map_err(|e| e.to_string())
+    }
+
+    /// Close the connection; the server then kills the engine.
+    pub fn kill(self) -> Result<(), String> {
+        self.stream.shutdown(Shutdown::Both).map_err(|e| e.to_string())
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Serve a shell script that answers `uci` with `uciok` on a free localhost port.
+    fn start_server(token: &str) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let engine = ServedEngine {
+            path: "/bin/sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                "while read -r line; do [ \"$line\" = uci ] && echo uciok; done".to_string(),
+            ],
+            options: SpawnOptions::default(),
+        };
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let log_lines = lines.clone();
+        let log = Arc::new(move |line: String| log_lines.lock().unwrap().push(line));
+        let token = token.to_string();
+        thread::spawn(move || serve(listener, token, engine, log));
+        (address, lines)
+    }
+
+    #[test]
+    fn rejects_wrong_token() {
+        let (address, _) = start_server("secret");
+        let remote = RemoteEngine {
+            address,
+            token: "guess".to_string(),
+        };
+        let error = RemoteChild::connect(&remote).err().expect("connection should be refused");
+        assert!(error.ends_with("refused the connection: invalid token"), "{}", error);
+    }
+
+    #[test]
+    fn relays_engine_over_localhost() {
+        let (address, lines) = start_server("secret");
+        let remote = RemoteEngine {
+            address,
+            token: "secret".to_string(),
+        };
+        let (mut rx, mut child) = RemoteChild::connect(&remote).unwrap();
+        child.write(b"uci\n").unwrap();
+
+        let mut output = Vec::new();
+        while !String::from_utf8_lossy(&output).contains('\n') {
+            match rx.blocking_recv() {
+                Some(CommandEvent::Stdout(bytes)) => output.extend(bytes),
+                other => panic!("unexpected event {:?}", other),
+            }
+        }
+        assert_eq!(String::from_utf8_lossy(&output), "uciok\n");
+
+        child.kill().unwrap();
+        loop {
+            match rx.blocking_recv() {
+                Some(CommandEvent::Terminated(_)) => break,
+                Some(_) => {}
+                None => panic!("receiver closed before the connection ended"),
+            }
+        }
+        assert!(lines.lock().unwrap().iter().any(|line| line.ends_with(": connected")));
+    }
+}
@@ -0,0 +1,364 @@
+// src-tauri/src/engine_discovery.rs
+// Desktop counterpart of Android's engine sync: scan directories for executables, probe each
+// with a short UCI and then JAI handshake, and pair the engines found with the network files
+// next to them, so they can be added without typing paths.
+use crate::engine_registry::{self, EngineProfile, EngineProtocol, EngineRegistry};
+use crate::engine_store::has_extension;
+use crate::match_runner::EngineOptionValue;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Most executables probed per scan, so pointing discovery at a system `bin` directory
+/// does not start hundreds of programs.
+const MAX_CANDIDATES: usize = 32;
+
+/// Extensions of files that are never engines even when marked executable.
+const SKIPPED_EXTENSIONS: &[&str] = &["so", "dylib", "dll", "nnue", "bin", "json", "txt", "md", "ini", "log"];
+
+/// Extensions of evaluation network files.
+const NETWORK_EXTENSIONS: &[&str] = &["nnue", "jnn"];
+
+/// Profile of one executable and whether it was probed now rather than found in the registry.
+type Probed = Result<(EngineProfile, bool), String>;
+
+/// Where to look and how long each handshake may take.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DiscoveryConfig {
+    /// Directories to scan; `default_dirs()` when empty.
+    #[serde(default)]
+    pub dirs: Vec<String>,
+    /// Levels of subdirectories scanned below each directory, for engines shipped in folders.
+    #[serde(default = "default_depth")]
+    pub depth: u32,
+    #[serde(default = "default_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_depth() -> u32 {
+    1
+}
+
+fn default_timeout_ms() -> u64 {
+    2000
+}
+
+impl Default for DiscoveryConfig {
+    fn default() -> Self {
+        DiscoveryConfig {
+            dirs: Vec::new(),
+            depth: default_depth(),
+            timeout_ms: default_timeout_ms(),
+        }
+    }
+}
+
+/// An engine found by a scan, ready to be added as a managed engine.
+#[derive(Clone, Debug, Serialize)]
+pub struct EngineCandidate {
+    pub path: String,
+    /// Name the engine reported, or its file name.
+    pub name: String,
+    pub profile: EngineProfile,
+    /// Network files in the engine's directory.
+    pub networks: Vec<String>,
+    /// Option to point the engine at its network, when its default does not name a file
+    /// that exists next to it.
+    pub network_option: Option<EngineOptionValue>,
+}
+
+/// An executable that did not answer either handshake.
+#[derive(Clone, Debug, Serialize)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DiscoveryResult {
+    /// Directories that were scanned, after defaults were applied.
+    pub dirs: Vec<String>,
+    pub candidates: Vec<EngineCandidate>,
+    pub skipped: Vec<SkippedFile>,
+}
+
+/// Directories scanned by default: `engines` next to the application and in the working
+/// directory, and `jieqibox/engines` in the home directory, like Android's user directory.
+pub fn default_dirs() -> Vec<String> {
+    let mut dirs = Vec::new();
+    if let Some(exe_dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+        dirs.push(exe_dir.join("engines"));
+    }
+    dirs.push(PathBuf::from("engines"));
+    if let Some(home) = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE")) {
+        dirs.push(Path::new(&home).join("jieqibox").join("engines"));
+    }
+    dirs.into_iter().map(|dir| dir.to_string_lossy().into_owned()).collect()
+}
+
+/// Scan the configured directories and probe every executable found. Engines already in the
+/// registry at `registry_path` are not started again; newly probed ones are added to it.
+pub fn discover(registry_path: &Path, config: &DiscoveryConfig) -> Result<DiscoveryResult, String> {
+    let dirs = if config.dirs.is_empty() { default_dirs() } else { config.dirs.clone() };
+    let mut result = DiscoveryResult {
+        dirs: dirs.clone(),
+        ..DiscoveryResult::default()
+    };
+
+    let mut executables = Vec::new();
+    for dir in &dirs {
+        collect_executables(Path::new(dir), config.depth, &mut executables);
+    }
+    executables.sort();
+    executables.dedup();
+    if executables.len() > MAX_CANDIDATES {
+        for path in executables.drain(MAX_CANDIDATES..) {
+            result.skipped.push(SkippedFile {
+                path: path.to_string_lossy().into_owned(),
+                reason: format!("more than {} executables found", MAX_CANDIDATES),
+            });
+        }
+    }
+
+    let mut registry = EngineRegistry::load(registry_path)?;
+    let timeout = Duration::from_millis(config.timeout_ms);
+    // Every unknown binary is probed on its own thread, as most of the time is spent waiting
+    let probes: Vec<(String, Probed)> = thread::scope(|scope| {
+        let handles: Vec<_> = executables
+            .iter()
+            .map(|path| {
+                let path = path.to_string_lossy().into_owned();
+                let registry = &registry;
+                scope.spawn(move || {
+                    let probed = engine_registry::hash_file(Path::new(&path)).and_then(|hash| match registry.get(&hash) {
+                        Some(profile) => Ok((EngineProfile { path: path.clone(), ..profile.clone() }, false)),
+                        None => probe_any(hash, &path, timeout).map(|profile| (profile, true)),
+                    });
+                    (path, probed)
+                })
+            })
+            .collect();
+        handles.into_iter().filter_map(|handle| handle.join().ok()).collect()
+    });
+
+    let mut registry_changed = false;
+    for (path, probed) in probes {
+        match probed {
+            Ok((profile, new)) => {
+                if new {
+                    registry.insert(profile.clone());
+                    registry_changed = true;
+                }
+                result.candidates.push(candidate(path, profile));
+            }
+            Err(reason) => result.skipped.push(SkippedFile { path, reason }),
+        }
+    }
+    if registry_changed {
+        registry.save(registry_path)?;
+    }
+    Ok(result)
+}
+
+/// Handshake as UCI first, then as JAI.
+fn probe_any(hash: String, path: &str, timeout: Duration) -> Result<EngineProfile, String> {
+    engine_registry::handshake(hash.clone(), path, &[], EngineProtocol::Uci, timeout)
+        .or_else(|_| engine_registry::handshake(hash, path, &[], EngineProtocol::Jai, timeout))
+        .map_err(|_| "no UCI or JAI handshake".to_string())
+}
+
+fn candidate(path: String, profile: EngineProfile) -> EngineCandidate {
+    let dir = Path::new(&path).parent().unwrap_or(Path::new("."));
+    let mut networks: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|p| p.is_file() && has_extension(p, NETWORK_EXTENSIONS))
+                .map(|p| p.to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    networks.sort();
+
+    // Engines find their network through an option such as Stockfish's `EvalFile`
+    let network_option = profile
+        .options
+        .iter()
+        .find(|option| {
+            let name = option.name.to_lowercase();
+            option.option_type == "string" && (name == "evalfile" || name.contains("nnue"))
+        })
+        .and_then(|option| {
+            let default_found = option.default.as_deref().is_some_and(|default| {
+                !default.is_empty() && (dir.join(default).is_file() || Path::new(default).is_file())
+            });
+            let network = networks.first().filter(|_| !default_found)?;
+            Some(EngineOptionValue {
+                name: option.name.clone(),
+                value: network.clone(),
+            })
+        });
+
+    let name = profile.name.clone().unwrap_or_else(|| {
+        Path::new(&path)
+            .file_stem()
+            .map_or_else(|| path.clone(), |stem| stem.to_string_lossy().into_owned())
+    });
+    EngineCandidate {
+        path,
+        name,
+        profile,
+        networks,
+        network_option,
+    }
+}
+
+/// Executable files in `dir` and, down to `depth` levels, its subdirectories.
+fn collect_executables(dir: &Path, depth: u32, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let own_exe = std::env::current_exe().ok().and_then(|exe| fs::canonicalize(exe).ok());
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if entry.file_name().to_string_lossy().starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            if depth > 0 {
+                collect_executables(&path, depth - 1, out);
+            }
+        } else if is_executable(&path) && !has_extension(&path, SKIPPED_EXTENSIONS) {
+            let Ok(path) = fs::canonicalize(&path) else {
+                continue;
+            };
+            if own_exe.as_ref() != Some(&path) {
+                out.push(path);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file() && has_extension(path, &["exe"])
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Answers `uci` like an NNUE engine whose default network is not installed.
+    const ENGINE_SCRIPT: &str = "#!/bin/sh
+while read -r line; do
+  case \"$line\" in
+    uci) echo 'id name Scripted'; echo 'option name EvalFile type string default missing.nnue'; echo uciok ;;
+    quit) exit 0 ;;
+  esac
+done
+";
+
+    /// A fresh directory under the system temp directory, removed when dropped.
+    struct TempRoot(PathBuf);
+
+    impl TempRoot {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("jieqibox-engine-discovery-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempRoot(path.canonicalize().unwrap())
+        }
+
+        fn write(&self, relative: &str, content: &str, mode: u32) -> PathBuf {
+            let path = self.0.join(relative);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+            path
+        }
+
+        fn config(&self, dir: &str) -> DiscoveryConfig {
+            DiscoveryConfig {
+                dirs: vec![self.0.join(dir).display().to_string()],
+                timeout_ms: 2000,
+                ..DiscoveryConfig::default()
+            }
+        }
+    }
+
+    impl Drop for TempRoot {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn collects_executables_down_to_the_depth() {
+        let root = TempRoot::new("collect");
+        let engine = root.write("engines/pikafish", ENGINE_SCRIPT, 0o755);
+        let nested = root.write("engines/folder/engine", ENGINE_SCRIPT, 0o755);
+        root.write("engines/folder/deeper/engine", ENGINE_SCRIPT, 0o755);
+        root.write("engines/pikafish.nnue", "", 0o755);
+        root.write("engines/.hidden", ENGINE_SCRIPT, 0o755);
+        root.write("engines/readme", "", 0o644);
+
+        let mut found = Vec::new();
+        collect_executables(&root.0.join("engines"), 1, &mut found);
+        found.sort();
+        assert_eq!(found, [nested, engine.clone()]);
+        let mut found = Vec::new();
+        collect_executables(&root.0.join("engines"), 0, &mut found);
+        assert_eq!(found, [engine]);
+    }
+
+    #[test]
+    fn pairs_an_engine_with_the_network_next_to_it() {
+        let root = TempRoot::new("network");
+        let engine = root.write("engines/pikafish", ENGINE_SCRIPT, 0o755);
+        let network = root.write("engines/pikafish.nnue", "", 0o644);
+        root.write("engines/silent", "#!/bin/sh\nexit 1\n", 0o755);
+        let registry = root.0.join("registry.json");
+
+        let result = discover(&registry, &root.config("engines")).unwrap();
+        assert_eq!(result.candidates.len(), 1);
+        let candidate = &result.candidates[0];
+        assert_eq!((candidate.path.as_str(), candidate.name.as_str()), (engine.to_str().unwrap(), "Scripted"));
+        assert_eq!(candidate.networks, [network.display().to_string()]);
+        let option = candidate.network_option.as_ref().unwrap();
+        assert_eq!((option.name.as_str(), option.value.as_str()), ("EvalFile", network.to_str().unwrap()));
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.skipped[0].reason, "no UCI or JAI handshake");
+        let registered = EngineRegistry::load(&registry).unwrap();
+        assert_eq!(registered.get(&candidate.profile.hash).unwrap().name.as_deref(), Some("Scripted"));
+
+        // Once the default network is installed the engine finds it by itself
+        root.write("engines/missing.nnue", "", 0o644);
+        let result = discover(&registry, &root.config("engines")).unwrap();
+        assert!(result.candidates[0].network_option.is_none());
+    }
+
+    #[test]
+    fn probes_at_most_max_candidates() {
+        let root = TempRoot::new("limit");
+        for i in 0..=MAX_CANDIDATES {
+            root.write(&format!("bin/tool{:02}", i), "#!/bin/sh\nexit 1\n", 0o755);
+        }
+        let result = discover(&root.0.join("registry.json"), &root.config("bin")).unwrap();
+        assert!(result.candidates.is_empty());
+        assert_eq!(result.skipped.len(), MAX_CANDIDATES + 1);
+        let over: Vec<_> = result.skipped.iter().filter(|s| s.reason.starts_with("more than")).collect();
+        assert_eq!(over.len(), 1);
+        assert!(over[0].path.ends_with(&format!("tool{}", MAX_CANDIDATES)));
+        assert_eq!(over[0].reason, format!("more than {} executables found", MAX_CANDIDATES));
+    }
+}
@@ -0,0 +1,179 @@
+// src-tauri/src/engine_registry.rs
+// What each engine binary reported in its handshake, keyed by a hash of the binary so the
+// record follows the file rather than its path and goes stale when the file is replaced.
+use crate::encoding::EngineEncoding;
+use crate::match_runner::{MatchEngine, ProcessEngine};
+use crate::spawn_options::SpawnOptions;
+use crate::ucci::EngineDialect;
+use crate::uci::{self, UciMessage, UciOption};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EngineProtocol {
+    Uci,
+    Ucci,
+    Jai,
+}
+
+impl EngineProtocol {
+    /// Command that starts the handshake and the line that ends it. UCCI engines are
+    /// probed through the UCCI adapter and so answer the UCI handshake.
+    fn handshake(self) -> (&'static str, &'static str) {
+        match self {
+            EngineProtocol::Uci | EngineProtocol::Ucci => ("uci", "uciok"),
+            EngineProtocol::Jai => ("jai", "jaiok"),
+        }
+    }
+
+    fn dialect(self) -> EngineDialect {
+        match self {
+            EngineProtocol::Ucci => EngineDialect::Ucci,
+            EngineProtocol::Uci | EngineProtocol::Jai => EngineDialect::Native,
+        }
+    }
+}
+
+/// Handshake result of one engine binary.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EngineProfile {
+    /// Hex SHA-256 of the binary.
+    pub hash: String,
+    /// Path the binary was last probed at.
+    pub path: String,
+    pub protocol: EngineProtocol,
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub options: Vec<UciOption>,
+    /// Unix time of the probe, in seconds.
+    pub probed_at: u64,
+}
+
+/// The registry file: profiles by binary hash.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct EngineRegistry {
+    pub engines: BTreeMap<String, EngineProfile>,
+}
+
+impl EngineRegistry {
+    /// Read the registry, or start an empty one when the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| format!("Invalid engine registry: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(EngineRegistry::default()),
+            Err(e) => Err(format!("Failed to read engine registry: {}", e)),
+        }
+    }
+
+    /// Write through a temporary file so a crash never leaves half a registry behind.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, content).map_err(|e| format!("Failed to write engine registry: {}", e))?;
+        fs::rename(&temp, path).map_err(|e| format!("Failed to write engine registry: {}", e))
+    }
+
+    pub fn get(&self, hash: &str) -> Option<&EngineProfile> {
+        self.engines.get(hash)
+    }
+
+    pub fn insert(&mut self, profile: EngineProfile) {
+        self.engines.insert(profile.hash.clone(), profile);
+    }
+}
+
+/// Hex SHA-256 of a file, read in chunks since engine binaries and their networks are large.
+pub fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 16];
+    loop {
+        let n = file.read(&mut buf).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Start `path`, run the handshake and collect `id` and `option` lines until the
+/// handshake ends. The engine is shut down afterwards.
+pub fn probe(path: &str, args: &[String], protocol: EngineProtocol, timeout: Duration) -> Result<EngineProfile, String> {
+    handshake(hash_file(Path::new(path))?, path, args, protocol, timeout)
+}
+
+/// `probe` for a binary whose hash is already known.
+pub fn handshake(hash: String, path: &str, args: &[String], protocol: EngineProtocol, timeout: Duration) -> Result<EngineProfile, String> {
+    let (command, ok) = protocol.handshake();
+    let mut engine = ProcessEngine::spawn(path, args, EngineEncoding::Auto, protocol.dialect(), &SpawnOptions::default())?;
+    engine.send(command)?;
+
+    let mut profile = EngineProfile {
+        hash,
+        path: path.to_string(),
+        protocol,
+        name: None,
+        author: None,
+        options: Vec::new(),
+        probed_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()),
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(format!("No '{}' received within {} ms.", ok, timeout.as_millis()));
+        }
+        let Some(line) = engine.recv(remaining)? else {
+            continue;
+        };
+        if line.trim() == ok {
+            return Ok(profile);
+        }
+        match uci::parse_line(&line) {
+            Some(UciMessage::Id(id)) => {
+                profile.name = id.name.or(profile.name);
+                profile.author = id.author.or(profile.author);
+            }
+            Some(UciMessage::Option(option)) => profile.options.push(option),
+            _ => {}
+        }
+    }
+}
+
+/// Profile of the binary at `path` from the registry at `registry_path`, probing it first
+/// when it is not registered yet or `refresh` is set.
+pub fn probe_cached(
+    registry_path: &Path,
+    path: &str,
+    args: &[String],
+    protocol: EngineProtocol,
+    timeout: Duration,
+    refresh: bool,
+) -> Result<EngineProfile, String> {
+    let hash = hash_file(Path::new(path))?;
+    let mut registry = EngineRegistry::load(registry_path)?;
+    if !refresh {
+        if let Some(profile) = registry.get(&hash).filter(|p| p.protocol == protocol) {
+            let mut profile = profile.clone();
+            profile.path = path.to_string();
+            return Ok(profile);
+        }
+    }
+    let profile = handshake(hash, path, args, protocol, timeout)?;
+    registry.insert(profile.clone());
+    registry.save(registry_path)?;
+    Ok(profile)
+}
+
+/// Profile of the binary at `path` if it is registered, without starting it.
+pub fn lookup(registry_path: &Path, path: &str) -> Result<Option<EngineProfile>, String> {
+    let hash = hash_file(Path::new(path))?;
+    Ok(EngineRegistry::load(registry_path)?.get(&hash).cloned())
+}
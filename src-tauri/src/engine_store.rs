@@ -0,0 +1,590 @@
+// src-tauri/src/engine_store.rs
+// Content-addressed store for engines copied into Android's internal storage. Every file is
+// kept once under its SHA-256, each engine gets a directory keyed by its hash holding links to
+// the engine and its networks, and a manifest records which networks belong to which engine
+// so files nothing refers to any more can be removed. Nothing here is Android-specific; the
+// store works on any root directory.
+use crate::engine_registry;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const OBJECTS_DIR: &str = "objects";
+const ENGINES_DIR: &str = "engines";
+
+/// Extensions of network files, which are paired with the engines found beside them when
+/// syncing rather than stored as engines.
+const NETWORK_EXTENSIONS: &[&str] = &["nnue", "jnn"];
+
+/// A network file paired with an engine.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct NetworkEntry {
+    pub hash: String,
+    /// Name the network has in the engine's directory, which engines look it up by.
+    pub file_name: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EngineEntry {
+    pub file_name: String,
+    /// File the engine was synced from, or `None` for an engine imported through the file
+    /// picker. Synced engines are dropped once their source file is gone.
+    pub source: Option<String>,
+    #[serde(default)]
+    pub networks: Vec<NetworkEntry>,
+    /// Unix time the engine was first stored, in seconds.
+    pub added_at: u64,
+}
+
+/// Size and modification time of a synced file when it was last hashed, so unchanged files
+/// are not read again on every scan.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub struct SourceEntry {
+    pub hash: String,
+    pub size: u64,
+    /// Unix time of the last modification, in nanoseconds, so a file replaced within the same
+    /// second is still hashed again.
+    pub modified: u64,
+}
+
+/// The manifest file: engines by hash and the synced files they came from.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Manifest {
+    pub engines: BTreeMap<String, EngineEntry>,
+    #[serde(default)]
+    pub sources: BTreeMap<String, SourceEntry>,
+}
+
+/// An engine in the store and the path it is started from.
+#[derive(Clone, Debug, Serialize)]
+pub struct StoredEngine {
+    pub hash: String,
+    pub path: String,
+    pub entry: EngineEntry,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SyncReport {
+    /// Files whose content was not in the store yet.
+    pub copied: usize,
+    /// Files skipped because their content was already stored.
+    pub unchanged: usize,
+    /// Paths of all engines in the store after the sync.
+    pub engines: Vec<String>,
+    /// Files that could not be stored, with the reason. They are skipped, and an engine synced
+    /// from one of them earlier stays in the store.
+    pub errors: Vec<String>,
+}
+
+/// Engines moved into the store from the directory used before it.
+#[derive(Debug, Default, Serialize)]
+pub struct LegacyImport {
+    /// New path of each imported engine, by its old path.
+    pub paths: BTreeMap<String, String>,
+    /// Files that could not be imported, with the reason.
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct GcReport {
+    /// Stored files, engine directories and stale links removed.
+    pub removed: usize,
+    /// Size of the stored files removed; links share their space with those.
+    pub freed_bytes: u64,
+}
+
+pub struct EngineStore {
+    root: PathBuf,
+    manifest: Manifest,
+}
+
+impl EngineStore {
+    /// Open the store at `root`, creating it when it does not exist yet.
+    pub fn open(root: impl Into<PathBuf>) -> Result<Self, String> {
+        let root = root.into();
+        for dir in [OBJECTS_DIR, ENGINES_DIR] {
+            fs::create_dir_all(root.join(dir)).map_err(|e| format!("Failed to create engine store: {}", e))?;
+        }
+        let manifest = match fs::read_to_string(root.join(MANIFEST_FILE)) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| format!("Invalid engine store manifest: {}", e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Manifest::default(),
+            Err(e) => return Err(format!("Failed to read engine store manifest: {}", e)),
+        };
+        Ok(EngineStore { root, manifest })
+    }
+
+    pub fn manifest(&self) -> &Manifest {
+        &self.manifest
+    }
+
+    /// Write the manifest through a temporary file, like the engine registry.
+    pub fn save(&self) -> Result<(), String> {
+        let path = self.root.join(MANIFEST_FILE);
+        let content = serde_json::to_string_pretty(&self.manifest).map_err(|e| e.to_string())?;
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, content).map_err(|e| format!("Failed to write engine store manifest: {}", e))?;
+        fs::rename(&temp, &path).map_err(|e| format!("Failed to write engine store manifest: {}", e))
+    }
+
+    /// Path an engine is started from: `engines/<hash>/<file name>`, so its working
+    /// directory holds its networks under the names they were added with.
+    pub fn engine_path(&self, hash: &str, file_name: &str) -> PathBuf {
+        self.root.join(ENGINES_DIR).join(hash).join(file_name)
+    }
+
+    pub fn engines(&self) -> Vec<StoredEngine> {
+        self.manifest
+            .engines
+            .iter()
+            .map(|(hash, entry)| StoredEngine {
+                hash: hash.clone(),
+                path: self.engine_path(hash, &entry.file_name).to_string_lossy().into_owned(),
+                entry: entry.clone(),
+            })
+            .collect()
+    }
+
+    /// Store the engine at `source` under `file_name` and return its hash and path. Adding
+    /// content that is already stored copies nothing and keeps its networks.
+    pub fn add_engine(&mut self, source: &Path, file_name: &str, origin: Option<String>) -> Result<StoredEngine, String> {
+        let hash = engine_registry::hash_file(source)?;
+        self.add_engine_with_hash(source, &hash, file_name, origin)?;
+        Ok(self.engines().into_iter().find(|engine| engine.hash == hash).expect("engine was just added"))
+    }
+
+    /// Pair the network at `source` with the stored engine `engine_hash`. A network with the
+    /// same file name replaces the engine's previous one.
+    pub fn add_network(&mut self, engine_hash: &str, source: &Path, file_name: &str) -> Result<PathBuf, String> {
+        let hash = engine_registry::hash_file(source)?;
+        self.add_network_with_hash(engine_hash, source, &hash, file_name)
+    }
+
+    /// Forget engines imported through the file picker whose paths are not in `in_use`, the
+    /// paths of the engines still configured. Synced engines are left to `sync`.
+    pub fn remove_unused(&mut self, in_use: &[String]) -> Vec<String> {
+        let unused: Vec<String> = self
+            .engines()
+            .into_iter()
+            .filter(|engine| engine.entry.source.is_none() && !in_use.contains(&engine.path))
+            .map(|engine| engine.hash)
+            .collect();
+        for hash in &unused {
+            self.manifest.engines.remove(hash);
+        }
+        unused
+    }
+
+    /// Store every file in `dirs`. Network files are paired with the engines from the same
+    /// directory, and engines synced from one of `dirs` whose source file is gone or now holds
+    /// other content are dropped.
+    /// Files whose size and modification time match the last scan are not read again, and a
+    /// file that cannot be read or copied is recorded in the report's `errors` and skipped.
+    pub fn sync(&mut self, dirs: &[PathBuf]) -> Result<SyncReport, String> {
+        let mut report = SyncReport::default();
+        let mut seen = BTreeSet::new();
+        for dir in dirs {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            let mut files: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).filter(|path| path.is_file()).collect();
+            files.sort();
+
+            let mut engines = Vec::new();
+            let mut networks = Vec::new();
+            for path in files {
+                let Some(file_name) = path.file_name().and_then(|name| name.to_str()).map(str::to_string) else {
+                    continue;
+                };
+                let source = path.to_string_lossy().into_owned();
+                seen.insert(source.clone());
+                let hash = match self.source_hash(&path, &source) {
+                    Ok(hash) => hash,
+                    Err(e) => {
+                        report.errors.push(e);
+                        continue;
+                    }
+                };
+                let stored = self.object_path(&hash).is_file();
+                if has_extension(&path, NETWORK_EXTENSIONS) {
+                    networks.push((path, hash, file_name));
+                } else {
+                    if let Err(e) = self.add_engine_with_hash(&path, &hash, &file_name, Some(source)) {
+                        report.errors.push(e);
+                        continue;
+                    }
+                    engines.push(hash);
+                }
+                if stored {
+                    report.unchanged += 1;
+                } else {
+                    report.copied += 1;
+                }
+            }
+            for engine_hash in &engines {
+                for (path, hash, file_name) in &networks {
+                    if let Err(e) = self.add_network_with_hash(engine_hash, path, hash, file_name) {
+                        report.errors.push(e);
+                    }
+                }
+            }
+        }
+
+        let in_scanned_dir = |source: &str| Path::new(source).parent().is_some_and(|parent| dirs.iter().any(|dir| dir == parent));
+        self.manifest.sources.retain(|source, _| seen.contains(source) || !in_scanned_dir(source));
+        // A synced engine goes with its source file, and with the old content of a replaced one
+        let sources = &self.manifest.sources;
+        self.manifest.engines.retain(|hash, entry| {
+            entry.source.as_deref().is_none_or(|source| {
+                if !in_scanned_dir(source) {
+                    return true;
+                }
+                seen.contains(source) && sources.get(source).is_some_and(|cached| &cached.hash == hash)
+            })
+        });
+        self.save()?;
+        report.engines = self.engines().into_iter().map(|engine| engine.path).collect();
+        Ok(report)
+    }
+
+    /// Import the flat directory engines were copied to before the store: synced copies at its
+    /// top level and one subdirectory per engine picked through the file picker, with the
+    /// networks beside each engine paired with it. Imported engines count as picked, so
+    /// `remove_unused` drops the ones no configured engine uses. The directory is deleted once
+    /// everything in it is imported, which makes this a no-op afterwards; on errors it is kept
+    /// so the next call retries.
+    pub fn import_legacy(&mut self, dir: &Path) -> Result<LegacyImport, String> {
+        let mut import = LegacyImport::default();
+        if !dir.is_dir() {
+            return Ok(import);
+        }
+        let mut groups = vec![dir.to_path_buf()];
+        groups.extend(read_dir_paths(dir)?.into_iter().filter(|path| path.is_dir()));
+        for group in groups {
+            let mut files: Vec<PathBuf> = read_dir_paths(&group)?.into_iter().filter(|path| path.is_file()).collect();
+            files.sort();
+            let (networks, engines): (Vec<PathBuf>, Vec<PathBuf>) =
+                files.into_iter().partition(|path| has_extension(path, NETWORK_EXTENSIONS));
+            for path in engines {
+                match self.import_legacy_engine(&path, &networks) {
+                    Ok(new_path) => {
+                        import.paths.insert(path.to_string_lossy().into_owned(), new_path);
+                    }
+                    Err(e) => import.errors.push(e),
+                }
+            }
+        }
+        self.save()?;
+        if import.errors.is_empty() {
+            fs::remove_dir_all(dir).map_err(|e| format!("Failed to remove {}: {}", dir.display(), e))?;
+        }
+        Ok(import)
+    }
+
+    /// Delete stored files and engine directories the manifest no longer refers to.
+    pub fn collect_garbage(&mut self) -> Result<GcReport, String> {
+        let mut report = GcReport::default();
+        let mut referenced = BTreeSet::new();
+        for (hash, entry) in &self.manifest.engines {
+            referenced.insert(hash.clone());
+            referenced.extend(entry.networks.iter().map(|network| network.hash.clone()));
+        }
+        self.manifest.sources.retain(|_, source| referenced.contains(&source.hash));
+
+        for entry in read_dir_paths(&self.root.join(OBJECTS_DIR))? {
+            let name = file_name_of(&entry);
+            if !referenced.contains(&name) {
+                report.freed_bytes += fs::metadata(&entry).map_or(0, |m| m.len());
+                remove_path(&entry, &mut report)?;
+            }
+        }
+        for dir in read_dir_paths(&self.root.join(ENGINES_DIR))? {
+            let Some(entry) = self.manifest.engines.get(&file_name_of(&dir)) else {
+                remove_path(&dir, &mut report)?;
+                continue;
+            };
+            for file in read_dir_paths(&dir)? {
+                let name = file_name_of(&file);
+                if name != entry.file_name && !entry.networks.iter().any(|network| network.file_name == name) {
+                    remove_path(&file, &mut report)?;
+                }
+            }
+        }
+        self.save()?;
+        Ok(report)
+    }
+
+    fn import_legacy_engine(&mut self, path: &Path, networks: &[PathBuf]) -> Result<String, String> {
+        let stored = self.add_engine(path, &file_name_of(path), None)?;
+        for network in networks {
+            self.add_network(&stored.hash, network, &file_name_of(network))?;
+        }
+        Ok(stored.path)
+    }
+
+    fn object_path(&self, hash: &str) -> PathBuf {
+        self.root.join(OBJECTS_DIR).join(hash)
+    }
+
+    /// Hash of a synced file, from the last scan when its size and modification time match.
+    fn source_hash(&mut self, path: &Path, source: &str) -> Result<String, String> {
+        let metadata = fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let size = metadata.len();
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos() as u64);
+        if let Some(cached) = self.manifest.sources.get(source) {
+            if cached.size == size && cached.modified == modified && self.object_path(&cached.hash).is_file() {
+                return Ok(cached.hash.clone());
+            }
+        }
+        let hash = engine_registry::hash_file(path)?;
+        self.manifest.sources.insert(source.to_string(), SourceEntry { hash: hash.clone(), size, modified });
+        Ok(hash)
+    }
+
+    /// Copy `source` to `objects/<hash>` unless that content is already stored.
+    fn store_object(&self, source: &Path, hash: &str, executable: bool) -> Result<PathBuf, String> {
+        let object = self.object_path(hash);
+        if !object.is_file() {
+            let temp = object.with_extension("tmp");
+            fs::copy(source, &temp).map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+            fs::rename(&temp, &object).map_err(|e| format!("Failed to store {}: {}", source.display(), e))?;
+        }
+        if executable {
+            set_executable(&object)?;
+        }
+        Ok(object)
+    }
+
+    fn add_engine_with_hash(&mut self, source: &Path, hash: &str, file_name: &str, origin: Option<String>) -> Result<(), String> {
+        let object = self.store_object(source, hash, true)?;
+        let entry = self.manifest.engines.entry(hash.to_string()).or_insert_with(|| EngineEntry {
+            file_name: file_name.to_string(),
+            source: origin.clone(),
+            networks: Vec::new(),
+            added_at: chrono::Utc::now().timestamp().max(0) as u64,
+        });
+        // A picked file keeps the engine even when the synced copy goes away
+        if origin.is_none() {
+            entry.source = None;
+        }
+        let file_name = entry.file_name.clone();
+        link(&object, &self.engine_path(hash, &file_name))
+    }
+
+    fn add_network_with_hash(&mut self, engine_hash: &str, source: &Path, hash: &str, file_name: &str) -> Result<PathBuf, String> {
+        let object = self.store_object(source, hash, false)?;
+        let path = self.engine_path(engine_hash, file_name);
+        let entry = self
+            .manifest
+            .engines
+            .get_mut(engine_hash)
+            .ok_or_else(|| format!("Engine {} is not in the store.", engine_hash))?;
+        let network = NetworkEntry {
+            hash: hash.to_string(),
+            file_name: file_name.to_string(),
+        };
+        if entry.networks.contains(&network) && path.is_file() {
+            return Ok(path);
+        }
+        entry.networks.retain(|n| n.file_name != file_name);
+        entry.networks.push(network);
+        let _ = fs::remove_file(&path);
+        link(&object, &path)?;
+        Ok(path)
+    }
+}
+
+/// Hard-link `object` to `path`, copying when the file system does not support links.
+fn link(object: &Path, path: &Path) -> Result<(), String> {
+    if path.is_file() {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    if fs::hard_link(object, path).is_err() {
+        fs::copy(object, path).map_err(|e| format!("Failed to copy {} to {}: {}", object.display(), path.display(), e))?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path).map_err(|e| e.to_string())?.permissions();
+    if permissions.mode() & 0o777 != 0o755 {
+        permissions.set_mode(0o755);
+        fs::set_permissions(path, permissions).map_err(|e| format!("Failed to set executable permission: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+fn read_dir_paths(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?;
+    Ok(entries.flatten().map(|entry| entry.path()).collect())
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+fn remove_path(path: &Path, report: &mut GcReport) -> Result<(), String> {
+    let removed = if path.is_dir() { fs::remove_dir_all(path) } else { fs::remove_file(path) };
+    removed.map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+    report.removed += 1;
+    Ok(())
+}
+
+/// Whether the extension of `path` is one of `extensions`, ignoring case.
+pub(crate) fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| extensions.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory under the system temp directory, removed when dropped.
+    struct TempRoot(PathBuf);
+
+    impl TempRoot {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("jieqibox-engine-store-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            TempRoot(path)
+        }
+
+        fn write(&self, relative: &str, content: &str) -> PathBuf {
+            let path = self.0.join(relative);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, content).unwrap();
+            path
+        }
+    }
+
+    impl Drop for TempRoot {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn sync_skips_unchanged_files() {
+        let temp = TempRoot::new("unchanged");
+        temp.write("source/engine", "engine v1");
+        let mut store = EngineStore::open(temp.0.join("store")).unwrap();
+        let first = store.sync(&[temp.0.join("source")]).unwrap();
+        assert_eq!((first.copied, first.unchanged), (1, 0));
+        let second = store.sync(&[temp.0.join("source")]).unwrap();
+        assert_eq!((second.copied, second.unchanged), (0, 1));
+        assert_eq!(second.engines, first.engines);
+        assert!(second.errors.is_empty());
+    }
+
+    #[test]
+    fn sync_rehashes_replaced_files() {
+        let temp = TempRoot::new("replaced");
+        let source = temp.write("source/engine", "engine v1");
+        let mut store = EngineStore::open(temp.0.join("store")).unwrap();
+        store.sync(&[temp.0.join("source")]).unwrap();
+        let old_hash = store.engines()[0].hash.clone();
+
+        fs::write(&source, "engine v2").unwrap();
+        let report = store.sync(&[temp.0.join("source")]).unwrap();
+        assert_eq!(report.copied, 1);
+        let engines = store.engines();
+        assert_eq!(engines.len(), 1);
+        assert_ne!(engines[0].hash, old_hash);
+        assert_eq!(engines[0].hash, engine_registry::hash_file(&source).unwrap());
+        assert_eq!(fs::read_to_string(&engines[0].path).unwrap(), "engine v2");
+    }
+
+    #[test]
+    fn sync_pairs_networks_with_engines() {
+        let temp = TempRoot::new("network");
+        temp.write("source/pikafish", "engine");
+        temp.write("source/pikafish.nnue", "network");
+        let mut store = EngineStore::open(temp.0.join("store")).unwrap();
+        let report = store.sync(&[temp.0.join("source")]).unwrap();
+        assert_eq!(report.engines.len(), 1);
+        let engine = &store.engines()[0];
+        assert_eq!(engine.entry.file_name, "pikafish");
+        assert_eq!(engine.entry.networks.len(), 1);
+        assert_eq!(engine.entry.networks[0].file_name, "pikafish.nnue");
+        let network = Path::new(&engine.path).with_file_name("pikafish.nnue");
+        assert_eq!(fs::read_to_string(network).unwrap(), "network");
+    }
+
+    #[test]
+    fn collect_garbage_removes_orphans() {
+        let temp = TempRoot::new("garbage");
+        temp.write("source/engine", "engine");
+        temp.write("store/objects/deadbeef", "orphan object");
+        temp.write("store/engines/deadbeef/engine", "orphan link");
+        let mut store = EngineStore::open(temp.0.join("store")).unwrap();
+        store.sync(&[temp.0.join("source")]).unwrap();
+        let engine = store.engines()[0].clone();
+        temp.write(&format!("store/engines/{}/stale.nnue", engine.hash), "stale");
+
+        let report = store.collect_garbage().unwrap();
+        assert_eq!(report.removed, 3);
+        assert_eq!(report.freed_bytes, "orphan object".len() as u64);
+        assert!(!temp.0.join("store/objects/deadbeef").exists());
+        assert!(!temp.0.join("store/engines/deadbeef").exists());
+        assert!(!Path::new(&engine.path).with_file_name("stale.nnue").exists());
+        assert!(Path::new(&engine.path).is_file());
+        assert!(store.object_path(&engine.hash).is_file());
+    }
+
+    #[test]
+    fn remove_unused_keeps_synced_engines() {
+        let temp = TempRoot::new("unused");
+        temp.write("source/synced", "synced engine");
+        let picked = temp.write("picked/engine", "picked engine");
+        let kept = temp.write("picked/kept", "kept engine");
+        let mut store = EngineStore::open(temp.0.join("store")).unwrap();
+        store.sync(&[temp.0.join("source")]).unwrap();
+        let picked = store.add_engine(&picked, "engine", None).unwrap();
+        let kept = store.add_engine(&kept, "kept", None).unwrap();
+
+        assert_eq!(store.remove_unused(std::slice::from_ref(&kept.path)), [picked.hash]);
+        let mut names: Vec<String> = store.engines().into_iter().map(|engine| engine.entry.file_name).collect();
+        names.sort();
+        assert_eq!(names, ["kept", "synced"]);
+    }
+
+    #[test]
+    fn import_legacy_moves_engines_once() {
+        let temp = TempRoot::new("legacy");
+        let synced = temp.write("legacy/engine", "synced copy");
+        let picked = temp.write("legacy/Pikafish_1700000000000/pikafish", "picked engine");
+        temp.write("legacy/Pikafish_1700000000000/pikafish.nnue", "network");
+        let mut store = EngineStore::open(temp.0.join("store")).unwrap();
+
+        let import = store.import_legacy(&temp.0.join("legacy")).unwrap();
+        assert!(import.errors.is_empty());
+        assert_eq!(import.paths.len(), 2);
+        let new_path = &import.paths[&picked.to_string_lossy().into_owned()];
+        assert_eq!(fs::read_to_string(new_path).unwrap(), "picked engine");
+        assert_eq!(fs::read_to_string(Path::new(new_path).with_file_name("pikafish.nnue")).unwrap(), "network");
+        assert!(import.paths.contains_key(&synced.to_string_lossy().into_owned()));
+        assert!(!temp.0.join("legacy").exists());
+        assert!(store.engines().iter().all(|engine| engine.entry.source.is_none()));
+
+        assert!(store.import_legacy(&temp.0.join("legacy")).unwrap().paths.is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+// src-tauri/src/fair_play.rs
+// Keeps engines from learning identities of dark pieces their side could not know. The only
+// such identities a `position` command can carry are those of a side's own dark pieces that
+// the opponent captured: in the FEN's captured pool and in the extended UCI moves of the
+// opponent's captures. Both are turned back into unknown pieces of the hidden pool.
+use crate::fen::{Fen, FenLayout};
+use crate::rules::{self, Board, Pool, Role, Side};
+use serde::{Deserialize, Serialize};
+
+/// Fair-play settings of a session.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub struct FairPlayConfig {
+    /// Side whose knowledge the engine gets; the side to move after the moves when `None`.
+    #[serde(default)]
+    pub side: Option<Side>,
+}
+
+/// FEN of a position as `side` may see it: identities of its own dark pieces that the
+/// opponent captured are unknown to it, so they stay in its hidden pool.
+pub fn engine_view(board: &Board, side: Side) -> String {
+    let mut view = board.clone();
+    hide_captured(&mut view.hidden, &mut view.captured, side);
+    view.to_fen()
+}
+
+fn hide_captured(hidden: &mut Pool, captured: &mut Pool, side: Side) {
+    for role in Role::ALL {
+        hidden.add(side, role, captured.get(side, role));
+        captured.set(side, role, 0);
+    }
+}
+
+/// Rewrite a `position` command so it reveals no more than the configured side may know.
+/// Commands whose FEN or moves cannot be parsed are rejected, as they cannot be checked.
+pub fn filter_position(command: &str, config: FairPlayConfig) -> Result<String, String> {
+    let reject = |reason: String| format!("Fair play: rejected '{}': {}", command, reason);
+    let mut tokens = command.split_whitespace();
+    if tokens.next() != Some("position") {
+        return Ok(command.to_string());
+    }
+    let fen_text: Option<String> = match tokens.next() {
+        Some("startpos") => None,
+        Some("fen") => Some(tokens.by_ref().take_while(|t| *t != "moves").collect::<Vec<_>>().join(" ")),
+        _ => return Err(reject("expected 'startpos' or 'fen'".to_string())),
+    };
+    // `take_while` consumed the `moves` keyword after a FEN; skip it after `startpos`
+    let moves: Vec<&str> = tokens.skip_while(|t| *t == "moves").collect();
+
+    let mut fen = Fen::parse(fen_text.as_deref().unwrap_or(rules::START_FEN)).map_err(|e| reject(e.to_string()))?;
+    let viewer = config.side.unwrap_or(if moves.len().is_multiple_of(2) {
+        fen.side_to_move
+    } else {
+        fen.side_to_move.opponent()
+    });
+
+    // Only the new layout has a captured pool
+    let hide = fen.layout == FenLayout::New && Role::ALL.iter().any(|&role| fen.captured.get(viewer, role) > 0);
+    if hide {
+        hide_captured(&mut fen.hidden, &mut fen.captured, viewer);
+    }
+
+    let mut mover = fen.side_to_move;
+    let mut filtered = Vec::with_capacity(moves.len());
+    for uci in moves {
+        let (mv, reveal, captured) =
+            rules::parse_extended_uci(uci, mover).ok_or_else(|| reject(format!("invalid move '{}'", uci)))?;
+        if captured.is_some() && mover != viewer {
+            let mut stripped = mv.to_string();
+            if let Some(role) = reveal {
+                stripped.push(role.fen_char(mover));
+            }
+            filtered.push(stripped);
+        } else {
+            filtered.push(uci.to_string());
+        }
+        mover = mover.opponent();
+    }
+
+    let mut out = match fen_text {
+        None => "position startpos".to_string(),
+        Some(_) if hide => format!("position fen {}", fen),
+        Some(text) => format!("position fen {}", text),
+    };
+    if !filtered.is_empty() {
+        out.push_str(" moves ");
+        out.push_str(&filtered.join(" "));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Start position after Red captured Black's dark piece on a9, which was a chariot.
+    const CAPTURED_FEN: &str =
+        "1xxxkxxxx/9/1x5x1/x1x1x1x1x/9/9/X1X1X1X1X/1X5X1/9/XXXXKXXXX w A2B2N2R2C2P5a2b2n2r1c2p5 r1 0 1";
+    /// The same position as Black may see it.
+    const CAPTURED_BLACK_VIEW: &str =
+        "1xxxkxxxx/9/1x5x1/x1x1x1x1x/9/9/X1X1X1X1X/1X5X1/9/XXXXKXXXX w R2r2N2n2B2b2A2a2C2c2P5p5 - 0 1";
+
+    fn filter(command: &str, side: Option<Side>) -> Result<String, String> {
+        filter_position(command, FairPlayConfig { side })
+    }
+
+    #[test]
+    fn engine_view_returns_captured_identities_to_the_pool() {
+        let board = Board::from_fen(CAPTURED_FEN).unwrap();
+        assert_eq!(engine_view(&board, Side::Black), CAPTURED_BLACK_VIEW);
+        // Red captured the piece and saw what it was
+        assert_eq!(engine_view(&board, Side::Red), board.to_fen());
+    }
+
+    #[test]
+    fn hides_the_captured_pool_from_its_owner() {
+        let command = format!("position fen {}", CAPTURED_FEN);
+        assert_eq!(filter(&command, None).unwrap(), command);
+        assert_eq!(filter(&command, Some(Side::Black)).unwrap(), format!("position fen {}", CAPTURED_BLACK_VIEW));
+        // After one move Black is to move, and so the viewer
+        assert_eq!(
+            filter(&format!("{} moves h2e2C", command), None).unwrap(),
+            format!("position fen {} moves h2e2C", CAPTURED_BLACK_VIEW)
+        );
+    }
+
+    #[test]
+    fn strips_identities_of_pieces_the_opponent_captured() {
+        // Red's cannon reveals itself and captures Black's dark horse on h9
+        assert_eq!(filter("position startpos moves h2h9Cn", None).unwrap(), "position startpos moves h2h9C");
+        assert_eq!(filter("position startpos moves h2h9Cn i9i8r", None).unwrap(), "position startpos moves h2h9Cn i9i8r");
+        assert_eq!(
+            filter("position startpos moves h2h9Cn i9i8r", Some(Side::Black)).unwrap(),
+            "position startpos moves h2h9C i9i8r"
+        );
+        let fen = format!("position fen {} moves h2h9Cn", rules::START_FEN);
+        assert_eq!(filter(&fen, None).unwrap(), format!("position fen {} moves h2h9C", rules::START_FEN));
+        // Black to move in the FEN: the viewer after one move is Red
+        let fen = format!("position fen {} moves h7h0cN", rules::START_FEN.replace(" w ", " b "));
+        assert_eq!(filter(&fen, None).unwrap(), fen.replace("h7h0cN", "h7h0c"));
+    }
+
+    #[test]
+    fn rejects_what_it_cannot_check() {
+        let rejected = |command: &str| filter(command, None).unwrap_err();
+        assert!(rejected("position fen 9/9 w").contains("the board has 2 rows"));
+        assert!(rejected("position startpos moves h2h9Cn zz").ends_with("invalid move 'zz'"));
+        assert!(rejected("position startpos moves h2e2CNR").ends_with("invalid move 'h2e2CNR'"));
+        assert!(rejected("position current").ends_with("expected 'startpos' or 'fen'"));
+        assert!(rejected("position").ends_with("expected 'startpos' or 'fen'"));
+        assert_eq!(filter("go depth 10", Some(Side::Red)).unwrap(), "go depth 10");
+    }
+}
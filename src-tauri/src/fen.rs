@@ -0,0 +1,536 @@
+// src-tauri/src/fen.rs
+// Typed parser for JieqiBox FENs, in the new (`board side hidden captured halfmove fullmove`)
+// and the old (`board hidden side - - halfmove fullmove`) layout, mirroring `fenValidator.ts`.
+// Errors name the field, the character offset in the FEN and the reason, so they can be shown
+// next to the input; `Fen::problems` adds the checks a well-formed FEN can still fail.
+use crate::hidden_pool;
+use crate::rules::{self, Piece, Pool, Role, Side, Square, COLS, ROWS};
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FenField {
+    Board,
+    Side,
+    Hidden,
+    Captured,
+    Halfmove,
+    Fullmove,
+}
+
+impl FenField {
+    fn name(self) -> &'static str {
+        match self {
+            FenField::Board => "board",
+            FenField::Side => "side to move",
+            FenField::Hidden => "hidden pool",
+            FenField::Captured => "captured pool",
+            FenField::Halfmove => "halfmove clock",
+            FenField::Fullmove => "fullmove number",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct FenError {
+    pub field: FenField,
+    /// Character offset in the FEN where the problem starts, from 0.
+    pub position: usize,
+    pub reason: String,
+}
+
+impl FenError {
+    fn new(field: FenField, position: usize, reason: impl Into<String>) -> Self {
+        FenError {
+            field,
+            position,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid FEN {} at character {}: {}", self.field.name(), self.position + 1, self.reason)
+    }
+}
+
+impl std::error::Error for FenError {}
+
+impl From<FenError> for String {
+    fn from(error: FenError) -> String {
+        error.to_string()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FenLayout {
+    #[default]
+    New,
+    Old,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fen {
+    pub squares: [[Option<Piece>; COLS]; ROWS],
+    pub side_to_move: Side,
+    /// Identities of the dark pieces still on the board.
+    pub hidden: Pool,
+    /// Identities of dark pieces captured before they were revealed; always empty in the old layout.
+    pub captured: Pool,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+    pub layout: FenLayout,
+    /// Where each piece and field was found, for `problems`.
+    square_offsets: [[usize; COLS]; ROWS],
+    hidden_offset: usize,
+}
+
+impl Fen {
+    /// Parse a FEN in either layout. Missing trailing fields take their defaults; fields past
+    /// the last one of the layout are an error.
+    pub fn parse(fen: &str) -> Result<Fen, FenError> {
+        let fields = split_fields(fen);
+        let end = fen.chars().count();
+        let Some(&(board_at, board_field)) = fields.first() else {
+            return Err(FenError::new(FenField::Board, 0, "the FEN is empty"));
+        };
+        let Some(&(second_at, second)) = fields.get(1) else {
+            return Err(FenError::new(FenField::Side, end, "the side to move is missing"));
+        };
+
+        let layout = if second == "w" || second == "b" { FenLayout::New } else { FenLayout::Old };
+        // Indices into `fields` of the side, the pools and the clocks, and the layout's field count
+        let (side, hidden, captured, halfmove, fullmove, field_count) = match layout {
+            // `board side hidden halfmove fullmove`, without a captured pool, is accepted as well
+            FenLayout::New if fields.len() == 5 => (1, Some(2), None, Some(3), Some(4), 5),
+            FenLayout::New => (1, Some(2), Some(3), Some(4), Some(5), 6),
+            // `board side` with anything but `w` or `b` as the side
+            FenLayout::Old if fields.len() == 2 => {
+                return Err(FenError::new(FenField::Side, second_at, format!("expected 'w' or 'b', found '{}'", second)))
+            }
+            FenLayout::Old => (2, Some(1), None, Some(5), Some(6), 7),
+        };
+        if let Some(&(at, extra)) = fields.get(field_count) {
+            return Err(FenError::new(
+                FenField::Fullmove,
+                at,
+                format!("unexpected field '{}' after the fullmove number", extra),
+            ));
+        }
+
+        let (squares, square_offsets) = parse_board(board_field, board_at)?;
+        let (side_at, side_field) = fields[side];
+        let side_to_move = match side_field {
+            "w" => Side::Red,
+            "b" => Side::Black,
+            _ => {
+                return Err(FenError::new(
+                    FenField::Side,
+                    side_at,
+                    format!("expected 'w' or 'b', found '{}'", side_field),
+                ))
+            }
+        };
+        let field = |index: Option<usize>| index.and_then(|i| fields.get(i).copied());
+        let hidden_field = field(hidden);
+        let hidden_pool = hidden_field.map(|(at, s)| parse_pool(s, FenField::Hidden, at)).transpose()?;
+        let captured_pool = field(captured).map(|(at, s)| parse_pool(s, FenField::Captured, at)).transpose()?;
+        let halfmove_clock = field(halfmove)
+            .map(|(at, s)| parse_number(s, FenField::Halfmove, at, 0))
+            .transpose()?;
+        let fullmove_number = field(fullmove)
+            .map(|(at, s)| parse_number(s, FenField::Fullmove, at, 1))
+            .transpose()?;
+
+        Ok(Fen {
+            squares,
+            side_to_move,
+            hidden: hidden_pool.unwrap_or_default(),
+            captured: captured_pool.unwrap_or_default(),
+            halfmove_clock: halfmove_clock.unwrap_or(0),
+            fullmove_number: fullmove_number.unwrap_or(1),
+            layout,
+            square_offsets,
+            hidden_offset: hidden_field.map_or(end, |(at, _)| at),
+        })
+    }
+
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        self.squares[square.row as usize][square.col as usize]
+    }
+
+    /// All occupied squares with their pieces.
+    pub fn pieces(&self) -> impl Iterator<Item = (Square, Piece)> + '_ {
+        (0..ROWS).flat_map(move |row| {
+            (0..COLS).filter_map(move |col| self.squares[row][col].map(|piece| (Square::new(row, col), piece)))
+        })
+    }
+
+    /// Dark pieces of `side` on the board.
+    pub fn dark_count(&self, side: Side) -> usize {
+        self.pieces().filter(|(_, p)| p.side == side && p.is_dark()).count()
+    }
+
+    /// Problems of a well-formed FEN that make it impossible in Jieqi: a side without exactly
+    /// one king in its palace, a dark piece off its side's starting squares (dark pieces are
+    /// revealed by their first move), or a hidden pool `hidden_pool::check_pool` rejects.
+    /// Empty when the position is fine.
+    ///
+    /// Revealed pawns are not checked for impossible rows, unlike in Xiangqi: a dark piece on a
+    /// chariot, horse, elephant, advisor or cannon square can be revealed as a pawn, so a pawn
+    /// can stand on any row of its own half, and from there reach every row ahead of it.
+    pub fn problems(&self) -> Vec<FenError> {
+        let mut problems = Vec::new();
+        let board_error = |square: Square, reason: String| {
+            let offset = self.square_offsets[square.row as usize][square.col as usize];
+            FenError::new(FenField::Board, offset, reason)
+        };
+        for side in [Side::Red, Side::Black] {
+            let name = side.name();
+            let kings: Vec<Square> = self
+                .pieces()
+                .filter(|(_, p)| p.side == side && p.role == Some(Role::King))
+                .map(|(square, _)| square)
+                .collect();
+            match kings.as_slice() {
+                [] => problems.push(FenError::new(FenField::Board, 0, format!("{} has no king", name))),
+                [king] if !in_palace(*king, side) => {
+                    problems.push(board_error(*king, format!("the {} king on {} is outside its palace", name, king)))
+                }
+                [_] => {}
+                [_, extra, ..] => {
+                    problems.push(board_error(*extra, format!("too many kings: {} has {}", name, kings.len())))
+                }
+            }
+
+            for (square, _) in self.pieces().filter(|(_, p)| p.side == side && p.is_dark()) {
+                if !is_dark_start(square, side) {
+                    problems.push(board_error(
+                        square,
+                        format!("a {} dark piece on {} is not on one of its starting squares", name, square),
+                    ));
+                }
+            }
+        }
+        problems.extend(
+            hidden_pool::check_pool(self)
+                .into_iter()
+                .map(|issue| FenError::new(FenField::Hidden, self.hidden_offset, issue.reason)),
+        );
+        problems
+    }
+}
+
+impl fmt::Display for Fen {
+    /// The FEN in its own layout, with every field written out.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let board = board_field(&self.squares);
+        let side = self.side_to_move.fen_char();
+        match self.layout {
+            FenLayout::New => write!(
+                f,
+                "{} {} {} {} {} {}",
+                board,
+                side,
+                self.hidden.to_fen(),
+                self.captured.to_fen(),
+                self.halfmove_clock,
+                self.fullmove_number
+            ),
+            FenLayout::Old => write!(
+                f,
+                "{} {} {} - - {} {}",
+                board,
+                self.hidden.to_fen(),
+                side,
+                self.halfmove_clock,
+                self.fullmove_number
+            ),
+        }
+    }
+}
+
+/// Piece placement field of a board.
+pub fn board_field(squares: &[[Option<Piece>; COLS]; ROWS]) -> String {
+    let mut rows = Vec::with_capacity(ROWS);
+    for row in squares {
+        let mut s = String::new();
+        let mut empty = 0;
+        for square in row {
+            match square {
+                None => empty += 1,
+                Some(piece) => {
+                    if empty > 0 {
+                        s.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    s.push(piece_char(*piece));
+                }
+            }
+        }
+        if empty > 0 {
+            s.push_str(&empty.to_string());
+        }
+        rows.push(s);
+    }
+    rows.join("/")
+}
+
+pub fn piece_char(piece: Piece) -> char {
+    match piece.role {
+        Some(role) => role.fen_char(piece.side),
+        None if piece.side == Side::Red => 'X',
+        None => 'x',
+    }
+}
+
+/// Parse a pool field such as `A2B2r1` (`-` for empty). Each piece letter appears at most
+/// once and is followed by its count.
+pub fn parse_pool(field: &str, which: FenField, at: usize) -> Result<Pool, FenError> {
+    let mut pool = Pool::default();
+    if field == "-" {
+        return Ok(pool);
+    }
+    let mut seen = Vec::new();
+    let chars: Vec<char> = field.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let (role, side) = Role::from_fen_char(c)
+            .ok_or_else(|| FenError::new(which, at + i, format!("'{}' is not a piece letter", c)))?;
+        if seen.contains(&c) {
+            return Err(FenError::new(which, at + i, format!("'{}' is listed twice", c)));
+        }
+        seen.push(c);
+        let digits: String = chars[i + 1..].iter().take_while(|c| c.is_ascii_digit()).collect();
+        if digits.is_empty() {
+            return Err(FenError::new(which, at + i + 1, format!("'{}' has no count", c)));
+        }
+        let count = digits
+            .parse::<u8>()
+            .map_err(|_| FenError::new(which, at + i + 1, format!("count {} of '{}' is too large", digits, c)))?;
+        pool.set(side, role, count);
+        i += 1 + digits.len();
+    }
+    Ok(pool)
+}
+
+type Squares = [[Option<Piece>; COLS]; ROWS];
+
+fn parse_board(field: &str, at: usize) -> Result<(Squares, [[usize; COLS]; ROWS]), FenError> {
+    let error = |offset: usize, reason: String| FenError::new(FenField::Board, at + offset, reason);
+    let mut squares = [[None; COLS]; ROWS];
+    let mut offsets = [[at; COLS]; ROWS];
+    let row_count = field.split('/').count();
+    if row_count != ROWS {
+        return Err(error(0, format!("the board has {} rows, expected {}", row_count, ROWS)));
+    }
+    let mut offset = 0;
+    for (row, row_str) in field.split('/').enumerate() {
+        let row_start = offset;
+        let mut col = 0;
+        for c in row_str.chars() {
+            if let Some(n) = c.to_digit(10) {
+                if n == 0 {
+                    return Err(error(offset, "'0' is not a square count".to_string()));
+                }
+                col += n as usize;
+            } else {
+                let piece = match c {
+                    'X' => Piece { side: Side::Red, role: None },
+                    'x' => Piece { side: Side::Black, role: None },
+                    _ => {
+                        let (role, side) = Role::from_fen_char(c)
+                            .ok_or_else(|| error(offset, format!("'{}' is not a piece letter", c)))?;
+                        Piece { side, role: Some(role) }
+                    }
+                };
+                if col < COLS {
+                    squares[row][col] = Some(piece);
+                    offsets[row][col] = at + offset;
+                }
+                col += 1;
+            }
+            if col > COLS {
+                return Err(error(offset, format!("row {} is longer than {} squares", row + 1, COLS)));
+            }
+            offset += 1;
+        }
+        if col < COLS {
+            return Err(error(row_start, format!("row {} has {} squares, expected {}", row + 1, col, COLS)));
+        }
+        // The `/` after the row
+        offset += 1;
+    }
+    Ok((squares, offsets))
+}
+
+fn parse_number(field: &str, which: FenField, at: usize, min: u32) -> Result<u32, FenError> {
+    match field.parse::<u32>() {
+        Ok(n) if n >= min => Ok(n),
+        Ok(n) => Err(FenError::new(which, at, format!("{} is below {}", n, min))),
+        Err(_) => Err(FenError::new(which, at, format!("expected a number, found '{}'", field))),
+    }
+}
+
+/// Whitespace-separated fields with their character offsets.
+fn split_fields(fen: &str) -> Vec<(usize, &str)> {
+    let mut fields = Vec::new();
+    let mut start = None;
+    for (index, (byte, c)) in fen.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((index, byte)),
+            (true, Some((at, from))) => {
+                fields.push((at, &fen[from..byte]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((at, from)) = start {
+        fields.push((at, &fen[from..]));
+    }
+    fields
+}
+
+fn in_palace(square: Square, side: Side) -> bool {
+    let rows = if side == Side::Red { 7..=9 } else { 0..=2 };
+    rows.contains(&square.row) && (3..=5).contains(&square.col)
+}
+
+/// Whether a dark piece of `side` may stand on `square`: one of its side's starting squares
+/// other than the king's.
+fn is_dark_start(square: Square, side: Side) -> bool {
+    let own_half = if side == Side::Red { square.row >= 5 } else { square.row < 5 };
+    own_half && rules::start_role(square).is_some_and(|role| role != Role::King)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOARD: &str = "xxxxkxxxx/9/1x5x1/x1x1x1x1x/9/9/X1X1X1X1X/1X5X1/9/XXXXKXXXX";
+    const OLD_START_FEN: &str =
+        "xxxxkxxxx/9/1x5x1/x1x1x1x1x/9/9/X1X1X1X1X/1X5X1/9/XXXXKXXXX A2B2N2R2C2P5a2b2n2r2c2p5 w - - 0 1";
+
+    fn assert_error(fen: &str, field: FenField, position: usize, reason: &str) {
+        let error = Fen::parse(fen).expect_err(fen);
+        assert_eq!(error, FenError::new(field, position, reason), "{}", fen);
+    }
+
+    fn problem_reasons(fen: &str) -> Vec<String> {
+        Fen::parse(fen).unwrap().problems().into_iter().map(|problem| problem.reason).collect()
+    }
+
+    #[test]
+    fn parses_new_layout() {
+        let fen = Fen::parse(rules::START_FEN).unwrap();
+        assert_eq!(fen.layout, FenLayout::New);
+        assert_eq!(fen.side_to_move, Side::Red);
+        assert_eq!(fen.hidden.get(Side::Red, Role::Pawn), 5);
+        assert_eq!(fen.hidden.get(Side::Black, Role::Chariot), 2);
+        assert_eq!(fen.captured, Pool::default());
+        assert_eq!((fen.halfmove_clock, fen.fullmove_number), (0, 1));
+        assert_eq!(fen.dark_count(Side::Black), 15);
+        // Pools are written in a fixed order
+        assert_eq!(fen.to_string(), format!("{} w R2r2N2n2B2b2A2a2C2c2P5p5 - 0 1", BOARD));
+        assert!(fen.problems().is_empty());
+
+        let fen = Fen::parse(&format!("{} b A2B2N2R2C2P5a2b2n2r2c2p5 3 7", BOARD)).unwrap();
+        assert_eq!(fen.side_to_move, Side::Black);
+        assert_eq!((fen.halfmove_clock, fen.fullmove_number), (3, 7));
+    }
+
+    #[test]
+    fn parses_old_layout() {
+        let fen = Fen::parse(OLD_START_FEN).unwrap();
+        assert_eq!(fen.layout, FenLayout::Old);
+        assert_eq!(fen.side_to_move, Side::Red);
+        assert_eq!(fen.hidden, Fen::parse(rules::START_FEN).unwrap().hidden);
+        assert_eq!(fen.to_string(), format!("{} R2r2N2n2B2b2A2a2C2c2P5p5 w - - 0 1", BOARD));
+        assert!(fen.problems().is_empty());
+    }
+
+    #[test]
+    fn reports_missing_and_extra_fields() {
+        assert_error("", FenField::Board, 0, "the FEN is empty");
+        assert_error(BOARD, FenField::Side, BOARD.len(), "the side to move is missing");
+        assert_error(&format!("{} z", BOARD), FenField::Side, BOARD.len() + 1, "expected 'w' or 'b', found 'z'");
+        assert_error(
+            &format!("{} A2 z - - 0 1", BOARD),
+            FenField::Side,
+            BOARD.len() + 4,
+            "expected 'w' or 'b', found 'z'",
+        );
+        assert_error(
+            &format!("{} extra", rules::START_FEN),
+            FenField::Fullmove,
+            rules::START_FEN.len() + 1,
+            "unexpected field 'extra' after the fullmove number",
+        );
+    }
+
+    #[test]
+    fn reports_board_errors() {
+        assert_error("9/9 w", FenField::Board, 0, "the board has 2 rows, expected 10");
+        let with_row = |row: usize, content: &str| {
+            let mut rows: Vec<&str> = BOARD.split('/').collect();
+            rows[row] = content;
+            format!("{} w", rows.join("/"))
+        };
+        assert_error(&with_row(1, "09"), FenField::Board, 10, "'0' is not a square count");
+        assert_error(&with_row(0, "xxxxkxxxz"), FenField::Board, 8, "'z' is not a piece letter");
+        assert_error(&with_row(0, "xxxxkxxxx1"), FenField::Board, 9, "row 1 is longer than 9 squares");
+        assert_error(&with_row(1, "8"), FenField::Board, 10, "row 2 has 8 squares, expected 9");
+    }
+
+    #[test]
+    fn reports_pool_and_clock_errors() {
+        let hidden_at = BOARD.len() + 3;
+        let fen = |hidden: &str, clocks: &str| format!("{} w {} - {}", BOARD, hidden, clocks);
+        assert_error(&fen("Q2", "0 1"), FenField::Hidden, hidden_at, "'Q' is not a piece letter");
+        assert_error(&fen("A2A1", "0 1"), FenField::Hidden, hidden_at + 2, "'A' is listed twice");
+        assert_error(&fen("A", "0 1"), FenField::Hidden, hidden_at + 1, "'A' has no count");
+        assert_error(&fen("A300", "0 1"), FenField::Hidden, hidden_at + 1, "count 300 of 'A' is too large");
+        assert_error(
+            &format!("{} w - q2 0 1", BOARD),
+            FenField::Captured,
+            BOARD.len() + 5,
+            "'q' is not a piece letter",
+        );
+        assert_error(&fen("-", "x 1"), FenField::Halfmove, hidden_at + 4, "expected a number, found 'x'");
+        assert_error(&fen("-", "0 0"), FenField::Fullmove, hidden_at + 6, "0 is below 1");
+    }
+
+    #[test]
+    fn reports_impossible_positions() {
+        const POOL: &str = "A2B2N2R2C2P5a2b2n2r2c2p5";
+        let with_rows = |rows: &[(usize, &str)], pool: &str| {
+            let mut board: Vec<&str> = BOARD.split('/').collect();
+            for &(row, content) in rows {
+                board[row] = content;
+            }
+            format!("{} w {} - 0 1", board.join("/"), pool)
+        };
+        assert_eq!(problem_reasons(&with_rows(&[(0, "xxxx1xxxx")], POOL)), ["Black has no king"]);
+        assert_eq!(
+            problem_reasons(&with_rows(&[(8, "4K4")], POOL)),
+            ["too many kings: Red has 2", "Red has 0 K hidden, 2 revealed and 0 captured dark, but only 1 in all"]
+        );
+        assert_eq!(
+            problem_reasons(&with_rows(&[(0, "xxxx1xxxx"), (4, "4k4")], POOL)),
+            ["the Black king on e5 is outside its palace"]
+        );
+        assert_eq!(
+            problem_reasons(&with_rows(&[(6, "1XX1X1X1X")], POOL)),
+            ["a Red dark piece on b3 is not on one of its starting squares"]
+        );
+        assert_eq!(
+            problem_reasons(&with_rows(&[], "A2B2N2R2C2P4a2b2n2r2c2p5")),
+            ["pool count mismatch: 14 hidden pieces for 15 X on board"]
+        );
+    }
+}
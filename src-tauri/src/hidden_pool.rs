@@ -0,0 +1,262 @@
+// src-tauri/src/hidden_pool.rs
+// Consistency of the hidden pool with the rest of the position. Each side has the standard
+// sixteen pieces; those neither revealed on the board, nor captured while dark (the captured
+// pool), nor captured after being revealed are exactly the ones that can still be hidden.
+// That fixes the pool a position admits and bounds what a hand-entered pool may list.
+use crate::fen::{self, Fen, FenField};
+use crate::rules::{Pool, Role, Side};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Pieces of a role each side starts with (`INITIAL_PIECE_COUNTS`).
+pub fn standard_count(role: Role) -> u8 {
+    match role {
+        Role::King => 1,
+        Role::Pawn => 5,
+        _ => 2,
+    }
+}
+
+/// An impossible count, for one role of a side or for the side's pool as a whole.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct PoolIssue {
+    pub side: Side,
+    /// FEN letter of the role, or `None` when the issue is the size of the pool.
+    pub piece: Option<char>,
+    pub reason: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PoolReconstruction {
+    /// The derived pool as a FEN field.
+    pub hidden: String,
+    /// Count of each FEN letter in the derived pool, zeros included.
+    pub counts: BTreeMap<char, u8>,
+    /// The position's FEN with the derived pool.
+    pub fen: String,
+}
+
+/// Revealed pieces of each side on the board.
+fn revealed(fen: &Fen) -> Pool {
+    let mut pool = Pool::default();
+    for (_, piece) in fen.pieces() {
+        if let Some(role) = piece.role {
+            pool.add(piece.side, role, 1);
+        }
+    }
+    pool
+}
+
+fn plural(count: usize, word: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, word)
+    } else {
+        format!("{} {}s", count, word)
+    }
+}
+
+/// Check the FEN's hidden pool against the standard composition: no role may have more
+/// pieces revealed, captured and hidden than a side starts with, kings are never hidden, and
+/// there must be an identity for every dark piece. More identities than dark pieces is
+/// fine, as a side does not learn what its dark pieces captured by the opponent were.
+pub fn check_pool(fen: &Fen) -> Vec<PoolIssue> {
+    let on_board = revealed(fen);
+    let mut issues = Vec::new();
+    for side in [Side::Red, Side::Black] {
+        for role in Role::ALL {
+            let letter = role.fen_char(side);
+            let (hidden, shown, captured) = (
+                fen.hidden.get(side, role),
+                on_board.get(side, role),
+                fen.captured.get(side, role),
+            );
+            let standard = standard_count(role);
+            let issue = |reason: String| PoolIssue {
+                side,
+                piece: Some(letter),
+                reason,
+            };
+            if role == Role::King && hidden > 0 {
+                issues.push(issue(format!("{} lists {} {}, but kings are never hidden", side.name(), hidden, letter)));
+            } else if shown + captured + hidden > standard {
+                issues.push(issue(format!(
+                    "{} has {} {} hidden, {} revealed and {} captured dark, but only {} in all",
+                    side.name(),
+                    hidden,
+                    letter,
+                    shown,
+                    captured,
+                    standard
+                )));
+            }
+        }
+        let hidden = fen.hidden.pieces(side).len();
+        let dark = fen.dark_count(side);
+        if hidden < dark {
+            issues.push(PoolIssue {
+                side,
+                piece: None,
+                reason: format!(
+                    "pool count mismatch: {} for {} {} on board",
+                    plural(hidden, "hidden piece"),
+                    dark,
+                    if side == Side::Red { 'X' } else { 'x' }
+                ),
+            });
+        }
+    }
+    issues
+}
+
+/// The hidden pool a position admits: per side, the standard pieces less those revealed on
+/// the board, those in the captured pool and `captured_revealed`, the revealed pieces that
+/// were captured. A side without dark pieces has nothing left to reveal and gets an empty pool.
+pub fn reconstruct_pool(fen: &Fen, captured_revealed: &Pool) -> Result<Pool, Vec<PoolIssue>> {
+    let on_board = revealed(fen);
+    let mut pool = Pool::default();
+    let mut issues = Vec::new();
+    for side in [Side::Red, Side::Black] {
+        let dark = fen.dark_count(side);
+        if dark == 0 {
+            continue;
+        }
+        for role in Role::ALL.into_iter().filter(|&role| role != Role::King) {
+            let seen = on_board.get(side, role) + fen.captured.get(side, role) + captured_revealed.get(side, role);
+            let standard = standard_count(role);
+            match standard.checked_sub(seen) {
+                Some(left) => pool.set(side, role, left),
+                None => issues.push(PoolIssue {
+                    side,
+                    piece: Some(role.fen_char(side)),
+                    reason: format!(
+                        "{} has {} {} revealed or captured, but only {} in all",
+                        side.name(),
+                        seen,
+                        role.fen_char(side),
+                        standard
+                    ),
+                }),
+            }
+        }
+        let left = pool.pieces(side).len();
+        if left < dark {
+            issues.push(PoolIssue {
+                side,
+                piece: None,
+                reason: format!(
+                    "{} has {} but only {} left to hide under them",
+                    side.name(),
+                    plural(dark, "dark piece"),
+                    plural(left, "piece")
+                ),
+            });
+        }
+    }
+    if issues.is_empty() {
+        Ok(pool)
+    } else {
+        Err(issues)
+    }
+}
+
+/// `reconstruct_pool` for a FEN, with the captured revealed pieces given as a pool field.
+pub fn reconstruct(fen: &str, captured_revealed: Option<&str>) -> Result<PoolReconstruction, String> {
+    let mut parsed = Fen::parse(fen)?;
+    let captured_revealed = match captured_revealed {
+        Some(field) => fen::parse_pool(field, FenField::Captured, 0)
+            .map_err(|e| format!("Invalid captured revealed pieces '{}': {}", field, e.reason))?,
+        None => Pool::default(),
+    };
+    let pool = reconstruct_pool(&parsed, &captured_revealed)
+        .map_err(|issues| issues.into_iter().map(|issue| issue.reason).collect::<Vec<_>>().join("; "))?;
+    let mut counts = BTreeMap::new();
+    for side in [Side::Red, Side::Black] {
+        for role in Role::ALL {
+            counts.insert(role.fen_char(side), pool.get(side, role));
+        }
+    }
+    parsed.hidden = pool;
+    Ok(PoolReconstruction {
+        hidden: pool.to_fen(),
+        counts,
+        fen: parsed.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// After Red's cannon revealed itself on e2 and captured Black's dark horse on h9.
+    const BOARD: &str = "xxxxkxx1x/9/1x5x1/x1x1x1x1x/9/9/X1X1X1X1X/1X2C4/9/XXXXKXXXX";
+
+    fn issues(fen: &str, captured_revealed: &Pool) -> Vec<PoolIssue> {
+        reconstruct_pool(&Fen::parse(fen).unwrap(), captured_revealed).unwrap_err()
+    }
+
+    #[test]
+    fn derives_the_pool_from_board_and_captures() {
+        let fen = Fen::parse(&format!("{} b - n1 1 1", BOARD)).unwrap();
+        let pool = reconstruct_pool(&fen, &Pool::default()).unwrap();
+        assert_eq!(pool.to_fen(), "R2r2N2n1B2b2A2a2C1c2P5p5");
+
+        // A red pawn revealed and then captured is not in the pool either
+        let board = BOARD.replace("X1X1X1X1X", "X1X1X1X2");
+        let result = reconstruct(&format!("{} b - n1 1 1", board), Some("P1")).unwrap();
+        assert_eq!(result.hidden, "R2r2N2n1B2b2A2a2C1c2P4p5");
+        assert_eq!((result.counts[&'P'], result.counts[&'n'], result.counts[&'K']), (4, 1, 0));
+        assert_eq!(result.fen, format!("{} b R2r2N2n1B2b2A2a2C1c2P4p5 n1 1 1", board));
+        // A side without dark pieces has nothing left to hide
+        let result = reconstruct("4k4/9/9/9/9/9/X8/9/9/R3K4 w - - 0 1", None).unwrap();
+        assert_eq!(result.hidden, "R1N2B2A2C2P5");
+    }
+
+    #[test]
+    fn reports_over_counted_roles() {
+        assert_eq!(
+            issues("4k4/9/9/9/9/9/X8/9/9/RRR1K4 w - - 0 1", &Pool::default()),
+            [PoolIssue {
+                side: Side::Red,
+                piece: Some('R'),
+                reason: "Red has 3 R revealed or captured, but only 2 in all".to_string(),
+            }]
+        );
+        let mut captured_revealed = Pool::default();
+        captured_revealed.set(Side::Black, Role::Cannon, 1);
+        assert_eq!(
+            issues(&format!("{} b - n1 1 1", BOARD.replace("1x5x1", "1c5c1")), &captured_revealed)[0].reason,
+            "Black has 3 c revealed or captured, but only 2 in all"
+        );
+        assert_eq!(
+            issues(&crate::rules::START_FEN.replace(" - 0 1", " R2N2 0 1"), &Pool::default()),
+            [PoolIssue {
+                side: Side::Red,
+                piece: None,
+                reason: "Red has 15 dark pieces but only 11 pieces left to hide under them".to_string(),
+            }]
+        );
+        assert!(reconstruct("4k4/9/9/9/9/9/X8/9/9/RRR1K4 w - - 0 1", Some("Q1"))
+            .unwrap_err()
+            .starts_with("Invalid captured revealed pieces 'Q1'"));
+    }
+
+    #[test]
+    fn checks_a_user_pool_against_the_derived_one() {
+        // The pool of the start position still lists both red cannons
+        let fen = Fen::parse(&format!("{} b A2B2N2R2C2P5a2b2n1r2c2p5 n1 1 1", BOARD)).unwrap();
+        assert_eq!(
+            check_pool(&fen),
+            [PoolIssue {
+                side: Side::Red,
+                piece: Some('C'),
+                reason: "Red has 2 C hidden, 1 revealed and 0 captured dark, but only 2 in all".to_string(),
+            }]
+        );
+        let fen = Fen::parse(&format!("{} b A2B2N2R2C1P4a2b2n1r2c2p5 n1 1 1", BOARD)).unwrap();
+        assert_eq!(check_pool(&fen)[0].reason, "pool count mismatch: 13 hidden pieces for 14 X on board");
+        // The derived pool replaces it
+        let result = reconstruct(&fen.to_string(), None).unwrap();
+        assert!(check_pool(&Fen::parse(&result.fen).unwrap()).is_empty());
+        assert_eq!(result.hidden, "R2r2N2n1B2b2A2a2C1c2P5p5");
+    }
+}
@@ -2,21 +2,80 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 #![allow(clippy::uninlined_format_args)]
 
-use tauri_plugin_shell::ShellExt;
-use tauri_plugin_shell::process::{CommandChild, CommandEvent};
 use tauri::{AppHandle, Emitter};
-use std::sync::{Arc, Mutex};
-use tauri::async_runtime;
 use std::process::Command;
-use encoding_rs::GBK;
 use std::path::Path;
 use std::fs;
 use base64::Engine;
-#[cfg(target_os = "android")]
-use std::os::unix::fs::PermissionsExt;
 use clipboard::{ClipboardContext, ClipboardProvider};
 
-mod opening_book;
+pub mod encoding;
+use encoding::EngineEncoding;
+
+pub mod spawn_options;
+use spawn_options::SpawnOptions;
+
+pub mod engine_bridge;
+use engine_bridge::RemoteEngine;
+
+mod engine;
+use engine::{EngineLaunch, EngineOutput, EngineSessions};
+
+pub mod uci;
+
+pub mod ucci;
+use ucci::EngineDialect;
+
+mod watchdog;
+use watchdog::WatchdogConfig;
+
+mod output_batch;
+use output_batch::BatchConfig;
+
+mod transcript;
+use transcript::TranscriptConfig;
+
+pub mod engine_registry;
+use engine_registry::{EngineProfile, EngineProtocol};
+
+pub mod engine_discovery;
+use engine_discovery::{DiscoveryConfig, DiscoveryResult};
+
+pub mod engine_store;
+#[cfg(target_os = "android")]
+use engine_store::EngineStore;
+
+pub mod fen;
+use fen::FenError;
+
+pub mod hidden_pool;
+use hidden_pool::{PoolIssue, PoolReconstruction};
+
+pub mod rules;
+use rules::{PositionSummary, Side};
+
+pub mod fair_play;
+use fair_play::FairPlayConfig;
+
+pub mod perft;
+use perft::{PerftResult, RevealMode};
+
+pub mod reveal_odds;
+use reveal_odds::RevealOdds;
+
+pub mod clock;
+use clock::{ClockControl, ClockEngine, ClockService, ClockState};
+
+pub mod notation;
+
+pub mod sprt;
+pub mod match_runner;
+use match_runner::{start_match, stop_match, MatchState, SessionEngine};
+
+pub mod benchmark;
+use benchmark::{BenchmarkConfig, BenchmarkResult, BenchmarkState, BenchmarkStore};
+
+pub mod opening_book;
 use opening_book::{JieqiOpeningBook, MoveData, OpeningBookStats, AddEntryRequest};
 
 mod linker;
@@ -25,11 +84,6 @@ use linker::{
     simulate_click, simulate_move, get_window_info
 };
 
-// -------------------------------------------------------------
-// type definition for the engine process state
-type EngineProcess = Arc<Mutex<Option<CommandChild>>>;
-// -------------------------------------------------------------
-
 /// Check if the engine file exists and is a file on Android.
 /// This is a prerequisite for setting permissions and spawning.
 #[cfg(target_os = "android")]
@@ -53,69 +107,6 @@ fn check_android_engine_file(path: &str) -> Result<(), String> {
     Ok(())
 }
 
-/// Copy a file from a user-accessible directory to the app's internal storage.
-/// Used for the legacy engine scanning mechanism.
-#[cfg(target_os = "android")]
-fn copy_file_to_internal_storage(source_path_str: &str, app_handle: &AppHandle) -> Result<String, String> {
-    let source_path = Path::new(source_path_str);
-    if !source_path.exists() {
-        let error_msg = format!("Source file not found: {}", source_path.display());
-        let _ = app_handle.emit("engine-output", format!("[DEBUG] {}", error_msg));
-        return Err(error_msg);
-    }
-
-    // Use dynamic bundle identifier for a robust internal storage path
-    let bundle_identifier = &app_handle.config().identifier;
-    let internal_dir = format!("/data/data/{}/files/engines", bundle_identifier);
-    if let Err(e) = fs::create_dir_all(&internal_dir) {
-        let error_msg = format!("Failed to create internal directory: {}", e);
-        let _ = app_handle.emit("engine-output", format!("[DEBUG] {}", error_msg));
-        return Err(error_msg);
-    }
-
-    // Generate destination path using the original filename
-    let filename = source_path.file_name()
-        .ok_or_else(|| "Invalid source path".to_string())?
-        .to_str()
-        .ok_or_else(|| "Invalid filename encoding".to_string())?;
-    let dest_path_str = format!("{}/{}", internal_dir, filename);
-    let dest_path = Path::new(&dest_path_str);
-
-    let _ = app_handle.emit("engine-output", format!("[DEBUG] Copying file from {} to {}", source_path.display(), dest_path.display()));
-
-    // Copy the file
-    if let Err(e) = fs::copy(source_path, dest_path) {
-        let error_msg = format!("Failed to copy file: {}", e);
-        let _ = app_handle.emit("engine-output", format!("[DEBUG] {}", error_msg));
-        return Err(error_msg);
-    }
-
-    let _ = app_handle.emit("engine-output", "[DEBUG] Setting executable permission...");
-    
-    // Set executable permissions (rwxr-xr-x) which is crucial on Android/Linux
-    match fs::metadata(dest_path) {
-        Ok(metadata) => {
-            let mut permissions = metadata.permissions();
-            // Set permissions to 0o755
-            permissions.set_mode(0o755);   
-
-            if let Err(e) = fs::set_permissions(dest_path, permissions) {
-                let error_msg = format!("Failed to set executable permission: {}", e);
-                let _ = app_handle.emit("engine-output", format!("[DEBUG] {}", error_msg));
-                return Err(error_msg);
-            }
-        },
-        Err(e) => {
-            let error_msg = format!("Failed to get metadata for setting permissions: {}", e);
-            let _ = app_handle.emit("engine-output", format!("[DEBUG] {}", error_msg));
-            return Err(error_msg);
-        }
-    }
-    
-    let _ = app_handle.emit("engine-output", format!("[DEBUG] Successfully copied and made executable: {}", dest_path.display()));
-    Ok(dest_path_str)
-}
-
 /// Save game notation to Android's external, user-accessible storage.
 #[tauri::command]
 async fn save_game_notation(content: String, filename: String, app: AppHandle) -> Result<String, String> {
@@ -219,6 +210,42 @@ fn get_opening_book_db_path(app: &AppHandle) -> Result<String, String> {
     }
 }
 
+/// Get the path to the engine registry file, which varies by platform.
+fn get_engine_registry_path(app: &AppHandle) -> Result<String, String> {
+    if cfg!(target_os = "android") {
+        // On Android, use the app's private internal data directory
+        let bundle_identifier = &app.config().identifier;
+        Ok(format!("/data/data/{}/files/engine_registry.json", bundle_identifier))
+    } else {
+        // On desktop, use the same directory as the config file
+        Ok("engine_registry.json".to_string())
+    }
+}
+
+/// Get the path to the benchmark results file, which varies by platform.
+fn get_benchmark_path(app: &AppHandle) -> Result<String, String> {
+    if cfg!(target_os = "android") {
+        // On Android, use the app's private internal data directory
+        let bundle_identifier = &app.config().identifier;
+        Ok(format!("/data/data/{}/files/benchmarks.json", bundle_identifier))
+    } else {
+        // On desktop, use the same directory as the config file
+        Ok("benchmarks.json".to_string())
+    }
+}
+
+/// Get the directory for engine transcript logs, which varies by platform.
+fn get_engine_log_dir(app: &AppHandle) -> Result<String, String> {
+    if cfg!(target_os = "android") {
+        // On Android, use the app's private internal data directory
+        let bundle_identifier = &app.config().identifier;
+        Ok(format!("/data/data/{}/files/engine-logs", bundle_identifier))
+    } else {
+        // On desktop, use the same directory as the config file
+        Ok("engine-logs".to_string())
+    }
+}
+
 /// Load configuration from the config file.
 #[tauri::command]
 async fn load_config(app: AppHandle) -> Result<String, String> {
@@ -313,88 +340,75 @@ fn get_user_engine_directory() -> String {
     "/storage/emulated/0/jieqibox/engines".to_string()
 }
 
-/// Scans user-facing directories for engines, copies them to internal storage,
+/// Get the root of the content-addressed engine store in the app's internal storage.
+#[cfg(target_os = "android")]
+fn get_engine_store_root(app_handle: &AppHandle) -> String {
+    format!("/data/data/{}/files/engine_store", app_handle.config().identifier)
+}
+
+/// Get the flat directory engines were copied to before the engine store.
+#[cfg(target_os = "android")]
+fn get_legacy_engine_directory(app_handle: &AppHandle) -> String {
+    format!("/data/data/{}/files/engines", app_handle.config().identifier)
+}
+
+/// Scans user-facing directories for engines, stores the ones whose content is new,
 /// and then returns a list of all engines available in internal storage.
 #[cfg(target_os = "android")]
 fn sync_and_list_engines(app_handle: &AppHandle) -> Result<Vec<String>, String> {
     let bundle_identifier = &app_handle.config().identifier;
     let source_dirs = vec![
-        get_user_engine_directory(),
-        format!("/storage/emulated/0/Android/data/{}/files/engines", bundle_identifier),
+        std::path::PathBuf::from(get_user_engine_directory()),
+        std::path::PathBuf::from(format!("/storage/emulated/0/Android/data/{}/files/engines", bundle_identifier)),
     ];
-    let internal_dir_str = format!("/data/data/{}/files/engines", bundle_identifier);
-    
-    let _ = app_handle.emit("engine-output", format!("[DEBUG] Syncing engines. Internal dir: {}. Source dirs: {:?}", internal_dir_str, source_dirs));
-    
-    // Ensure the internal engine directory exists
-    if let Err(e) = fs::create_dir_all(&internal_dir_str) {
-        let error_msg = format!("Failed to create internal directory '{}': {}", internal_dir_str, e);
-        let _ = app_handle.emit("engine-output", format!("[DEBUG] {}", error_msg));
-        return Err(error_msg);
-    } else {
-        let _ = app_handle.emit("engine-output", format!("[DEBUG] Internal directory created/exists: {}", internal_dir_str));
-    }
-
-    // Iterate over all possible source directories
-    for user_dir in &source_dirs {
-        let _ = app_handle.emit("engine-output", format!("[DEBUG] Checking source directory: {}", user_dir));
-        let user_path = Path::new(user_dir);
-
-        if !user_path.exists() {
-            let _ = app_handle.emit("engine-output", format!("[DEBUG] Source directory does not exist, skipping: {}", user_dir));
-            continue;
-        }
-
-        if let Ok(entries) = fs::read_dir(user_path) {
-            for entry_result in entries {
-                if let Ok(entry) = entry_result {
-                    let path = entry.path();
-                    if path.is_file() {
-                        if let Err(e) = copy_file_to_internal_storage(path.to_str().unwrap_or(""), app_handle) {
-                            let _ = app_handle.emit("engine-output", format!("[DEBUG] Failed to copy file {}: {}", path.display(), e));
-                        }
-                    }
-                }
-            }
-        }
+    let store_root = get_engine_store_root(app_handle);
+    let _ = app_handle.emit("engine-output", EngineOutput::broadcast(format!("[DEBUG] Syncing engines. Store: {}. Source dirs: {:?}", store_root, source_dirs)));
+
+    let mut store = EngineStore::open(&store_root)?;
+    let report = store.sync(&source_dirs)?;
+    let gc = store.collect_garbage()?;
+    let _ = app_handle.emit("engine-output", EngineOutput::broadcast(format!(
+        "[DEBUG] Engine sync: {} copied, {} unchanged, {} stale files removed ({} bytes)",
+        report.copied, report.unchanged, gc.removed, gc.freed_bytes
+    )));
+    for error in &report.errors {
+        let _ = app_handle.emit("engine-output", EngineOutput::broadcast(format!("[DEBUG] Engine sync skipped a file: {}", error)));
     }
-
-    // List all files in the internal directory and return their full paths
-    let mut available_engines = Vec::new();
-    if let Ok(entries) = fs::read_dir(&internal_dir_str) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if path.is_file() {
-                if let Some(path_str) = path.to_str() {
-                    available_engines.push(path_str.to_string());
-                }
-            }
-        }
-    }
-    
-    let _ = app_handle.emit("engine-output", format!("[DEBUG] Available internal engines: {:?}", available_engines));
-    Ok(available_engines)
+    let _ = app_handle.emit("engine-output", EngineOutput::broadcast(format!("[DEBUG] Available internal engines: {:?}", report.engines)));
+    Ok(report.engines)
 }
 
-/// Explicitly kills the currently running engine process, if any.
+/// Explicitly kills the engine process of a session, if any, and closes the session: its
+/// watchdog and other settings are dropped.
 #[tauri::command]
-async fn kill_engine(process_state: tauri::State<'_, EngineProcess>) -> Result<(), String> {
-    if let Some(child) = process_state.lock().unwrap().take() {
-        let _ = child.kill();
+async fn kill_engine(session_id: String, sessions: tauri::State<'_, EngineSessions>) -> Result<(), String> {
+    if let Some(session) = sessions.remove(&session_id) {
+        let _ = session.child.kill();
     }
+    sessions.forget(&session_id);
     Ok(())
 }
 
-/// Spawns a new engine process with a given path and arguments.
+/// Spawns a new engine process for a session with a given path and arguments.
+/// `encoding` applies to both its output and the commands sent to it; `spawn_options` sets
+/// the environment, working directory, nice level and CPU affinity. With `remote`, the
+/// session connects to an engine served by `jieqibox-cli serve` instead, and `path` is only a label.
+/// A `ucci` dialect has the session translate the UCI commands it gets into UCCI and back.
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 async fn spawn_engine(
+    session_id: String,
     path: String,
     args: Vec<String>,
+    encoding: Option<EngineEncoding>,
+    spawn_options: Option<SpawnOptions>,
+    remote: Option<RemoteEngine>,
+    dialect: Option<EngineDialect>,
     app: AppHandle,
-    process_state: tauri::State<'_, EngineProcess>,
+    sessions: tauri::State<'_, EngineSessions>,
 ) -> Result<(), String> {
     if cfg!(target_os = "android") {
-        let _ = app.emit("engine-output", format!("[DEBUG] Spawning engine: Path={}, Args={:?}", path, args));
+        let _ = app.emit("engine-output", EngineOutput::debug(&session_id, format!("[DEBUG] Spawning engine: Path={}, Args={:?}", path, args)));
     }
     
     // The path must be an absolute, accessible file path
@@ -402,76 +416,255 @@ async fn spawn_engine(
 
     #[cfg(target_os = "android")]
     {
-        if let Err(e) = check_android_engine_file(&final_path) {
-            let _ = app.emit("engine-output", format!("[DEBUG] Engine file validation failed: {}", e));
-            return Err(e);
+        if remote.is_none() {
+            if let Err(e) = check_android_engine_file(&final_path) {
+                let _ = app.emit("engine-output", EngineOutput::debug(&session_id, format!("[DEBUG] Engine file validation failed: {}", e)));
+                return Err(e);
+            }
+            let _ = app.emit("engine-output", EngineOutput::debug(&session_id, "[DEBUG] Engine file validation passed."));
         }
-        let _ = app.emit("engine-output", "[DEBUG] Engine file validation passed.");
     }
     
-    // Ensure any previous engine process is terminated before starting a new one
-    kill_engine(process_state.clone()).await.ok();
-    
-    // The engine's working directory should be its parent directory
-    let engine_dir = Path::new(&final_path)
-        .parent()
-        .ok_or_else(|| "Failed to get engine directory".to_string())?
-        .to_str()
-        .ok_or_else(|| "Failed to convert engine directory to string".to_string())?;
-    
-    // Spawn the new process
-    let (mut rx, child) = match app.shell().command(&final_path)
-        .args(args)
-        .current_dir(engine_dir)
-        .spawn() 
-    {
-        Ok(result) => result,
-        Err(e) => {
-            let error_msg = format!("Failed to spawn engine: {}", e);
-            if cfg!(target_os = "android") {
-                let _ = app.emit("engine-output", format!("[DEBUG] {}", error_msg));
-            }
-            return Err(error_msg);
-        }
-    };
+    // Ensure any previous engine process of this session is terminated before starting a new
+    // one; the session's settings stay, as they may have been set for this spawn
+    if let Some(previous) = sessions.remove(&session_id) {
+        let _ = previous.child.kill();
+    }
 
-    // Store the new child process in the shared state
-    *process_state.lock().unwrap() = Some(child);
-    
-    // Spawn an async task to listen for the engine's stdout/stderr
-    let app_clone = app.clone();
-    async_runtime::spawn(async move {
-        while let Some(event) = rx.recv().await {
-            if let CommandEvent::Stdout(buf) | CommandEvent::Stderr(buf) = event {
-                // Decode output using GBK for Windows, UTF-8 for others
-                let text = if cfg!(target_os = "windows") {
-                    let (cow, ..) = GBK.decode(&buf);
-                    cow.into_owned()
-                } else {
-                    String::from_utf8_lossy(&buf).into_owned()
-                };
-                let _ = app_clone.emit("engine-output", text);
-            }
+    // Start the process; its output is forwarded and its health watched from background tasks
+    let mut launch = EngineLaunch::new(final_path, args);
+    launch.encoding = encoding.unwrap_or_default();
+    launch.spawn = spawn_options.unwrap_or_default();
+    launch.remote = remote;
+    launch.dialect = dialect.unwrap_or_default();
+    if let Err(error_msg) = engine::spawn_session(&app, &session_id, launch, false) {
+        if cfg!(target_os = "android") {
+            let _ = app.emit("engine-output", EngineOutput::debug(&session_id, format!("[DEBUG] {}", error_msg)));
         }
-    });
+        return Err(error_msg);
+    }
 
     Ok(())
 }
 
-/// Sends a command string to the running engine process.
+/// Sends a command string to the engine process of a session.
 #[tauri::command]
 async fn send_to_engine(
+    session_id: String,
     command: String,
-    process_state: tauri::State<'_, EngineProcess>,
+    sessions: tauri::State<'_, EngineSessions>,
 ) -> Result<(), String> {
-    if let Some(child) = process_state.lock().unwrap().as_mut() {
-        child
-            .write(format!("{}\n", command).as_bytes())
-            .map_err(|e| format!("Failed to write to engine: {}", e))?;
-        Ok(())
-    } else {
-        Err("Engine not running.".into())
+    sessions.send(&session_id, &command)
+}
+
+/// Configures the health checks of a session's engine. An interval of 0 turns them off, as
+/// they are by default; the setting lasts until the session is killed or its engine exits.
+#[tauri::command]
+async fn set_engine_watchdog(
+    session_id: String,
+    interval_ms: u64,
+    timeout_ms: u64,
+    sessions: tauri::State<'_, EngineSessions>,
+) -> Result<(), String> {
+    sessions.set_watchdog(&session_id, WatchdogConfig { interval_ms, timeout_ms });
+    Ok(())
+}
+
+/// Sets how long a session's stdout lines are gathered before they are emitted together.
+/// `bestmove` and `readyok` are always emitted at once; an interval of 0 turns batching off.
+/// The setting lasts until the session is killed or its engine exits on its own.
+#[tauri::command]
+async fn set_engine_output_batching(
+    session_id: String,
+    interval_ms: u64,
+    sessions: tauri::State<'_, EngineSessions>,
+) -> Result<(), String> {
+    sessions.set_batching(&session_id, BatchConfig { interval_ms });
+    Ok(())
+}
+
+/// Turns the fair-play filter of a session on or off. While on, `position` commands sent to the
+/// engine never reveal identities of dark pieces beyond what `side` (by default the side to
+/// move) could know; ones that cannot be checked are rejected. The setting lasts until the
+/// session is killed or its engine exits on its own.
+#[tauri::command]
+async fn set_engine_fair_play(
+    session_id: String,
+    enabled: bool,
+    side: Option<Side>,
+    sessions: tauri::State<'_, EngineSessions>,
+) -> Result<(), String> {
+    sessions.set_fair_play(&session_id, enabled.then_some(FairPlayConfig { side }));
+    Ok(())
+}
+
+/// Turns the I/O transcript of a session on or off, from its next spawn on. The log is
+/// rotated once it reaches `max_bytes`, keeping `max_files` older logs. The setting lasts until
+/// the session is killed or its engine exits on its own.
+/// Returns the directory the logs are written to.
+#[tauri::command]
+async fn set_engine_transcript(
+    session_id: String,
+    enabled: bool,
+    max_bytes: u64,
+    max_files: u32,
+    app: AppHandle,
+    sessions: tauri::State<'_, EngineSessions>,
+) -> Result<String, String> {
+    let dir = get_engine_log_dir(&app)?;
+    let config = enabled.then(|| TranscriptConfig { dir: dir.clone(), max_bytes, max_files });
+    sessions.set_transcript(&session_id, config);
+    Ok(dir)
+}
+
+/// Runs the `uci`, `ucci` or `jai` handshake of an engine binary and records what it reported in
+/// the engine registry. A binary already registered under the same hash is not started again
+/// unless `refresh` is set.
+#[tauri::command]
+async fn probe_engine(
+    path: String,
+    args: Vec<String>,
+    protocol: EngineProtocol,
+    timeout_ms: u64,
+    refresh: bool,
+    app: AppHandle,
+) -> Result<EngineProfile, String> {
+    let registry_path = get_engine_registry_path(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        engine_registry::probe_cached(
+            Path::new(&registry_path),
+            &path,
+            &args,
+            protocol,
+            std::time::Duration::from_millis(timeout_ms),
+            refresh,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Returns the registered profile of an engine binary, if it has been probed before.
+#[tauri::command]
+async fn get_engine_profile(path: String, app: AppHandle) -> Result<Option<EngineProfile>, String> {
+    let registry_path = get_engine_registry_path(&app)?;
+    engine_registry::lookup(Path::new(&registry_path), &path)
+}
+
+/// Scans directories for engine executables, probes each with a UCI and then JAI handshake and
+/// pairs them with the network files next to them. Probed engines are added to the registry.
+#[tauri::command]
+async fn discover_engines(config: DiscoveryConfig, app: AppHandle) -> Result<DiscoveryResult, String> {
+    let registry_path = get_engine_registry_path(&app)?;
+    tauri::async_runtime::spawn_blocking(move || engine_discovery::discover(Path::new(&registry_path), &config))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Returns the directories `discover_engines` scans when given none.
+#[tauri::command]
+async fn get_default_engine_dirs() -> Result<Vec<String>, String> {
+    Ok(engine_discovery::default_dirs())
+}
+
+/// Runs a benchmark in the `benchmark` engine session and stores the result under the engine's
+/// hash and this machine. Each searched position is reported through `benchmark-position`.
+#[tauri::command]
+async fn run_benchmark(
+    config: BenchmarkConfig,
+    app: AppHandle,
+    state: tauri::State<'_, BenchmarkState>,
+) -> Result<BenchmarkResult, String> {
+    let stop = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    {
+        let mut current = state.stop.lock().unwrap();
+        if current.is_some() {
+            return Err("A benchmark is already running.".to_string());
+        }
+        *current = Some(stop.clone());
     }
+    let store_path = get_benchmark_path(&app)?;
+    let worker = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let hash = engine_registry::hash_file(Path::new(&config.engine.path))?;
+        let mut engine = SessionEngine::spawn(&worker, "benchmark", &config.engine)?;
+        let result = benchmark::run(&mut engine, &config, hash, &stop, &mut |index, position| {
+            let _ = worker.emit("benchmark-position", serde_json::json!({ "index": index, "result": position }));
+        })?;
+        BenchmarkStore::record(Path::new(&store_path), result.clone())?;
+        Ok(result)
+    })
+    .await
+    .map_err(|e| e.to_string());
+    state.stop.lock().unwrap().take();
+    result?
+}
+
+/// Asks the running benchmark to stop; it then fails with "Benchmark stopped." and stores nothing.
+#[tauri::command]
+async fn stop_benchmark(state: tauri::State<'_, BenchmarkState>) -> Result<(), String> {
+    if let Some(stop) = state.stop.lock().unwrap().as_ref() {
+        stop.store(true, std::sync::atomic::Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// Returns the stored benchmark runs, oldest first: those of the binary at `path`, or all.
+#[tauri::command]
+async fn get_benchmarks(path: Option<String>, app: AppHandle) -> Result<Vec<BenchmarkResult>, String> {
+    let hash = path.map(|path| engine_registry::hash_file(Path::new(&path))).transpose()?;
+    let store = BenchmarkStore::load(Path::new(&get_benchmark_path(&app)?))?;
+    Ok(store.runs(hash.as_deref()))
+}
+
+/// Starts a new game clock with `side_to_move` on move, replacing any previous one. With
+/// `engine`, that session's `bestmove` replies press the clock for the engine's side.
+#[tauri::command]
+async fn start_clock(
+    control: ClockControl,
+    side_to_move: Side,
+    engine: Option<ClockEngine>,
+    app: AppHandle,
+    clock: tauri::State<'_, ClockService>,
+) -> Result<ClockState, String> {
+    clock.start(&app, control, side_to_move, engine)
+}
+
+/// Ends the move of `side`; ignored when `side` is not on move (e.g. the engine's clock
+/// was already pressed by its `bestmove`).
+#[tauri::command]
+async fn press_clock(side: Side, clock: tauri::State<'_, ClockService>) -> Result<Option<ClockState>, String> {
+    Ok(clock.update(|c, now| {
+        c.press(side, now);
+    }))
+}
+
+#[tauri::command]
+async fn pause_clock(clock: tauri::State<'_, ClockService>) -> Result<Option<ClockState>, String> {
+    Ok(clock.update(|c, now| c.pause(now)))
+}
+
+#[tauri::command]
+async fn resume_clock(clock: tauri::State<'_, ClockService>) -> Result<Option<ClockState>, String> {
+    Ok(clock.update(|c, now| c.start(now)))
+}
+
+#[tauri::command]
+async fn stop_clock(clock: tauri::State<'_, ClockService>) -> Result<(), String> {
+    clock.stop();
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_clock_state(clock: tauri::State<'_, ClockService>) -> Result<Option<ClockState>, String> {
+    Ok(clock.update(|_, _| {}))
+}
+
+/// Returns the `go` arguments for the side to move (`wtime ... btime ...`, `movetime ...`,
+/// `depth ...`), or `None` without a clock.
+#[tauri::command]
+async fn get_clock_go_params(clock: tauri::State<'_, ClockService>) -> Result<Option<String>, String> {
+    Ok(clock.go_params())
 }
 
 /// Get the path to a directory where users can manually place engines.
@@ -506,6 +699,30 @@ async fn scan_android_engines(app: AppHandle) -> Result<Vec<String>, String> {
     sync_and_list_engines(&app)
 }
 
+/// Forget stored engines picked through SAF that no configured engine uses any more,
+/// given the paths of the engines still configured, and delete the files left unreferenced.
+#[cfg(target_os = "android")]
+#[tauri::command]
+async fn prune_android_engines(in_use: Vec<String>, app: AppHandle) -> Result<engine_store::GcReport, String> {
+    let mut store = EngineStore::open(get_engine_store_root(&app))?;
+    store.remove_unused(&in_use);
+    store.collect_garbage()
+}
+
+/// Move the engines copied to internal storage before the engine store into it, and return
+/// their new paths by old path so the frontend can update configured engines. The old
+/// directory is deleted once everything in it is imported, so later calls return nothing.
+#[cfg(target_os = "android")]
+#[tauri::command]
+async fn import_legacy_android_engines(app: AppHandle) -> Result<std::collections::BTreeMap<String, String>, String> {
+    let mut store = EngineStore::open(get_engine_store_root(&app))?;
+    let import = store.import_legacy(Path::new(&get_legacy_engine_directory(&app)))?;
+    for error in &import.errors {
+        let _ = app.emit("engine-output", EngineOutput::broadcast(format!("[DEBUG] Legacy engine import skipped a file: {}", error)));
+    }
+    Ok(import.paths)
+}
+
 /// Emits an event to the Android native side to request a file via SAF.
 #[cfg(target_os = "android")]
 #[tauri::command]
@@ -531,55 +748,28 @@ async fn handle_saf_file_result(
     has_nnue: bool,
     app: AppHandle,
 ) -> Result<(), String> {
-    let _ = app.emit("engine-output", format!("[DEBUG] SAF result for engine '{}': TempPath={}, Filename={}", name, temp_file_path, filename));
+    let _ = app.emit("engine-output", EngineOutput::broadcast(format!("[DEBUG] SAF result for engine '{}': TempPath={}, Filename={}", name, temp_file_path, filename)));
 
     if temp_file_path.is_empty() {
         return Err("SAF file processing failed: temporary path is empty.".to_string());
     }
 
-    // Generate a unique identifier for this specific engine instance to be used as the directory name.
-    // This prevents conflicts if an engine file and a desired directory have the same name,
-    // and also allows adding the same engine multiple times.
-    let engine_instance_id = format!("{}_{}", name, chrono::Utc::now().timestamp_millis());
-
-    // Define the final destination directory for the engine using the unique ID.
-    let bundle_identifier = &app.config().identifier;
-    let engine_base_dir = format!("/data/data/{}/files/engines/{}", bundle_identifier, &engine_instance_id);
-
-    // Create the engine-specific directory
-    if let Err(e) = fs::create_dir_all(&engine_base_dir) {
-        let error_msg = format!("Failed to create final engine directory: {}", e);
-        let _ = app.emit("engine-output", format!("[DEBUG] {}", error_msg));
-        return Err(error_msg);
-    }
-    
-    // Define the final path for the engine executable
-    let final_path_str = format!("{}/{}", engine_base_dir, &filename);
-
-    // Move the file from the temporary location to the final destination
-    if let Err(e) = fs::rename(&temp_file_path, &final_path_str) {
-        let error_msg = format!("Failed to move engine file from temp to final destination: {}", e);
-        let _ = app.emit("engine-output", format!("[DEBUG] {}", error_msg));
-        // Fallback to copy if rename fails (e.g., cross-device link)
-        if let Err(copy_err) = fs::copy(&temp_file_path, &final_path_str) {
-             let copy_error_msg = format!("Fallback copy also failed: {}", copy_err);
-             let _ = app.emit("engine-output", format!("[DEBUG] {}", copy_error_msg));
-             return Err(copy_error_msg);
-        } else {
-            // Copy succeeded, remove the original temp file
-            let _ = fs::remove_file(&temp_file_path);
-        }
-    }
-
-    // Set executable permission on the final file
-    let final_path = Path::new(&final_path_str);
-    let mut perms = fs::metadata(final_path).map_err(|e| e.to_string())?.permissions();
-    perms.set_mode(0o755);
-    fs::set_permissions(final_path, perms).map_err(|e| e.to_string())?;
+    // Store the engine by content: adding the same binary again reuses its copy and directory,
+    // and the engine's hash identifies it when its NNUE file arrives.
+    let mut store = EngineStore::open(get_engine_store_root(&app))?;
+    let stored = store.add_engine(Path::new(&temp_file_path), &filename, None);
+    let _ = fs::remove_file(&temp_file_path);
+    let stored = stored.map_err(|e| {
+        let _ = app.emit("engine-output", EngineOutput::broadcast(format!("[DEBUG] Failed to store engine: {}", e)));
+        e
+    })?;
+    store.save()?;
+    let engine_instance_id = stored.hash;
+    let final_path_str = stored.path;
 
     // Handle NNUE file if requested
     if has_nnue {
-        let _ = app.emit("engine-output", "[DEBUG] Engine requires NNUE file, requesting file selection...");
+        let _ = app.emit("engine-output", EngineOutput::broadcast("[DEBUG] Engine requires NNUE file, requesting file selection..."));
         
         // Request NNUE file selection from the frontend
         let nnue_request_data = serde_json::json!({
@@ -620,35 +810,26 @@ async fn handle_nnue_file_result(
     engine_instance_id: String,
     app: AppHandle,
 ) -> Result<(), String> {
-    let _ = app.emit("engine-output", format!("[DEBUG] NNUE file result for engine '{}': TempPath={}, Filename={}", engine_name, temp_file_path, filename));
+    let _ = app.emit("engine-output", EngineOutput::broadcast(format!("[DEBUG] NNUE file result for engine '{}': TempPath={}, Filename={}", engine_name, temp_file_path, filename)));
 
     if temp_file_path.is_empty() {
         return Err("NNUE file processing failed: temporary path is empty.".to_string());
     }
 
-    // Get the engine directory path
-    let bundle_identifier = &app.config().identifier;
-    let engine_base_dir = format!("/data/data/{}/files/engines/{}", bundle_identifier, &engine_instance_id);
-
-    // Define the final path for the NNUE file in the same directory as the engine
-    let final_nnue_path_str = format!("{}/{}", engine_base_dir, &filename);
-
-    // Move the NNUE file from the temporary location to the final destination
-    if let Err(e) = fs::rename(&temp_file_path, &final_nnue_path_str) {
-        let error_msg = format!("Failed to move NNUE file from temp to final destination: {}", e);
-        let _ = app.emit("engine-output", format!("[DEBUG] {}", error_msg));
-        // Fallback to copy if rename fails (e.g., cross-device link)
-        if let Err(copy_err) = fs::copy(&temp_file_path, &final_nnue_path_str) {
-             let copy_error_msg = format!("Fallback copy also failed: {}", copy_err);
-             let _ = app.emit("engine-output", format!("[DEBUG] {}", copy_error_msg));
-             return Err(copy_error_msg);
-        } else {
-            // Copy succeeded, remove the original temp file
-            let _ = fs::remove_file(&temp_file_path);
-        }
-    }
-
-    let _ = app.emit("engine-output", format!("[DEBUG] NNUE file successfully copied to: {}", final_nnue_path_str));
+    // Pair the NNUE file with its engine; it is linked into the engine's directory
+    let mut store = EngineStore::open(get_engine_store_root(&app))?;
+    let stored = store.add_network(&engine_instance_id, Path::new(&temp_file_path), &filename);
+    let _ = fs::remove_file(&temp_file_path);
+    let final_nnue_path_str = stored
+        .map_err(|e| {
+            let _ = app.emit("engine-output", EngineOutput::broadcast(format!("[DEBUG] Failed to store NNUE file: {}", e)));
+            e
+        })?
+        .display()
+        .to_string();
+    store.save()?;
+
+    let _ = app.emit("engine-output", EngineOutput::broadcast(format!("[DEBUG] NNUE file successfully copied to: {}", final_nnue_path_str)));
 
     // Create the ManagedEngine object to send back to the frontend
     let new_engine_data = serde_json::json!({
@@ -686,14 +867,75 @@ async fn open_external_url(url: String, app: AppHandle) -> Result<(), String> {
     }
 }
 
+// Rules Commands
+
+/// Side to move, check status and legal moves of a position (`getAllLegalMovesForCurrentPosition`).
+#[tauri::command]
+async fn get_legal_moves(fen: String) -> Result<PositionSummary, String> {
+    Ok(PositionSummary::of(&rules::Board::from_fen(&fen)?))
+}
+
+/// Checks a move against a position; the error says why it is not legal.
+#[tauri::command]
+async fn validate_move(fen: String, uci_move: String) -> Result<(), String> {
+    rules::validate_move(&fen, &uci_move).map(|_| ())
+}
+
+/// Whether `side` (by default the side to move) is in check (`isInCheck`).
+#[tauri::command]
+async fn is_in_check(fen: String, side: Option<Side>) -> Result<bool, String> {
+    let board = rules::Board::from_fen(&fen)?;
+    Ok(board.in_check(side.unwrap_or(board.side_to_move)))
+}
+
+/// Everything wrong with a FEN: the syntax error that stops parsing, or the problems that
+/// make a well-formed position impossible. Empty for a good FEN.
+#[tauri::command]
+async fn validate_fen(fen: String) -> Result<Vec<FenError>, String> {
+    Ok(match fen::Fen::parse(&fen) {
+        Ok(parsed) => parsed.problems(),
+        Err(e) => vec![e],
+    })
+}
+
+/// Impossible counts in a FEN's hidden pool, per side and role.
+#[tauri::command]
+async fn check_hidden_pool(fen: String) -> Result<Vec<PoolIssue>, String> {
+    Ok(hidden_pool::check_pool(&fen::Fen::parse(&fen)?))
+}
+
+/// The one hidden pool a position admits, given the revealed pieces that were captured as a
+/// pool field (none when omitted), with the FEN rewritten to carry it.
+#[tauri::command]
+async fn reconstruct_hidden_pool(fen: String, captured_revealed: Option<String>) -> Result<PoolReconstruction, String> {
+    hidden_pool::reconstruct(&fen, captured_revealed.as_deref())
+}
+
+/// Odds of each identity for every dark piece of a position, and of what captures involving
+/// dark pieces reveal: `uci_move` alone, or every such legal capture when omitted.
+#[tauri::command]
+async fn reveal_odds(fen: String, uci_move: Option<String>) -> Result<RevealOdds, String> {
+    reveal_odds::reveal_odds(&fen, uci_move.as_deref())
+}
+
+/// Leaf counts `depth` plies below a position, split by root move. Flips are expanded over
+/// the hidden pool unless `mode` says to count them once.
+#[tauri::command]
+async fn perft(fen: String, depth: u32, mode: Option<RevealMode>) -> Result<PerftResult, String> {
+    tauri::async_runtime::spawn_blocking(move || perft::perft(&fen, depth, mode.unwrap_or_default()))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
 // Opening Book Commands
 
-/// Add an entry to the opening book
+/// Add an entry to the opening book. The move must be legal in the entry's position.
 #[tauri::command]
 async fn opening_book_add_entry(
     request: AddEntryRequest,
     app: AppHandle,
 ) -> Result<bool, String> {
+    rules::validate_move(&request.fen, &request.uci_move)?;
     let db_path = get_opening_book_db_path(&app)?;
     let book = JieqiOpeningBook::new(db_path).map_err(|e| e.to_string())?;
     book.add_entry(&request)
@@ -758,29 +1000,7 @@ async fn opening_book_import_entries(
     let entries: Vec<opening_book::OpeningBookEntry> =
         serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
 
-    let mut imported = 0;
-    let mut errors = Vec::new();
-
-    for entry in entries {
-        for move_data in entry.moves {
-            let request = AddEntryRequest {
-                fen: entry.fen.clone(),
-                uci_move: move_data.uci_move.clone(),
-                priority: move_data.priority,
-                wins: move_data.wins,
-                draws: move_data.draws,
-                losses: move_data.losses,
-                allowed: move_data.allowed,
-                comment: move_data.comment.clone(),
-            };
-            match book.add_entry(&request) {
-                Ok(_) => imported += 1,
-                Err(e) => errors.push(format!("Failed to import move {}: {}", move_data.uci_move, e)),
-            }
-        }
-    }
-
-    Ok((imported, errors))
+    Ok(book.import_entries(entries))
 }
 
 /// Export opening book database file to a specified path
@@ -861,13 +1081,36 @@ async fn paste_from_clipboard(_app: AppHandle) -> Result<String, String> {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
-        .manage(Arc::new(Mutex::new(None)) as EngineProcess)
+        .manage(EngineSessions::default())
+        .manage(MatchState::default())
+        .manage(ClockService::default())
+        .manage(BenchmarkState::default())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_shell::init())
         .invoke_handler(tauri::generate_handler![
             spawn_engine, 
             kill_engine,
             send_to_engine, 
+            set_engine_watchdog,
+            set_engine_output_batching,
+            set_engine_fair_play,
+            set_engine_transcript,
+            probe_engine,
+            get_engine_profile,
+            discover_engines,
+            get_default_engine_dirs,
+            start_match,
+            stop_match,
+            run_benchmark,
+            stop_benchmark,
+            get_benchmarks,
+            start_clock,
+            press_clock,
+            pause_clock,
+            resume_clock,
+            stop_clock,
+            get_clock_state,
+            get_clock_go_params,
             open_external_url,
             save_game_notation,
             save_chart_image,
@@ -879,6 +1122,15 @@ pub fn run() {
             save_game_notation_with_dialog,
             copy_to_clipboard,
             paste_from_clipboard,
+            // Rules commands
+            get_legal_moves,
+            validate_move,
+            is_in_check,
+            validate_fen,
+            check_hidden_pool,
+            reconstruct_hidden_pool,
+            reveal_odds,
+            perft,
             // Opening book commands
             opening_book_add_entry,
             opening_book_delete_entry,
@@ -907,6 +1159,10 @@ pub fn run() {
             #[cfg(target_os = "android")]
             scan_android_engines,
             #[cfg(target_os = "android")]
+            prune_android_engines,
+            #[cfg(target_os = "android")]
+            import_legacy_android_engines,
+            #[cfg(target_os = "android")]
             request_saf_file_selection,
             #[cfg(target_os = "android")]
             handle_saf_file_result,
@@ -1,12 +1,10 @@
 // src-tauri/src/linker.rs
-use tauri::AppHandle;
 use std::{thread, time};
 use enigo::{Enigo, MouseControllable, MouseButton}; 
 use base64::{Engine as _, engine::general_purpose::STANDARD};
 use std::io::Cursor;
 use xcap::Window;
 use image::{DynamicImage, Rgba};
-use std::cmp;
 
 #[derive(serde::Serialize, Clone)]
 pub struct WindowInfo {
@@ -0,0 +1,1017 @@
+// src-tauri/src/match_runner.rs
+use crate::clock::{ClockControl, GameClock};
+use crate::encoding::{EngineCodec, EngineEncoding};
+use crate::engine::{self, EngineLaunch, EngineSessions, LineFramer};
+use crate::fair_play::{engine_view, FairPlayConfig};
+use crate::rules::{self, Board, Move, Role, Side, Square};
+use crate::spawn_options::SpawnOptions;
+use crate::ucci::{EngineDialect, UcciAdapter};
+use crate::sprt::{SprtConfig, SprtStatus};
+use crate::uci::{self, UciMessage};
+use crate::watchdog::WatchdogConfig;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+/// Time allowed for `uciok`/`readyok` while setting an engine up.
+const SETUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time a forfeiting engine gets to answer `stop` before the next game.
+const STOP_GRACE: Duration = Duration::from_secs(1);
+
+/// Longest single wait, so a stop request is noticed quickly.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Centipawn value used for mate scores in adjudication.
+const MATE_SCORE: i32 = 30_000;
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EngineOptionValue {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MatchEngineSpec {
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub options: Vec<EngineOptionValue>,
+    #[serde(default)]
+    pub encoding: EngineEncoding,
+    #[serde(default)]
+    pub spawn_options: SpawnOptions,
+    #[serde(default)]
+    pub dialect: EngineDialect,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct TimeControl {
+    pub base_ms: u64,
+    pub increment_ms: u64,
+    /// Fixed time per move; base and increment are ignored when set.
+    pub movetime_ms: Option<u64>,
+    /// How far an engine may overrun its clock before it loses on time.
+    pub margin_ms: u64,
+    /// Any other control (byo-yomi, moves per session, fixed depth, ...); the fields above
+    /// except `margin_ms` are ignored when set.
+    pub control: Option<ClockControl>,
+}
+
+impl TimeControl {
+    pub fn clock_control(&self) -> ClockControl {
+        match (&self.control, self.movetime_ms) {
+            (Some(control), _) => control.clone(),
+            (None, Some(movetime_ms)) => ClockControl::MoveTime { movetime_ms },
+            (None, None) => ClockControl::Fischer {
+                base_ms: self.base_ms,
+                increment_ms: self.increment_ms,
+            },
+        }
+    }
+}
+
+impl Default for TimeControl {
+    fn default() -> Self {
+        TimeControl {
+            base_ms: 60_000,
+            increment_ms: 1_000,
+            movetime_ms: None,
+            margin_ms: 100,
+            control: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Adjudication {
+    /// Draw after this many plies (0 = no limit).
+    pub max_plies: u32,
+    /// Draw after this many plies without a capture (0 = off).
+    pub draw_halfmoves: u32,
+    /// Draw on the third occurrence of a position.
+    pub repetition_draw: bool,
+    /// On such a repetition, a side that gave check with every move since the position first
+    /// occurred loses instead, unless both sides did (the perpetual check rule). Perpetual
+    /// chasing is not detected, so a repeated chase is still scored as a draw.
+    pub perpetual_check: bool,
+    /// A side loses once, for `resign_moves` moves each, its engine scores it at or below
+    /// minus this value and the opponent's engine at or above it.
+    pub resign_score_cp: Option<i32>,
+    pub resign_moves: u32,
+    /// Draw once, for `draw_moves` moves each, both engines score the game within this value,
+    /// from ply `draw_min_ply` on.
+    pub draw_score_cp: Option<i32>,
+    pub draw_moves: u32,
+    pub draw_min_ply: u32,
+}
+
+impl Default for Adjudication {
+    fn default() -> Self {
+        Adjudication {
+            max_plies: 400,
+            draw_halfmoves: 120,
+            repetition_draw: true,
+            perpetual_check: true,
+            resign_score_cp: None,
+            resign_moves: 3,
+            draw_score_cp: None,
+            draw_moves: 8,
+            draw_min_ply: 80,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MatchConfig {
+    /// Results and pair counts are given from the point of view of the first engine.
+    pub engines: [MatchEngineSpec; 2],
+    /// Number of game pairs. Both games of a pair start from the same opening with the same
+    /// dark-piece identities, each engine playing Red once. The maximum when `sprt` is set.
+    pub pairs: u32,
+    #[serde(default)]
+    pub time_control: TimeControl,
+    /// Opening FENs, used in turn; the standard start position when empty.
+    #[serde(default)]
+    pub openings: Vec<String>,
+    /// Seed for dealing dark-piece identities, to make a match reproducible.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    #[serde(default)]
+    pub adjudication: Adjudication,
+    /// Stop as soon as this test accepts a hypothesis.
+    #[serde(default)]
+    pub sprt: Option<SprtConfig>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameOutcome {
+    RedWins,
+    BlackWins,
+    Draw,
+}
+
+impl GameOutcome {
+    fn win_for(side: Side) -> Self {
+        match side {
+            Side::Red => GameOutcome::RedWins,
+            Side::Black => GameOutcome::BlackWins,
+        }
+    }
+
+    /// Points scored by `side`, in half points.
+    fn half_points(self, side: Side) -> u32 {
+        match (self, side) {
+            (GameOutcome::Draw, _) => 1,
+            (GameOutcome::RedWins, Side::Red) | (GameOutcome::BlackWins, Side::Black) => 2,
+            _ => 0,
+        }
+    }
+}
+
+/// Payload of the `match-move` event.
+#[derive(Clone, Debug, Serialize)]
+pub struct MatchMove {
+    pub game: u32,
+    pub ply: u32,
+    /// Move in extended UCI notation (with reveal and captured-identity letters).
+    pub uci_move: String,
+    /// Position after the move, with every identity the match knows about.
+    pub fen: String,
+    pub red_time_ms: i64,
+    pub black_time_ms: i64,
+    /// Last score the mover's engine reported, from its own point of view.
+    pub score_cp: Option<i32>,
+}
+
+/// Payload of the `match-game` event.
+#[derive(Clone, Debug, Serialize)]
+pub struct GameResult {
+    pub game: u32,
+    pub pair: u32,
+    /// Names of the engines that played Red and Black.
+    pub red: String,
+    pub black: String,
+    pub outcome: GameOutcome,
+    pub reason: String,
+    pub opening: String,
+    pub moves: Vec<String>,
+}
+
+/// Payload of the `match-score` event: the running score of the first engine.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct MatchScore {
+    pub games: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// Finished pairs by the first engine's pair score of 0, 0.5, 1, 1.5 and 2 points.
+    pub ptnml: [u32; 5],
+    /// Test state after the last finished pair, when the match runs an SPRT.
+    pub sprt: Option<SprtStatus>,
+}
+
+/// Something that happened in a running match.
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum MatchEvent {
+    Move(MatchMove),
+    Game(GameResult),
+    Score(MatchScore),
+}
+
+impl MatchEvent {
+    /// Name of the Tauri event this is emitted as.
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            MatchEvent::Move(_) => "match-move",
+            MatchEvent::Game(_) => "match-game",
+            MatchEvent::Score(_) => "match-score",
+        }
+    }
+}
+
+/// Payload of the `match-finished` event.
+#[derive(Clone, Debug, Serialize)]
+pub struct MatchFinished {
+    pub score: MatchScore,
+    pub stopped: bool,
+    pub error: Option<String>,
+}
+
+/// A running engine as the match runner sees it: commands go in, stdout lines come out.
+pub trait MatchEngine: Send {
+    fn send(&mut self, command: &str) -> Result<(), String>;
+    /// Wait up to `timeout` for the next stdout line. `Ok(None)` means the wait timed out,
+    /// an error that the engine is gone.
+    fn recv(&mut self, timeout: Duration) -> Result<Option<String>, String>;
+}
+
+/// How a game ended, before it is turned into a `GameResult`.
+struct Finish {
+    outcome: GameOutcome,
+    reason: String,
+}
+
+impl Finish {
+    fn win(side: Side, reason: impl Into<String>) -> Self {
+        Finish {
+            outcome: GameOutcome::win_for(side),
+            reason: reason.into(),
+        }
+    }
+
+    fn draw(reason: impl Into<String>) -> Self {
+        Finish {
+            outcome: GameOutcome::Draw,
+            reason: reason.into(),
+        }
+    }
+}
+
+/// Result of a threefold repetition, given who moved and whether it gave check for every ply
+/// since the position first occurred. With `perpetual_check`, a side that checked with all of
+/// its moves loses unless the other side did too.
+fn repetition_finish(stretch: &[(Side, bool)], perpetual_check: bool) -> Finish {
+    let checked_throughout = |side: Side| stretch.iter().filter(|(mover, _)| *mover == side).all(|(_, check)| *check);
+    if perpetual_check {
+        match (checked_throughout(Side::Red), checked_throughout(Side::Black)) {
+            (true, false) => return Finish::win(Side::Black, "perpetual check"),
+            (false, true) => return Finish::win(Side::Red, "perpetual check"),
+            _ => {}
+        }
+    }
+    Finish::draw("threefold repetition")
+}
+
+/// Consecutive-move counters for score adjudication, per side.
+#[derive(Default)]
+struct ScoreStreaks {
+    losing: [u32; 2],
+    winning: [u32; 2],
+    drawish: [u32; 2],
+}
+
+impl ScoreStreaks {
+    fn update(&mut self, side: Side, score: Option<i32>, rules: &Adjudication) {
+        let i = side.index();
+        let Some(score) = score else {
+            self.losing[i] = 0;
+            self.winning[i] = 0;
+            self.drawish[i] = 0;
+            return;
+        };
+        let bump = |streak: &mut u32, hit: bool| *streak = if hit { *streak + 1 } else { 0 };
+        if let Some(cp) = rules.resign_score_cp {
+            bump(&mut self.losing[i], score <= -cp);
+            bump(&mut self.winning[i], score >= cp);
+        }
+        if let Some(cp) = rules.draw_score_cp {
+            bump(&mut self.drawish[i], score.abs() <= cp);
+        }
+    }
+
+    fn verdict(&self, ply: u32, rules: &Adjudication) -> Option<Finish> {
+        for side in [Side::Red, Side::Black] {
+            let (me, them) = (side.index(), side.opponent().index());
+            if rules.resign_score_cp.is_some() && self.losing[me] >= rules.resign_moves && self.winning[them] >= rules.resign_moves {
+                return Some(Finish::win(side.opponent(), "adjudicated by score"));
+            }
+        }
+        let drawish = self.drawish.iter().all(|&n| n >= rules.draw_moves);
+        (rules.draw_score_cp.is_some() && ply >= rules.draw_min_ply && drawish).then(|| Finish::draw("adjudicated draw by score"))
+    }
+}
+
+/// Plays a match between two UCI engines. The runner owns the board, the clocks and the
+/// dark-piece identities; engines only ever see what their side could know.
+pub struct MatchRunner {
+    config: MatchConfig,
+    engines: [Box<dyn MatchEngine>; 2],
+    stop: Arc<AtomicBool>,
+    rng: StdRng,
+}
+
+impl MatchRunner {
+    pub fn new(config: MatchConfig, engines: [Box<dyn MatchEngine>; 2], stop: Arc<AtomicBool>) -> Self {
+        let rng = match config.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        MatchRunner {
+            config,
+            engines,
+            stop,
+            rng,
+        }
+    }
+
+    /// Run every pair, reporting progress through `on_event`. Returns the final score;
+    /// a stop request ends the match early without counting the unfinished game, and an
+    /// SPRT decision ends it after the pair that crossed a bound.
+    pub fn run(&mut self, on_event: &mut dyn FnMut(MatchEvent)) -> Result<MatchScore, String> {
+        if let Some(sprt) = &self.config.sprt {
+            sprt.validate()?;
+        }
+        self.config.time_control.clock_control().validate()?;
+        let openings = if self.config.openings.is_empty() {
+            vec![Board::from_fen(rules::START_FEN)?]
+        } else {
+            self.config.openings.iter().map(|fen| Board::from_fen(fen)).collect::<Result<Vec<_>, _>>()?
+        };
+        for index in 0..2 {
+            self.init_engine(index)?;
+        }
+
+        let mut score = MatchScore::default();
+        for pair in 0..self.config.pairs {
+            let opening = &openings[pair as usize % openings.len()];
+            let identities = self.deal(opening)?;
+            let mut pair_points = 0;
+            for first_is_red in [true, false] {
+                let red_engine = if first_is_red { 0 } else { 1 };
+                let game = score.games + 1;
+                let Some((finish, moves)) = self.play_game(game, opening, &identities, red_engine, on_event)? else {
+                    return Ok(score);
+                };
+
+                let first_side = if first_is_red { Side::Red } else { Side::Black };
+                let points = finish.outcome.half_points(first_side);
+                pair_points += points;
+                score.games += 1;
+                match points {
+                    2 => score.wins += 1,
+                    1 => score.draws += 1,
+                    _ => score.losses += 1,
+                }
+                if !first_is_red {
+                    score.ptnml[pair_points as usize] += 1;
+                    score.sprt = self.config.sprt.map(|sprt| sprt.status(&score.ptnml));
+                }
+
+                on_event(MatchEvent::Game(GameResult {
+                    game,
+                    pair: pair + 1,
+                    red: self.config.engines[red_engine].name.clone(),
+                    black: self.config.engines[1 - red_engine].name.clone(),
+                    outcome: finish.outcome,
+                    reason: finish.reason,
+                    opening: opening.to_fen(),
+                    moves,
+                }));
+                on_event(MatchEvent::Score(score.clone()));
+            }
+            if score.sprt.is_some_and(|status| status.decision.is_some()) {
+                break;
+            }
+        }
+        Ok(score)
+    }
+
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    fn init_engine(&mut self, index: usize) -> Result<(), String> {
+        let spec = self.config.engines[index].clone();
+        let engine = &mut self.engines[index];
+        engine.send("uci")?;
+        wait_for(engine.as_mut(), "uciok", SETUP_TIMEOUT).map_err(|e| format!("{}: {}", spec.name, e))?;
+        for option in &spec.options {
+            engine.send(&format!("setoption name {} value {}", option.name, option.value))?;
+        }
+        Ok(())
+    }
+
+    /// Decide the identity of every dark piece of an opening by shuffling each side's hidden pool.
+    fn deal(&mut self, opening: &Board) -> Result<HashMap<Square, Role>, String> {
+        let mut identities = HashMap::new();
+        for side in [Side::Red, Side::Black] {
+            let squares: Vec<Square> = opening
+                .pieces()
+                .filter(|(_, p)| p.side == side && p.is_dark())
+                .map(|(square, _)| square)
+                .collect();
+            let mut pool = opening.hidden.pieces(side);
+            if pool.len() < squares.len() {
+                return Err(format!(
+                    "Opening '{}' has {} dark pieces for {:?} but only {} identities in its hidden pool.",
+                    opening.to_fen(),
+                    squares.len(),
+                    side,
+                    pool.len()
+                ));
+            }
+            pool.shuffle(&mut self.rng);
+            identities.extend(squares.into_iter().zip(pool));
+        }
+        Ok(identities)
+    }
+
+    /// Play one game. Returns `None` when the match was stopped before the game ended.
+    fn play_game(
+        &mut self,
+        game: u32,
+        opening: &Board,
+        identities: &HashMap<Square, Role>,
+        red_engine: usize,
+        on_event: &mut dyn FnMut(MatchEvent),
+    ) -> Result<Option<(Finish, Vec<String>)>, String> {
+        for index in 0..2 {
+            let name = self.config.engines[index].name.clone();
+            let engine = self.engines[index].as_mut();
+            engine.send("ucinewgame")?;
+            engine.send("isready")?;
+            wait_for(engine, "readyok", SETUP_TIMEOUT).map_err(|e| format!("{}: {}", name, e))?;
+        }
+
+        let tc = self.config.time_control.clone();
+        let rules = self.config.adjudication.clone();
+        let mut board = opening.clone();
+        // Engines get the position after the last reveal or capture plus the quiet moves since
+        let mut anchor = board.clone();
+        let mut quiet_moves: Vec<Move> = Vec::new();
+        let mut clock = GameClock::new(tc.clock_control(), board.side_to_move, tc.margin_ms);
+        clock.start(Instant::now());
+        // Plies at which each position occurred, and per ply who moved and whether it gave check
+        let mut repetitions: HashMap<(String, Side), Vec<usize>> = HashMap::new();
+        repetitions.insert((board.board_fen(), board.side_to_move), vec![0]);
+        let mut checks: Vec<(Side, bool)> = Vec::new();
+        let mut streaks = ScoreStreaks::default();
+        let mut moves = Vec::new();
+
+        let finish = loop {
+            if self.stopped() {
+                return Ok(None);
+            }
+            let side = board.side_to_move;
+            let ply = moves.len() as u32;
+
+            if board.king_square(side).is_none() {
+                break Finish::win(side.opponent(), "king captured");
+            }
+            if board.legal_moves().is_empty() {
+                let reason = if board.in_check(side) { "checkmate" } else { "no legal moves" };
+                break Finish::win(side.opponent(), reason);
+            }
+            if rules.max_plies > 0 && ply >= rules.max_plies {
+                break Finish::draw("move limit");
+            }
+            if rules.draw_halfmoves > 0 && board.halfmove_clock >= rules.draw_halfmoves {
+                break Finish::draw("no capture limit");
+            }
+
+            let engine_index = if side == Side::Red { red_engine } else { 1 - red_engine };
+            let mut position = format!("position fen {}", engine_view(&anchor, side));
+            if !quiet_moves.is_empty() {
+                position.push_str(" moves");
+                for mv in &quiet_moves {
+                    position.push(' ');
+                    position.push_str(&mv.to_string());
+                }
+            }
+            let now = Instant::now();
+            let go = format!("go {}", clock.go_params(now));
+            // Untimed controls (fixed depth or nodes) wait as long as the engine takes
+            let deadline = clock.time_to_flag(now).map(|ms| now + Duration::from_millis(ms as u64));
+
+            let engine = self.engines[engine_index].as_mut();
+            if let Err(e) = engine.send(&position).and_then(|_| engine.send(&go)) {
+                break Finish::win(side.opponent(), format!("engine failure: {}", e));
+            }
+            let (bestmove, score) = match self.wait_for_bestmove(engine_index, deadline) {
+                Ok(Some(reply)) => reply,
+                Ok(None) if self.stopped() => return Ok(None),
+                Ok(None) => break Finish::win(side.opponent(), "time forfeit"),
+                Err(e) => break Finish::win(side.opponent(), format!("engine failure: {}", e)),
+            };
+            if clock.press(side, Instant::now()).is_none() {
+                break Finish::win(side.opponent(), "time forfeit");
+            }
+
+            let checked = Move::from_uci(&bestmove)
+                .ok_or_else(|| "not a UCI move".to_string())
+                .and_then(|mv| board.check_move(mv).map(|_| mv));
+            let mv = match checked {
+                Ok(mv) => mv,
+                Err(reason) => break Finish::win(side.opponent(), format!("illegal move {}: {}", bestmove, reason)),
+            };
+            let reveal = board.piece_at(mv.from).filter(|p| p.is_dark()).and_then(|_| identities.get(&mv.from).copied());
+            let captured_identity = board.piece_at(mv.to).filter(|p| p.is_dark()).and_then(|_| identities.get(&mv.to).copied());
+            let outcome = board.play(mv, reveal, captured_identity)?;
+            let uci_move = rules::extended_uci(mv, side, &outcome);
+            moves.push(uci_move.clone());
+
+            if outcome.revealed.is_some() || outcome.captured.is_some() {
+                anchor = board.clone();
+                quiet_moves.clear();
+            } else {
+                quiet_moves.push(mv);
+            }
+            streaks.update(side, score, &rules);
+            let times = clock.state(Instant::now());
+            on_event(MatchEvent::Move(MatchMove {
+                game,
+                ply: ply + 1,
+                uci_move,
+                fen: board.to_fen(),
+                red_time_ms: times.red.time_ms,
+                black_time_ms: times.black.time_ms,
+                score_cp: score,
+            }));
+
+            checks.push((side, board.in_check(side.opponent())));
+            let seen = repetitions.entry((board.board_fen(), board.side_to_move)).or_default();
+            seen.push(moves.len());
+            if rules.repetition_draw && seen.len() >= 3 {
+                break repetition_finish(&checks[seen[0]..], rules.perpetual_check);
+            }
+            if let Some(verdict) = streaks.verdict(ply + 1, &rules) {
+                break verdict;
+            }
+        };
+        Ok(Some((finish, moves)))
+    }
+
+    /// Collect an engine's reply to `go`: its bestmove and the last main-line score it reported.
+    /// Returns `None` when the time ran out (the engine is then stopped) or the match was stopped.
+    fn wait_for_bestmove(&mut self, index: usize, deadline: Option<Instant>) -> Result<Option<(String, Option<i32>)>, String> {
+        let mut score = None;
+        loop {
+            let now = Instant::now();
+            if deadline.is_some_and(|deadline| now >= deadline) || self.stopped() {
+                let engine = self.engines[index].as_mut();
+                engine.send("stop")?;
+                let _ = wait_for(engine, "bestmove", STOP_GRACE);
+                return Ok(None);
+            }
+            let Some(line) = self.engines[index].recv(deadline.map_or(POLL_INTERVAL, |deadline| (deadline - now).min(POLL_INTERVAL)))? else {
+                continue;
+            };
+            match uci::parse_line(&line) {
+                Some(UciMessage::Info(info)) if info.multipv.unwrap_or(1) == 1 => {
+                    if let Some(cp) = info.score_cp {
+                        score = Some(cp);
+                    } else if let Some(mate) = info.mate {
+                        score = Some(if mate > 0 { MATE_SCORE - mate } else { -MATE_SCORE - mate });
+                    }
+                }
+                Some(UciMessage::BestMove(best)) => return Ok(Some((best.bestmove, score))),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Wait until the engine writes a line starting with `token`, skipping everything else.
+pub fn wait_for(engine: &mut dyn MatchEngine, token: &str, timeout: Duration) -> Result<(), String> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(format!("no '{}' within {} ms", token, timeout.as_millis()));
+        }
+        if let Some(line) = engine.recv(deadline - now)? {
+            if line.split_whitespace().next() == Some(token) {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Match engine backed by an engine session, so its traffic also shows up as `engine-output`.
+pub(crate) struct SessionEngine {
+    app: AppHandle,
+    session_id: String,
+    lines: Receiver<String>,
+}
+
+impl SessionEngine {
+    pub(crate) fn spawn(app: &AppHandle, session_id: &str, spec: &MatchEngineSpec) -> Result<Self, String> {
+        let sessions = app.state::<EngineSessions>();
+        // The runner has its own timeouts; health checks would only get in the way of its clocks
+        sessions.set_watchdog(session_id, WatchdogConfig { interval_ms: 0, ..WatchdogConfig::default() });
+        // Match positions are built with `engine_view`; the session filter checks they stay fair
+        sessions.set_fair_play(session_id, Some(FairPlayConfig::default()));
+        let mut launch = EngineLaunch::new(spec.path.clone(), spec.args.clone());
+        launch.encoding = spec.encoding;
+        launch.spawn = spec.spawn_options.clone();
+        launch.dialect = spec.dialect;
+        engine::spawn_session(app, session_id, launch, false)?;
+        let (tx, lines) = mpsc::channel();
+        sessions.set_tap(session_id, tx)?;
+        Ok(SessionEngine {
+            app: app.clone(),
+            session_id: session_id.to_string(),
+            lines,
+        })
+    }
+}
+
+impl MatchEngine for SessionEngine {
+    fn send(&mut self, command: &str) -> Result<(), String> {
+        self.app.state::<EngineSessions>().send(&self.session_id, command)
+    }
+
+    fn recv(&mut self, timeout: Duration) -> Result<Option<String>, String> {
+        recv_line(&self.lines, timeout)
+    }
+}
+
+impl Drop for SessionEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let sessions = self.app.state::<EngineSessions>();
+        if let Some(session) = sessions.remove(&self.session_id) {
+            let _ = session.child.kill();
+        }
+        sessions.forget(&self.session_id);
+    }
+}
+
+/// Match engine run as a plain child process, for use without a window (the CLI).
+pub struct ProcessEngine {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    codec: Arc<EngineCodec>,
+    adapter: Option<Arc<Mutex<UcciAdapter>>>,
+}
+
+impl ProcessEngine {
+    /// Start an engine the way `spawn_engine` does: by default with its own directory as
+    /// working directory.
+    pub fn spawn(
+        path: &str,
+        args: &[String],
+        encoding: EngineEncoding,
+        dialect: EngineDialect,
+        options: &SpawnOptions,
+    ) -> Result<Self, String> {
+        let path = fs::canonicalize(path).map_err(|e| format!("Engine '{}' not found: {}", path, e))?;
+        let mut command = Command::new(&path);
+        command
+            .args(args)
+            .envs(&options.env)
+            .current_dir(options.working_dir(&path.to_string_lossy())?)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null());
+        let mut child = command.spawn().map_err(|e| format!("Failed to spawn engine: {}", e))?;
+        if let Err(e) = options.apply_to_process(child.id()) {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(e);
+        }
+        let stdin = child.stdin.take().ok_or("Failed to open engine stdin")?;
+        let mut stdout = child.stdout.take().ok_or("Failed to open engine stdout")?;
+
+        let codec = Arc::new(EngineCodec::new(encoding));
+        let reader_codec = codec.clone();
+        let adapter = dialect.adapter().map(|a| Arc::new(Mutex::new(a)));
+        let reader_adapter = adapter.clone();
+        let (tx, lines) = mpsc::channel();
+        thread::spawn(move || {
+            let forward = |line: &[u8]| {
+                let line = reader_codec.decode(line);
+                match &reader_adapter {
+                    Some(adapter) => adapter.lock().unwrap().from_engine(&line).is_none_or(|line| tx.send(line).is_ok()),
+                    None => tx.send(line).is_ok(),
+                }
+            };
+            let mut framer = LineFramer::default();
+            let mut buf = [0u8; 4096];
+            while let Ok(n) = stdout.read(&mut buf) {
+                if n == 0 {
+                    break;
+                }
+                for line in framer.push(&buf[..n]) {
+                    if !forward(&line) {
+                        return;
+                    }
+                }
+            }
+            if let Some(line) = framer.finish() {
+                forward(&line);
+            }
+        });
+        Ok(ProcessEngine {
+            child,
+            stdin,
+            lines,
+            codec,
+            adapter,
+        })
+    }
+}
+
+impl MatchEngine for ProcessEngine {
+    fn send(&mut self, command: &str) -> Result<(), String> {
+        let lines = match &self.adapter {
+            Some(adapter) => adapter.lock().unwrap().to_engine(command),
+            None => vec![command.to_string()],
+        };
+        for line in lines {
+            self.stdin
+                .write_all(&self.codec.encode(&format!("{}\n", line)))
+                .and_then(|_| self.stdin.flush())
+                .map_err(|e| format!("Failed to write to engine: {}", e))?;
+        }
+        Ok(())
+    }
+
+    fn recv(&mut self, timeout: Duration) -> Result<Option<String>, String> {
+        recv_line(&self.lines, timeout)
+    }
+}
+
+impl Drop for ProcessEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn recv_line(lines: &Receiver<String>, timeout: Duration) -> Result<Option<String>, String> {
+    match lines.recv_timeout(timeout) {
+        Ok(line) => Ok(Some(line)),
+        Err(RecvTimeoutError::Timeout) => Ok(None),
+        Err(RecvTimeoutError::Disconnected) => Err("engine exited".to_string()),
+    }
+}
+
+/// Stop flag of the match in progress, if any.
+#[derive(Default)]
+pub struct MatchState {
+    stop: Mutex<Option<Arc<AtomicBool>>>,
+}
+
+/// Starts a match between two engines in the background. Progress is reported through the
+/// `match-move`, `match-game` and `match-score` events, the end through `match-finished`.
+#[tauri::command]
+pub async fn start_match(config: MatchConfig, app: AppHandle, state: tauri::State<'_, MatchState>) -> Result<(), String> {
+    let stop = Arc::new(AtomicBool::new(false));
+    {
+        let mut current = state.stop.lock().unwrap();
+        if current.is_some() {
+            return Err("A match is already running.".to_string());
+        }
+        *current = Some(stop.clone());
+    }
+
+    let engines = SessionEngine::spawn(&app, "match-engine-1", &config.engines[0])
+        .and_then(|first| Ok([first, SessionEngine::spawn(&app, "match-engine-2", &config.engines[1])?]));
+    let engines: [Box<dyn MatchEngine>; 2] = match engines {
+        Ok([first, second]) => [Box::new(first), Box::new(second)],
+        Err(e) => {
+            state.stop.lock().unwrap().take();
+            return Err(e);
+        }
+    };
+
+    thread::spawn(move || {
+        let mut score = MatchScore::default();
+        let result = {
+            let mut runner = MatchRunner::new(config, engines, stop.clone());
+            runner.run(&mut |event| {
+                if let MatchEvent::Score(s) = &event {
+                    score = s.clone();
+                }
+                let _ = app.emit(event.event_name(), &event);
+            })
+        };
+        app.state::<MatchState>().stop.lock().unwrap().take();
+        let finished = match result {
+            Ok(final_score) => MatchFinished {
+                score: final_score,
+                stopped: stop.load(Ordering::Relaxed),
+                error: None,
+            },
+            Err(e) => MatchFinished {
+                score,
+                stopped: false,
+                error: Some(e),
+            },
+        };
+        let _ = app.emit("match-finished", finished);
+    });
+    Ok(())
+}
+
+/// Asks the running match to stop after the current move. The unfinished game is not counted.
+#[tauri::command]
+pub async fn stop_match(state: tauri::State<'_, MatchState>) -> Result<(), String> {
+    if let Some(stop) = state.stop.lock().unwrap().as_ref() {
+        stop.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perpetual_checker_loses_repetition() {
+        // Red checks on every move, Black only escapes
+        let stretch = [(Side::Red, true), (Side::Black, false), (Side::Red, true), (Side::Black, false)];
+        let finish = repetition_finish(&stretch, true);
+        assert_eq!(finish.outcome, GameOutcome::BlackWins);
+        assert_eq!(finish.reason, "perpetual check");
+        assert_eq!(repetition_finish(&stretch, false).outcome, GameOutcome::Draw);
+    }
+
+    #[test]
+    fn mutual_or_no_perpetual_check_is_a_draw() {
+        let mutual = [(Side::Black, true), (Side::Red, true), (Side::Black, true), (Side::Red, true)];
+        assert_eq!(repetition_finish(&mutual, true).outcome, GameOutcome::Draw);
+        let broken = [(Side::Red, true), (Side::Black, false), (Side::Red, false), (Side::Black, false)];
+        assert_eq!(repetition_finish(&broken, true).outcome, GameOutcome::Draw);
+    }
+
+    /// Engine that answers the handshake and gives the next scripted move to each `go`;
+    /// a `None` move never answers. Every command it gets is recorded.
+    struct ScriptedEngine {
+        moves: std::collections::VecDeque<Option<&'static str>>,
+        output: std::collections::VecDeque<String>,
+        received: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl ScriptedEngine {
+        fn boxed(moves: &[Option<&'static str>]) -> (Box<dyn MatchEngine>, Arc<Mutex<Vec<String>>>) {
+            let received = Arc::new(Mutex::new(Vec::new()));
+            let engine = ScriptedEngine {
+                moves: moves.iter().copied().collect(),
+                output: Default::default(),
+                received: received.clone(),
+            };
+            (Box::new(engine), received)
+        }
+    }
+
+    impl MatchEngine for ScriptedEngine {
+        fn send(&mut self, command: &str) -> Result<(), String> {
+            self.received.lock().unwrap().push(command.to_string());
+            match command.split_whitespace().next() {
+                Some("uci") => self.output.push_back("uciok".to_string()),
+                Some("isready") => self.output.push_back("readyok".to_string()),
+                Some("go") => {
+                    if let Some(Some(mv)) = self.moves.pop_front() {
+                        self.output.push_back(format!("info depth 1 score cp 12 pv {}", mv));
+                        self.output.push_back(format!("bestmove {}", mv));
+                    }
+                }
+                Some("stop") => self.output.push_back("bestmove (none)".to_string()),
+                _ => {}
+            }
+            Ok(())
+        }
+
+        fn recv(&mut self, timeout: Duration) -> Result<Option<String>, String> {
+            if self.output.is_empty() {
+                thread::sleep(timeout.min(Duration::from_millis(5)));
+            }
+            Ok(self.output.pop_front())
+        }
+    }
+
+    fn spec(name: &str) -> MatchEngineSpec {
+        MatchEngineSpec {
+            name: name.to_string(),
+            path: String::new(),
+            args: Vec::new(),
+            options: Vec::new(),
+            encoding: EngineEncoding::default(),
+            spawn_options: SpawnOptions::default(),
+            dialect: EngineDialect::default(),
+        }
+    }
+
+    fn runner(first: Box<dyn MatchEngine>, second: Box<dyn MatchEngine>) -> MatchRunner {
+        let config = MatchConfig {
+            engines: [spec("first"), spec("second")],
+            pairs: 1,
+            time_control: TimeControl {
+                margin_ms: 0,
+                control: Some(ClockControl::MoveTime { movetime_ms: 50 }),
+                ..TimeControl::default()
+            },
+            openings: Vec::new(),
+            seed: Some(7),
+            adjudication: Adjudication::default(),
+            sprt: None,
+        };
+        MatchRunner::new(config, [first, second], Arc::new(AtomicBool::new(false)))
+    }
+
+    /// Play one game from the start position with the first engine as Red.
+    fn play(runner: &mut MatchRunner) -> (Finish, Vec<String>) {
+        let opening = Board::from_fen(rules::START_FEN).unwrap();
+        let identities = runner.deal(&opening).unwrap();
+        runner.play_game(1, &opening, &identities, 0, &mut |_| {}).unwrap().unwrap()
+    }
+
+    #[test]
+    fn illegal_bestmove_forfeits() {
+        let (red, red_received) = ScriptedEngine::boxed(&[Some("a3a4")]);
+        // a0 is Red's, so Black may not move it
+        let (black, _) = ScriptedEngine::boxed(&[Some("a0a5")]);
+        let (finish, moves) = play(&mut runner(red, black));
+        assert_eq!(finish.outcome, GameOutcome::RedWins);
+        assert_eq!(finish.reason, "illegal move a0a5: The piece on a0 is not the side to move's.");
+        assert_eq!(moves.len(), 1);
+        assert!(moves[0].starts_with("a3a4"));
+        let start = engine_view(&Board::from_fen(rules::START_FEN).unwrap(), Side::Red);
+        let received = red_received.lock().unwrap();
+        assert_eq!(received[..3], ["ucinewgame", "isready", &format!("position fen {}", start)]);
+        assert_eq!(received[3], "go movetime 50");
+    }
+
+    #[test]
+    fn silent_engine_loses_on_time() {
+        let (red, red_received) = ScriptedEngine::boxed(&[None]);
+        let (black, _) = ScriptedEngine::boxed(&[]);
+        let (finish, moves) = play(&mut runner(red, black));
+        assert_eq!(finish.outcome, GameOutcome::BlackWins);
+        assert_eq!(finish.reason, "time forfeit");
+        assert!(moves.is_empty());
+        assert_eq!(red_received.lock().unwrap().last().unwrap(), "stop");
+    }
+
+    #[test]
+    fn pair_result_lands_in_its_ptnml_bucket() {
+        // The second engine loses both games: as Black with an illegal move, as Red on time
+        let (first, _) = ScriptedEngine::boxed(&[Some("a3a4")]);
+        let (second, _) = ScriptedEngine::boxed(&[Some("a0a5"), None]);
+        let mut events = Vec::new();
+        let score = runner(first, second).run(&mut |event| events.push(event)).unwrap();
+        assert_eq!((score.games, score.wins, score.draws, score.losses), (2, 2, 0, 0));
+        assert_eq!(score.ptnml, [0, 0, 0, 0, 1]);
+        let reasons: Vec<_> = events
+            .iter()
+            .filter_map(|event| match event {
+                MatchEvent::Game(result) => Some((result.red.as_str(), result.reason.as_str())),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(reasons.len(), 2);
+        assert_eq!(reasons[1], ("second", "time forfeit"));
+    }
+}
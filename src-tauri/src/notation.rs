@@ -0,0 +1,236 @@
+// src-tauri/src/notation.rs
+// Saved games in JieqiBox's JSON notation: loading and writing them, replaying their moves
+// against the rules core, and rendering them as plain text.
+use crate::rules::{self, Board, Side};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryType {
+    Move,
+    Adjust,
+}
+
+/// One history entry of a saved game (`HistoryEntry` in `useChessGame.ts`).
+/// Fields this side does not use are kept in `extra` so a round trip loses nothing.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HistoryEntry {
+    #[serde(rename = "type")]
+    pub entry_type: EntryType,
+    /// Extended UCI move, or a description of the position edit for `adjust` entries.
+    pub data: String,
+    /// Position after the entry.
+    pub fen: String,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub round: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub white: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub black: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_fen: Option<String>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// JieqiBox's JSON game notation (`GameNotation` in `useChessGame.ts`).
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GameNotation {
+    pub metadata: GameMetadata,
+    pub moves: Vec<HistoryEntry>,
+}
+
+/// A move of a replayed game together with the position it was played in.
+#[derive(Clone, Debug)]
+pub struct ReplayedMove {
+    /// Index of the entry in `GameNotation::moves`.
+    pub index: usize,
+    pub uci_move: String,
+    pub side: Side,
+    pub fen_before: String,
+}
+
+impl GameNotation {
+    pub fn from_json(json: &str) -> Result<GameNotation, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid game notation: {}", e))
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to write game notation: {}", e))
+    }
+
+    pub fn initial_fen(&self) -> &str {
+        self.metadata.initial_fen.as_deref().unwrap_or(rules::START_FEN)
+    }
+
+    /// Replay the game from its initial position. Every move must be legal, name the identity
+    /// of any piece it reveals, and lead to the position recorded with it.
+    pub fn replay(&self) -> Result<Vec<ReplayedMove>, String> {
+        let mut board = Board::from_fen(self.initial_fen()).map_err(|e| format!("Initial position: {}", e))?;
+        let mut replayed = Vec::new();
+
+        for (index, entry) in self.moves.iter().enumerate() {
+            let context = |e: String| format!("Entry {} ({}): {}", index + 1, entry.data, e);
+            let recorded = Board::from_fen(&entry.fen).map_err(context)?;
+            if entry.entry_type == EntryType::Adjust {
+                board = recorded;
+                continue;
+            }
+
+            let side = board.side_to_move;
+            let (mv, reveal, captured) =
+                rules::parse_extended_uci(&entry.data, side).ok_or_else(|| context("not a UCI move".to_string()))?;
+            let fen_before = board.to_fen();
+            board.play(mv, reveal, captured).map_err(context)?;
+            if board.board_fen() != recorded.board_fen() || board.side_to_move != recorded.side_to_move {
+                return Err(context(format!(
+                    "recorded position {} does not match the replayed position {}",
+                    entry.fen,
+                    board.to_fen()
+                )));
+            }
+            replayed.push(ReplayedMove {
+                index,
+                uci_move: entry.data.clone(),
+                side,
+                fen_before,
+            });
+        }
+        Ok(replayed)
+    }
+
+    /// Rewrite every FEN in the new field layout.
+    pub fn normalize_fens(&mut self) -> Result<(), String> {
+        if let Some(fen) = &mut self.metadata.initial_fen {
+            *fen = Board::from_fen(fen)?.to_fen();
+        }
+        for entry in &mut self.moves {
+            entry.fen = Board::from_fen(&entry.fen)?.to_fen();
+        }
+        Ok(())
+    }
+
+    /// Plain-text rendering in the style of PGN: tag pairs, then numbered moves.
+    pub fn to_text(&self) -> Result<String, String> {
+        let meta = &self.metadata;
+        let mut out = String::new();
+        let tags = [
+            ("Event", &meta.event),
+            ("Site", &meta.site),
+            ("Date", &meta.date),
+            ("Round", &meta.round),
+            ("Red", &meta.white),
+            ("Black", &meta.black),
+            ("Result", &meta.result),
+        ];
+        for (name, value) in tags {
+            if let Some(value) = value {
+                out.push_str(&format!("[{} \"{}\"]\n", name, value));
+            }
+        }
+        out.push_str(&format!("[FEN \"{}\"]\n\n", self.initial_fen()));
+
+        let start = Board::from_fen(self.initial_fen())?;
+        let mut number = start.fullmove_number;
+        let mut tokens = Vec::new();
+        for (i, mv) in self.replay()?.iter().enumerate() {
+            match mv.side {
+                Side::Red => tokens.push(format!("{}.", number)),
+                Side::Black if i == 0 => tokens.push(format!("{}...", number)),
+                Side::Black => {}
+            }
+            tokens.push(mv.uci_move.clone());
+            if mv.side == Side::Black {
+                number += 1;
+            }
+        }
+        tokens.push(meta.result.clone().unwrap_or_else(|| "*".to_string()));
+        out.push_str(&tokens.join(" "));
+        out.push('\n');
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Two reveals from the start position, with fields only the frontend uses.
+    fn game() -> Value {
+        json!({
+            "metadata": {
+                "event": "Club match",
+                "white": "Red engine",
+                "flipMode": "random",
+            },
+            "moves": [
+                {
+                    "type": "move",
+                    "data": "h2e2C",
+                    "fen": "xxxxkxxxx/9/1x5x1/x1x1x1x1x/9/9/X1X1X1X1X/1X2C4/9/XXXXKXXXX b A2B2N2R2C1P5a2b2n2r2c2p5 - 1 1",
+                    "comment": "central cannon",
+                },
+                {
+                    "type": "move",
+                    "data": "h7e7c",
+                    "fen": "xxxxkxxxx/9/1x2c4/x1x1x1x1x/9/9/X1X1X1X1X/1X2C4/9/XXXXKXXXX w A2B2N2R2C1P5a2b2n2r2c1p5 - 2 2",
+                    "engineScore": 12,
+                },
+            ],
+        })
+    }
+
+    #[test]
+    fn replays_and_round_trips_a_game() {
+        let notation = GameNotation::from_json(&game().to_string()).unwrap();
+        let replayed = notation.replay().unwrap();
+        let moves: Vec<_> = replayed.iter().map(|mv| (mv.index, mv.uci_move.as_str(), mv.side)).collect();
+        assert_eq!(moves, [(0, "h2e2C", Side::Red), (1, "h7e7c", Side::Black)]);
+        assert_eq!(replayed[0].fen_before, Board::from_fen(rules::START_FEN).unwrap().to_fen());
+        assert!(replayed[1].fen_before.starts_with("xxxxkxxxx/9/1x5x1/x1x1x1x1x/9/9/X1X1X1X1X/1X2C4/9/XXXXKXXXX b "));
+        assert_eq!(
+            notation.to_text().unwrap(),
+            format!("[Event \"Club match\"]\n[Red \"Red engine\"]\n[FEN \"{}\"]\n\n1. h2e2C h7e7c *\n", rules::START_FEN)
+        );
+
+        // Fields this side does not know survive the round trip
+        let json = notation.to_json().unwrap();
+        let reread: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(reread, game());
+        assert_eq!(GameNotation::from_json(&json).unwrap().moves[1].extra["engineScore"], 12);
+    }
+
+    #[test]
+    fn rejects_a_move_that_disagrees_with_its_recorded_position() {
+        let mut edited = game();
+        // The cannon is recorded on d2 instead of e2
+        edited["moves"][0]["fen"] = json!("xxxxkxxxx/9/1x5x1/x1x1x1x1x/9/9/X1X1X1X1X/1X1C5/9/XXXXKXXXX b - - 1 1");
+        let error = GameNotation::from_json(&edited.to_string()).unwrap().replay().unwrap_err();
+        assert!(error.starts_with("Entry 1 (h2e2C): recorded position xxxxkxxxx/"), "{}", error);
+        assert!(error.contains("does not match the replayed position"), "{}", error);
+
+        // A reveal without its identity
+        let mut edited = game();
+        edited["moves"][1]["data"] = json!("h7e7");
+        let error = GameNotation::from_json(&edited.to_string()).unwrap().replay().unwrap_err();
+        assert!(error.starts_with("Entry 2 (h7e7): "), "{}", error);
+        assert!(GameNotation::from_json("{\"moves\": []}").unwrap_err().starts_with("Invalid game notation: "));
+    }
+}
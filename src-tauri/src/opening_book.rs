@@ -1,7 +1,10 @@
+use crate::fen::{self, Fen, FenError};
+use crate::rules::{self, Pool, Role, Side};
 use rusqlite::{Connection, Result};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::fmt;
 use std::path::Path;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +45,36 @@ pub struct OpeningBookStats {
     pub disallowed_moves: i64,
 }
 
+/// Why a book operation on a position failed: its FEN did not parse, or the database failed.
+#[derive(Debug)]
+pub enum BookError {
+    Fen(FenError),
+    Sql(rusqlite::Error),
+}
+
+impl fmt::Display for BookError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookError::Fen(e) => e.fmt(f),
+            BookError::Sql(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for BookError {}
+
+impl From<FenError> for BookError {
+    fn from(error: FenError) -> Self {
+        BookError::Fen(error)
+    }
+}
+
+impl From<rusqlite::Error> for BookError {
+    fn from(error: rusqlite::Error) -> Self {
+        BookError::Sql(error)
+    }
+}
+
 pub struct JieqiOpeningBook {
     conn: Connection,
 }
@@ -74,8 +107,8 @@ impl JieqiOpeningBook {
         Ok(())
     }
 
-    pub fn add_entry(&self, request: &AddEntryRequest) -> Result<bool> {
-        let (key_blob, transform_idx) = compute_key_and_transform(&request.fen);
+    pub fn add_entry(&self, request: &AddEntryRequest) -> std::result::Result<bool, BookError> {
+        let (key_blob, transform_idx) = compute_key_and_transform(&request.fen)?;
         let transformed_uci = transform_uci_move(&request.uci_move, transform_idx);
         let move_int = uci_to_int(&transformed_uci) as i64;
 
@@ -106,8 +139,8 @@ impl JieqiOpeningBook {
         Ok(true)
     }
 
-    pub fn delete_entry(&self, fen: &str, uci_move: &str) -> Result<bool> {
-        let (key_blob, transform_idx) = compute_key_and_transform(fen);
+    pub fn delete_entry(&self, fen: &str, uci_move: &str) -> std::result::Result<bool, BookError> {
+        let (key_blob, transform_idx) = compute_key_and_transform(fen)?;
         let transformed_uci = transform_uci_move(uci_move, transform_idx);
         let move_int = uci_to_int(&transformed_uci) as i64;
 
@@ -119,8 +152,8 @@ impl JieqiOpeningBook {
         Ok(affected_rows > 0)
     }
 
-    pub fn query_moves(&self, fen: &str) -> Result<Vec<MoveData>> {
-        let (key_blob, transform_idx) = compute_key_and_transform(fen);
+    pub fn query_moves(&self, fen: &str) -> std::result::Result<Vec<MoveData>, BookError> {
+        let (key_blob, transform_idx) = compute_key_and_transform(fen)?;
 
         let mut stmt = self.conn.prepare(
             "SELECT move, priority, wins, draws, losses, allowed, comment FROM openings WHERE key = ?1 ORDER BY priority DESC"
@@ -204,222 +237,145 @@ impl JieqiOpeningBook {
 
         // Sort moves by priority for each entry
         for entry in entries.values_mut() {
-            entry.moves.sort_by(|a, b| b.priority.cmp(&a.priority));
+            entry.moves.sort_by_key(|m| std::cmp::Reverse(m.priority));
         }
 
         Ok(entries.into_values().collect())
     }
-}
 
-// FEN processing functions
-fn parse_pool_string(pool_str: &str) -> (HashMap<char, i32>, HashMap<char, i32>) {
-    let mut red_pool = HashMap::new();
-    let mut black_pool = HashMap::new();
-    
-    let chars: Vec<char> = pool_str.chars().collect();
-    let mut i = 0;
-    
-    while i < chars.len() {
-        if chars[i].is_alphabetic() {
-            let piece = chars[i];
-            let mut count = 1;
-            
-            if i + 1 < chars.len() && chars[i + 1].is_ascii_digit() {
-                count = chars[i + 1].to_digit(10).unwrap_or(1) as i32;
-                i += 1;
-            }
-            
-            if piece.is_uppercase() {
-                *red_pool.entry(piece).or_insert(0) += count;
-            } else {
-                *black_pool.entry(piece).or_insert(0) += count;
+    /// Add every move of the given entries. Returns the number of moves imported and
+    /// an error message for each move that failed. Moves of entries that carry their FEN
+    /// must be legal in it.
+    pub fn import_entries(&self, entries: Vec<OpeningBookEntry>) -> (i32, Vec<String>) {
+        let mut imported = 0;
+        let mut errors = Vec::new();
+
+        for entry in entries {
+            for move_data in entry.moves {
+                if !entry.fen.is_empty() {
+                    if let Err(e) = rules::validate_move(&entry.fen, &move_data.uci_move) {
+                        errors.push(format!("Skipped move {}: {}", move_data.uci_move, e));
+                        continue;
+                    }
+                }
+                let request = AddEntryRequest {
+                    fen: entry.fen.clone(),
+                    uci_move: move_data.uci_move.clone(),
+                    priority: move_data.priority,
+                    wins: move_data.wins,
+                    draws: move_data.draws,
+                    losses: move_data.losses,
+                    allowed: move_data.allowed,
+                    comment: move_data.comment.clone(),
+                };
+                match self.add_entry(&request) {
+                    Ok(_) => imported += 1,
+                    Err(e) => errors.push(format!("Failed to import move {}: {}", move_data.uci_move, e)),
+                }
             }
         }
-        i += 1;
+
+        (imported, errors)
     }
-    
-    (red_pool, black_pool)
 }
 
-fn calculate_gcd(pool: &HashMap<char, i32>) -> i32 {
-    let values: Vec<i32> = pool.values().filter(|&&v| v > 0).copied().collect();
-    if values.is_empty() {
-        return 1;
-    }
-    
-    let mut result = values[0];
-    for &value in &values[1..] {
-        result = gcd(result, value);
+// FEN processing functions
+
+/// Book form of a position: the hidden pools of sides without dark pieces and the captured
+/// pools are dropped. A side with a single dark piece has its pool divided by the GCD of its
+/// counts, as only the odds of each identity matter for that piece.
+pub(crate) fn normalize_fen(fen: &str) -> std::result::Result<Fen, FenError> {
+    let mut fen = Fen::parse(fen)?;
+    for side in [Side::Red, Side::Black] {
+        let divisor = match fen.dark_count(side) {
+            0 => None,
+            1 => Some(pool_gcd(&fen.hidden, side)),
+            _ => Some(1),
+        };
+        for role in Role::ALL {
+            let count = divisor.map_or(0, |d| fen.hidden.get(side, role) / d);
+            fen.hidden.set(side, role, count);
+        }
     }
-    
-    if result == 0 { 1 } else { result }
+    fen.captured = Pool::default();
+    Ok(fen)
+}
+
+/// GCD of a side's non-zero pool counts, 1 for an empty pool.
+fn pool_gcd(pool: &Pool, side: Side) -> u8 {
+    Role::ALL
+        .iter()
+        .map(|&role| pool.get(side, role))
+        .filter(|&count| count > 0)
+        .reduce(gcd)
+        .unwrap_or(1)
 }
 
-fn gcd(a: i32, b: i32) -> i32 {
+fn gcd(a: u8, b: u8) -> u8 {
     if b == 0 { a } else { gcd(b, a % b) }
 }
 
-fn format_pool(pool: &HashMap<char, i32>, order: &str) -> String {
-    let mut result = String::new();
-    for piece_type in order.chars() {
-        if let Some(&count) = pool.get(&piece_type) {
+/// The string a book key hashes: `board side pool -`, the pool in `RNBACP` order, red first,
+/// with counts of 1 left out.
+fn key_fen(fen: &Fen) -> String {
+    const ORDER: [Role; 6] = [Role::Chariot, Role::Horse, Role::Elephant, Role::Advisor, Role::Cannon, Role::Pawn];
+    let mut pool = String::new();
+    for side in [Side::Red, Side::Black] {
+        for role in ORDER {
+            let count = fen.hidden.get(side, role);
             if count > 0 {
-                result.push(piece_type);
+                pool.push(role.fen_char(side));
                 if count > 1 {
-                    result.push_str(&count.to_string());
+                    pool.push_str(&count.to_string());
                 }
             }
         }
     }
-    result
-}
-
-fn normalize_fen(fen: &str) -> String {
-    let parts: Vec<&str> = fen.split_whitespace().collect();
-    if parts.len() < 4 {
-        return fen.to_string();
+    if pool.is_empty() {
+        pool.push('-');
     }
-    
-    let board = parts[0];
-    let side_to_move = parts[1];
-    let dark_pool_str = parts[2];
-    
-    // Check which side has dark pieces
-    let red_has_dark_pieces = board.contains('X');
-    let black_has_dark_pieces = board.contains('x');
-    let red_dark_count_on_board = board.chars().filter(|&c| c == 'X').count();
-    let black_dark_count_on_board = board.chars().filter(|&c| c == 'x').count();
-    
-    let (mut red_dark, mut black_dark) = if dark_pool_str != "-" {
-        let (temp_red, temp_black) = parse_pool_string(dark_pool_str);
-        (
-            if red_has_dark_pieces { temp_red } else { HashMap::new() },
-            if black_has_dark_pieces { temp_black } else { HashMap::new() }
-        )
-    } else {
-        (HashMap::new(), HashMap::new())
-    };
-    
-    // Normalize by GCD when and only when that side has exactly one dark piece
-    let gcd_red_dark = if red_dark_count_on_board == 1 { calculate_gcd(&red_dark) } else { 1 };
-    if gcd_red_dark > 1 {
-        for value in red_dark.values_mut() {
-            *value /= gcd_red_dark;
-        }
-    }
-    
-    let gcd_black_dark = if black_dark_count_on_board == 1 { calculate_gcd(&black_dark) } else { 1 };
-    if gcd_black_dark > 1 {
-        for value in black_dark.values_mut() {
-            *value /= gcd_black_dark;
-        }
-    }
-    
-    // Format pools
-    let red_order = "RNBACP";
-    let black_order = "rnbacp";
-    
-    let mut final_dark_pool = format_pool(&red_dark, red_order);
-    final_dark_pool.push_str(&format_pool(&black_dark, black_order));
-    
-    if final_dark_pool.is_empty() {
-        final_dark_pool = "-".to_string();
-    }
-    
-    format!("{} {} {} -", board, side_to_move, final_dark_pool)
+    format!("{} {} {} -", fen::board_field(&fen.squares), fen.side_to_move.fen_char(), pool)
 }
 
-fn flip_board(fen_string: &str) -> String {
-    let parts: Vec<&str> = fen_string.split_whitespace().collect();
-    if parts.len() < 4 {
-        return fen_string.to_string();
-    }
-    
-    let board = parts[0];
-    let side = parts[1];
-    let dark_pool = parts[2];
-    
-    let rows: Vec<&str> = board.split('/').collect();
-    let mut flipped_rows = Vec::new();
-    
-    for row in rows {
-        let flipped_row: String = row.chars().rev().collect();
-        flipped_rows.push(flipped_row);
+/// Mirror the board left to right.
+fn flip_board(fen: &Fen) -> Fen {
+    let mut flipped = fen.clone();
+    for row in flipped.squares.iter_mut() {
+        row.reverse();
     }
-    
-    let flipped_board = flipped_rows.join("/");
-    format!("{} {} {} -", flipped_board, side, dark_pool)
+    flipped
 }
 
-fn swap_colors_fen(normalized_fen: &str) -> String {
-    let parts: Vec<&str> = normalized_fen.split_whitespace().collect();
-    if parts.len() < 4 {
-        return normalized_fen.to_string();
+/// Swap the colours: pieces change side, the board is flipped top to bottom, and the side to
+/// move and the hidden pools are exchanged.
+fn swap_colors_fen(fen: &Fen) -> Fen {
+    let mut swapped = fen.clone();
+    swapped.squares.reverse();
+    for piece in swapped.squares.iter_mut().flatten().flatten() {
+        piece.side = piece.side.opponent();
     }
-    
-    let board = parts[0];
-    let side_to_move = parts[1];
-    let dark_pool_str = parts[2];
-    
-    // 1. Swap piece cases and flip board vertically
-    let swapped_case_board: String = board.chars().map(|c| {
-        if c.is_lowercase() && c.is_alphabetic() {
-            c.to_uppercase().next().unwrap_or(c)
-        } else if c.is_uppercase() && c.is_alphabetic() {
-            c.to_lowercase().next().unwrap_or(c)
-        } else {
-            c
-        }
-    }).collect();
-    
-    // Vertically flip the board (reverse row order)
-    let rows: Vec<&str> = swapped_case_board.split('/').collect();
-    let reversed_rows: Vec<&str> = rows.into_iter().rev().collect();
-    let swapped_board = reversed_rows.join("/");
-    
-    // 2. Swap side to move
-    let swapped_side = if side_to_move == "w" { "b" } else { "w" };
-    
-    // 3. Swap dark pool only
-    let (red_dark_orig, black_dark_orig) = parse_pool_string(dark_pool_str);
-    
-    let mut new_red_dark = HashMap::new();
-    for (&piece, &count) in &black_dark_orig {
-        new_red_dark.insert(piece.to_uppercase().next().unwrap_or(piece), count);
+    swapped.side_to_move = fen.side_to_move.opponent();
+    for role in Role::ALL {
+        swapped.hidden.set(Side::Red, role, fen.hidden.get(Side::Black, role));
+        swapped.hidden.set(Side::Black, role, fen.hidden.get(Side::Red, role));
     }
-    
-    let mut new_black_dark = HashMap::new();
-    for (&piece, &count) in &red_dark_orig {
-        new_black_dark.insert(piece.to_lowercase().next().unwrap_or(piece), count);
-    }
-    
-    let red_order = "RNBACP";
-    let black_order = "rnbacp";
-    
-    let mut swapped_dark_pool = format_pool(&new_red_dark, red_order);
-    swapped_dark_pool.push_str(&format_pool(&new_black_dark, black_order));
-    
-    if swapped_dark_pool.is_empty() {
-        swapped_dark_pool = "-".to_string();
-    }
-    
-    // Always use "-" for captured pool to ignore it in key calculation
-    format!("{} {} {} -", swapped_board, swapped_side, swapped_dark_pool)
+    swapped
 }
 
 
 // Compute key value, also return the transformation index used
 // Transformation index definitions:
 // 0 = original normalized FEN; 1 = horizontal mirror; 2 = color swap (with vertical flip); 3 = color swap then horizontal mirror
-fn compute_key_and_transform(fen: &str) -> (Vec<u8>, usize) {
-    let norm_fen = normalize_fen(fen);
+// Fails for a FEN that does not parse.
+fn compute_key_and_transform(fen: &str) -> std::result::Result<(Vec<u8>, usize), FenError> {
+    let norm_fen = normalize_fen(fen)?;
     let swapped_fen = swap_colors_fen(&norm_fen);
 
     let fens = [
-        norm_fen.clone(),
-        flip_board(&norm_fen),
-        swapped_fen.clone(),
-        flip_board(&swapped_fen),
+        key_fen(&norm_fen),
+        key_fen(&flip_board(&norm_fen)),
+        key_fen(&swapped_fen),
+        key_fen(&flip_board(&swapped_fen)),
     ];
 
     let mut min_reversed_hash = String::new();
@@ -445,7 +401,7 @@ fn compute_key_and_transform(fen: &str) -> (Vec<u8>, usize) {
         let byte = u8::from_str_radix(&key_hex[i..i + 2], 16).unwrap_or(0);
         key_blob.push(byte);
     }
-    (key_blob, min_idx)
+    Ok((key_blob, min_idx))
 }
 
 // Transform UCI move coordinates according to transformation index. This function is its own inverse (repeated calls with same index restore original).
@@ -517,3 +473,21 @@ fn int_to_uci(move_int: u16) -> String {
     
     format!("{}{}{}{}", from_x, from_y, to_x, to_y)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn both_layouts_share_a_key() {
+        let old = "xxxxkxxxx/9/1x5x1/x1x1x1x1x/9/9/X1X1X1X1X/1X5X1/9/XXXXKXXXX A2B2N2R2C2P5a2b2n2r2c2p5 w - - 0 1";
+        assert_eq!(compute_key_and_transform(old).unwrap(), compute_key_and_transform(rules::START_FEN).unwrap());
+    }
+
+    #[test]
+    fn invalid_fen_is_a_fen_error() {
+        let book = JieqiOpeningBook::new(":memory:").unwrap();
+        let error = book.query_moves("9/9 w").unwrap_err();
+        assert!(matches!(error, BookError::Fen(FenError { field: fen::FenField::Board, .. })), "{}", error);
+    }
+}
@@ -0,0 +1,144 @@
+// src-tauri/src/output_batch.rs
+// Coalescing of engine stdout before it reaches the frontend. Lines are gathered for a short
+// interval and emitted together; analysis lines superseded by a newer one for the same
+// multipv within a batch are dropped, and lines the frontend waits on go out at once.
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// How long stdout lines are gathered before they are emitted.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct BatchConfig {
+    /// 0 emits every read as it arrives.
+    pub interval_ms: u64,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        BatchConfig { interval_ms: 50 }
+    }
+}
+
+/// Lines waiting to be emitted, and when they are due.
+#[derive(Default)]
+pub struct OutputBatch {
+    lines: Vec<String>,
+    deadline: Option<Instant>,
+}
+
+impl OutputBatch {
+    /// Add lines to the batch. Returns true when the batch must be emitted now: because a
+    /// line in it ends a search or answers `isready`, or because batching is off.
+    pub fn push(&mut self, lines: Vec<String>, config: BatchConfig) -> bool {
+        let mut urgent = config.interval_ms == 0;
+        for line in lines {
+            if let Some(multipv) = analysis_multipv(&line) {
+                self.lines.retain(|pending| analysis_multipv(pending) != Some(multipv));
+            }
+            urgent |= is_urgent(&line);
+            self.lines.push(line);
+        }
+        if !self.lines.is_empty() && self.deadline.is_none() {
+            self.deadline = Some(Instant::now() + Duration::from_millis(config.interval_ms));
+        }
+        urgent
+    }
+
+    /// When the batch is due, if it holds any lines.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    pub fn take(&mut self) -> Vec<String> {
+        self.deadline = None;
+        std::mem::take(&mut self.lines)
+    }
+}
+
+/// `bestmove` and `readyok` are waited on by the frontend and the clock, so they are never held back.
+fn is_urgent(line: &str) -> bool {
+    matches!(line.split_whitespace().next(), Some("bestmove") | Some("readyok"))
+}
+
+/// Multipv slot of an `info` line carrying a score or pv, which the next such line for the
+/// same slot replaces. Other `info` lines (`string`, `currmove`, ...) are always kept.
+fn analysis_multipv(line: &str) -> Option<u32> {
+    let mut tokens = line.split_whitespace();
+    if tokens.next() != Some("info") {
+        return None;
+    }
+    let mut multipv = 1;
+    let mut analysis = false;
+    while let Some(token) = tokens.next() {
+        match token {
+            // Free text runs to the end of the line and may contain any keyword
+            "string" => return None,
+            "score" | "pv" => analysis = true,
+            "multipv" => multipv = tokens.next().and_then(|v| v.parse().ok()).unwrap_or(1),
+            _ => {}
+        }
+    }
+    analysis.then_some(multipv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BATCHED: BatchConfig = BatchConfig { interval_ms: 50 };
+
+    fn lines(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn newer_analysis_replaces_its_multipv_slot() {
+        let mut batch = OutputBatch::default();
+        assert!(!batch.push(lines(&["info depth 1 score cp 10 pv h2e2", "info depth 1 multipv 2 score cp 5 pv b2e2"]), BATCHED));
+        assert!(!batch.push(lines(&["info currmove h2e2 currmovenumber 1", "info depth 2 score cp 20 pv h2e2 h9g7"]), BATCHED));
+        assert!(!batch.push(lines(&["info string score pv are just words here", "info nodes 1000 nps 5000"]), BATCHED));
+        assert_eq!(
+            batch.take(),
+            [
+                "info depth 1 multipv 2 score cp 5 pv b2e2",
+                "info currmove h2e2 currmovenumber 1",
+                "info depth 2 score cp 20 pv h2e2 h9g7",
+                "info string score pv are just words here",
+                "info nodes 1000 nps 5000",
+            ]
+        );
+        assert_eq!(analysis_multipv("info depth 3 multipv 3 pv a0a1"), Some(3));
+        assert_eq!(analysis_multipv("info depth 3 multipv x score cp 1"), Some(1));
+        assert_eq!(analysis_multipv("bestmove h2e2"), None);
+    }
+
+    #[test]
+    fn bestmove_and_readyok_flush_at_once() {
+        let mut batch = OutputBatch::default();
+        assert!(!batch.push(lines(&["info depth 5 score cp 30 pv h2e2"]), BATCHED));
+        assert!(batch.push(lines(&["bestmove h2e2 ponder h9g7"]), BATCHED));
+        assert_eq!(batch.take().len(), 2);
+        assert!(batch.push(lines(&["readyok"]), BATCHED));
+        assert!(!is_urgent("info string bestmove soon"));
+        // Without an interval every read goes out as it arrives
+        assert!(batch.push(lines(&["id name Pikafish"]), BatchConfig { interval_ms: 0 }));
+    }
+
+    #[test]
+    fn deadline_follows_the_interval() {
+        let mut batch = OutputBatch::default();
+        assert!(!batch.push(Vec::new(), BATCHED));
+        assert_eq!(batch.deadline(), None);
+
+        let before = Instant::now();
+        batch.push(lines(&["info depth 1 score cp 10 pv h2e2"]), BATCHED);
+        let deadline = batch.deadline().unwrap();
+        assert!(deadline >= before + Duration::from_millis(50));
+        assert!(deadline <= Instant::now() + Duration::from_millis(50));
+        // Later lines join the batch without moving its deadline
+        batch.push(lines(&["info depth 2 score cp 12 pv h2e2"]), BatchConfig { interval_ms: 500 });
+        assert_eq!(batch.deadline(), Some(deadline));
+
+        assert_eq!(batch.take().len(), 1);
+        assert_eq!(batch.deadline(), None);
+    }
+}
@@ -0,0 +1,221 @@
+// src-tauri/src/perft.rs
+// Move-path enumeration for testing the move generator. A flip is a chance event: it can be
+// expanded into one child per identity the hidden pool still holds for the moving side, or
+// counted as a single move whose piece turns out to be the role of its starting square.
+use crate::rules::{self, Board, Move, Role};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+/// How a move that reveals a dark piece is counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevealMode {
+    /// One child per distinct identity left in the mover's hidden pool.
+    #[default]
+    Expand,
+    /// One child, revealed as its starting square's role (or the first role left in the pool).
+    Single,
+}
+
+/// Leaf counts, split by the kind of the last move.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PerftCounts {
+    pub nodes: u64,
+    /// Last move revealed a dark piece.
+    pub reveals: u64,
+    /// Last move was made by a revealed piece.
+    pub plain: u64,
+    pub captures: u64,
+    /// Last move left the opponent in check.
+    pub checks: u64,
+}
+
+impl std::ops::AddAssign for PerftCounts {
+    fn add_assign(&mut self, other: Self) {
+        self.nodes += other.nodes;
+        self.reveals += other.reveals;
+        self.plain += other.plain;
+        self.captures += other.captures;
+        self.checks += other.checks;
+    }
+}
+
+/// Counts below one root move.
+#[derive(Clone, Debug, Serialize)]
+pub struct MoveCounts {
+    pub uci_move: String,
+    pub counts: PerftCounts,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PerftResult {
+    pub fen: String,
+    pub depth: u32,
+    pub mode: RevealMode,
+    pub totals: PerftCounts,
+    /// Root moves in generation order.
+    pub moves: Vec<MoveCounts>,
+    pub time_ms: u64,
+}
+
+/// A position with its recorded leaf count, for catching regressions in the generator.
+#[derive(Clone, Copy, Debug)]
+pub struct PerftReference {
+    pub name: &'static str,
+    pub fen: &'static str,
+    pub mode: RevealMode,
+    pub depth: u32,
+    pub nodes: u64,
+    /// Whether the count matches an independent source rather than only this generator.
+    pub verified: bool,
+}
+
+/// A flip on the opening's first move: dark pieces on both sides, one revealed cannon.
+const FLIPPED_CANNON_FEN: &str =
+    "xxxxkxxxx/9/1x5x1/x1x1x1x1x/9/9/X1X1X1X1X/1X2C4/9/XXXXKXXXX b A2B2N2R2C1P5a2b2n2r2c2p5 - 1 1";
+/// Everything revealed, so both modes count the same.
+const REVEALED_FEN: &str = "2bakab2/9/4c4/p1p1R1p1p/9/9/P1P1P1P1P/4C4/9/2BAKAB2 w - - 0 1";
+
+/// Reference counts recorded from this generator. Counting flips once from the opening gives
+/// Xiangqi's start position counts, as dark pieces move like the pieces that start on their squares,
+/// so those three match the published Xiangqi perft values and are marked verified. The others
+/// have no external source and only catch changes in the generator.
+pub const REFERENCE_POSITIONS: &[PerftReference] = &[
+    PerftReference { name: "start", fen: rules::START_FEN, mode: RevealMode::Single, depth: 1, nodes: 44, verified: true },
+    PerftReference { name: "start", fen: rules::START_FEN, mode: RevealMode::Single, depth: 2, nodes: 1920, verified: true },
+    PerftReference { name: "start", fen: rules::START_FEN, mode: RevealMode::Single, depth: 3, nodes: 79666, verified: true },
+    PerftReference { name: "start", fen: rules::START_FEN, mode: RevealMode::Expand, depth: 1, nodes: 259, verified: false },
+    PerftReference { name: "start", fen: rules::START_FEN, mode: RevealMode::Expand, depth: 2, nodes: 66462, verified: false },
+    PerftReference { name: "start", fen: rules::START_FEN, mode: RevealMode::Expand, depth: 3, nodes: 13862869, verified: false },
+    PerftReference { name: "flipped cannon", fen: FLIPPED_CANNON_FEN, mode: RevealMode::Single, depth: 2, nodes: 1564, verified: false },
+    PerftReference { name: "flipped cannon", fen: FLIPPED_CANNON_FEN, mode: RevealMode::Single, depth: 3, nodes: 66333, verified: false },
+    PerftReference { name: "flipped cannon", fen: FLIPPED_CANNON_FEN, mode: RevealMode::Expand, depth: 2, nodes: 43442, verified: false },
+    PerftReference { name: "revealed", fen: REVEALED_FEN, mode: RevealMode::Expand, depth: 3, nodes: 9879, verified: false },
+    PerftReference { name: "revealed", fen: REVEALED_FEN, mode: RevealMode::Expand, depth: 4, nodes: 141736, verified: false },
+];
+
+/// Count the leaves `depth` plies below `fen`, split by root move.
+pub fn perft(fen: &str, depth: u32, mode: RevealMode) -> Result<PerftResult, String> {
+    let started = Instant::now();
+    let board = Board::from_fen(fen)?;
+    let mut totals = PerftCounts::default();
+    let mut moves = Vec::new();
+    if depth == 0 {
+        totals.nodes = 1;
+    } else {
+        for mv in board.legal_moves() {
+            let mut counts = PerftCounts::default();
+            for child in children(&board, mv, mode)? {
+                count(&child, depth - 1, mode, &mut counts)?;
+            }
+            totals += counts;
+            moves.push(MoveCounts {
+                uci_move: mv.to_string(),
+                counts,
+            });
+        }
+    }
+    Ok(PerftResult {
+        fen: board.to_fen(),
+        depth,
+        mode,
+        totals,
+        moves,
+        time_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Run every reference position up to `max_depth` plies, returning each with the count found.
+pub fn check_references(max_depth: u32) -> Vec<(PerftReference, Result<u64, String>)> {
+    REFERENCE_POSITIONS
+        .iter()
+        .filter(|reference| reference.depth <= max_depth)
+        .map(|reference| {
+            let found = perft(reference.fen, reference.depth, reference.mode);
+            (*reference, found.map(|result| result.totals.nodes))
+        })
+        .collect()
+}
+
+/// A position reached by one move, and what kind of move it was.
+struct Child {
+    board: Board,
+    reveal: bool,
+    capture: bool,
+}
+
+/// Add the leaves `depth` plies below `child` to `out`.
+fn count(child: &Child, depth: u32, mode: RevealMode, out: &mut PerftCounts) -> Result<(), String> {
+    if depth == 0 {
+        out.nodes += 1;
+        if child.reveal {
+            out.reveals += 1;
+        } else {
+            out.plain += 1;
+        }
+        if child.capture {
+            out.captures += 1;
+        }
+        if child.board.in_check(child.board.side_to_move) {
+            out.checks += 1;
+        }
+        return Ok(());
+    }
+    for mv in child.board.legal_moves() {
+        for grandchild in children(&child.board, mv, mode)? {
+            count(&grandchild, depth - 1, mode, out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Positions after `mv`: one per possible identity of a revealed piece. A dark piece that is
+/// captured leaves the pools as they are, as its identity is not dealt here.
+fn children(board: &Board, mv: Move, mode: RevealMode) -> Result<Vec<Child>, String> {
+    let piece = board.piece_at(mv.from).expect("legal move has a piece");
+    let capture = board.piece_at(mv.to).is_some();
+    let reveals: Vec<Option<Role>> = if piece.is_dark() {
+        let available: Vec<Role> = Role::ALL
+            .into_iter()
+            .filter(|&role| board.hidden.get(piece.side, role) > 0)
+            .collect();
+        if available.is_empty() {
+            return Err(format!("The hidden pool has no piece left for the dark piece on {}.", mv.from));
+        }
+        match mode {
+            RevealMode::Expand => available.into_iter().map(Some).collect(),
+            RevealMode::Single => {
+                let start = rules::start_role(mv.from).filter(|role| available.contains(role));
+                vec![Some(start.unwrap_or(available[0]))]
+            }
+        }
+    } else {
+        vec![None]
+    };
+    reveals
+        .into_iter()
+        .map(|reveal| {
+            let mut next = board.clone();
+            next.play(mv, reveal, None)?;
+            Ok(Child {
+                board: next,
+                reveal: reveal.is_some(),
+                capture,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shallow_references_match() {
+        let checked = check_references(2);
+        assert!(!checked.is_empty());
+        for (reference, found) in checked {
+            assert_eq!(found, Ok(reference.nodes), "{} ({:?}, depth {})", reference.name, reference.mode, reference.depth);
+        }
+    }
+}
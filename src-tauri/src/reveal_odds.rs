@@ -0,0 +1,200 @@
+// src-tauri/src/reveal_odds.rs
+// What dark pieces turn out to be. Every dark piece of a side is dealt from that side's hidden
+// pool, so each one is a given role with probability count / pool size. The pool is read in
+// book form (`normalize_fen`); its GCD division for a lone dark piece keeps these ratios.
+use crate::opening_book::normalize_fen;
+use crate::rules::{Board, Move, Pool, Role, Side};
+use serde::Serialize;
+
+/// Probability of one identity.
+#[derive(Clone, Debug, Serialize)]
+pub struct RoleOdds {
+    /// FEN letter of the role.
+    pub piece: char,
+    /// Count in the normalized pool.
+    pub count: u8,
+    pub probability: f64,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct SideOdds {
+    pub side: Side,
+    pub dark_pieces: usize,
+    /// Pieces in the normalized pool.
+    pub pool_size: usize,
+    pub odds: Vec<RoleOdds>,
+}
+
+/// Odds for the dark piece on one square.
+#[derive(Clone, Debug, Serialize)]
+pub struct SquareOdds {
+    pub square: String,
+    pub side: Side,
+    pub odds: Vec<RoleOdds>,
+}
+
+/// What a capture turns over.
+#[derive(Clone, Debug, Serialize)]
+pub struct CaptureOdds {
+    pub uci_move: String,
+    /// Identity of the capturing piece, when it is dark and so revealed by moving.
+    pub mover: Option<Vec<RoleOdds>>,
+    /// Identity of the captured piece, when it is dark.
+    pub captured: Option<Vec<RoleOdds>>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RevealOdds {
+    /// Sides with dark pieces on the board.
+    pub sides: Vec<SideOdds>,
+    /// Dark squares in board order, top left first.
+    pub squares: Vec<SquareOdds>,
+    pub captures: Vec<CaptureOdds>,
+}
+
+/// Odds of each role left in `side`'s pool, in `Role::ALL` order.
+fn pool_odds(pool: &Pool, side: Side) -> Vec<RoleOdds> {
+    let size = pool.pieces(side).len();
+    Role::ALL
+        .into_iter()
+        .filter(|&role| pool.get(side, role) > 0)
+        .map(|role| RoleOdds {
+            piece: role.fen_char(side),
+            count: pool.get(side, role),
+            probability: pool.get(side, role) as f64 / size as f64,
+        })
+        .collect()
+}
+
+/// Odds for every dark piece of a position, and for what captures involving dark pieces turn
+/// over: `uci_move` alone when given (which must be a legal capture), otherwise every such
+/// legal capture.
+pub fn reveal_odds(fen: &str, uci_move: Option<&str>) -> Result<RevealOdds, String> {
+    let fen = normalize_fen(fen)?;
+    let mut sides = Vec::new();
+    for side in [Side::Red, Side::Black] {
+        let dark_pieces = fen.dark_count(side);
+        if dark_pieces == 0 {
+            continue;
+        }
+        let pool_size = fen.hidden.pieces(side).len();
+        if pool_size == 0 {
+            return Err(format!("The hidden pool has no piece left for {}'s dark pieces.", side.name()));
+        }
+        sides.push(SideOdds {
+            side,
+            dark_pieces,
+            pool_size,
+            odds: pool_odds(&fen.hidden, side),
+        });
+    }
+    let squares = fen
+        .pieces()
+        .filter(|(_, piece)| piece.is_dark())
+        .map(|(square, piece)| SquareOdds {
+            square: square.to_string(),
+            side: piece.side,
+            odds: pool_odds(&fen.hidden, piece.side),
+        })
+        .collect();
+
+    let board = Board::from(fen);
+    let dark_odds = |square| {
+        let piece = board.piece_at(square)?;
+        piece.is_dark().then(|| pool_odds(&board.hidden, piece.side))
+    };
+    let capture_odds = |mv: Move| CaptureOdds {
+        uci_move: mv.to_string(),
+        mover: dark_odds(mv.from),
+        captured: dark_odds(mv.to),
+    };
+    let captures = match uci_move {
+        Some(uci) => {
+            let mv = Move::from_uci(uci).ok_or_else(|| format!("Invalid move '{}'.", uci))?;
+            board.check_move(mv)?;
+            if board.piece_at(mv.to).is_none() {
+                return Err(format!("{} is not a capture.", mv));
+            }
+            vec![capture_odds(mv)]
+        }
+        None => board
+            .legal_moves()
+            .into_iter()
+            .filter(|&mv| board.piece_at(mv.to).is_some())
+            .map(capture_odds)
+            .filter(|capture| capture.mover.is_some() || capture.captured.is_some())
+            .collect(),
+    };
+    Ok(RevealOdds { sides, squares, captures })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::START_FEN;
+
+    fn probability(odds: &[RoleOdds], piece: char) -> f64 {
+        odds.iter().find(|o| o.piece == piece).map_or(0.0, |o| o.probability)
+    }
+
+    #[test]
+    fn probabilities_sum_to_one_per_side() {
+        let odds = reveal_odds(START_FEN, None).unwrap();
+        assert_eq!(odds.sides.len(), 2);
+        for side in &odds.sides {
+            assert_eq!((side.dark_pieces, side.pool_size), (15, 15));
+            let total: f64 = side.odds.iter().map(|o| o.probability).sum();
+            assert!((total - 1.0).abs() < 1e-9);
+        }
+        assert_eq!(probability(&odds.sides[0].odds, 'P'), 5.0 / 15.0);
+        assert_eq!(probability(&odds.sides[1].odds, 'r'), 2.0 / 15.0);
+        assert_eq!(odds.squares.len(), 30);
+        assert_eq!((odds.squares[0].square.as_str(), odds.squares[0].side), ("a9", Side::Black));
+    }
+
+    #[test]
+    fn a_lone_dark_piece_reads_the_pool_in_lowest_terms() {
+        let odds = reveal_odds("3k5/9/9/9/9/9/9/9/9/3XK4 w R2C2P4 r1 0 1", None).unwrap();
+        assert_eq!(odds.sides.len(), 1);
+        let red = &odds.sides[0];
+        assert_eq!((red.side, red.dark_pieces, red.pool_size), (Side::Red, 1, 4));
+        let counts: Vec<_> = red.odds.iter().map(|o| (o.piece, o.count)).collect();
+        assert_eq!(counts, [('R', 1), ('C', 1), ('P', 2)]);
+        assert_eq!(probability(&red.odds, 'P'), 0.5);
+    }
+
+    #[test]
+    fn captures_are_the_legal_ones_of_the_given_move() {
+        let all = reveal_odds(START_FEN, None).unwrap().captures;
+        let moves: Vec<_> = all.iter().map(|c| c.uci_move.as_str()).collect();
+        assert_eq!(moves, ["b2b9", "h2h9"]);
+
+        let given = reveal_odds(START_FEN, Some("b2b9")).unwrap().captures;
+        assert_eq!(given.len(), 1);
+        let capture = &given[0];
+        assert_eq!(probability(capture.mover.as_ref().unwrap(), 'C'), 2.0 / 15.0);
+        assert_eq!(probability(capture.captured.as_ref().unwrap(), 'n'), 2.0 / 15.0);
+
+        // Revealed pieces turn nothing over, so only the given move reports them
+        let fen = "3k5/9/9/9/4n4/9/9/9/4R4/3XK4 w A2 - 0 1";
+        assert!(reveal_odds(fen, None).unwrap().captures.is_empty());
+        let given = reveal_odds(fen, Some("e1e5")).unwrap().captures;
+        assert!(given[0].mover.is_none() && given[0].captured.is_none());
+    }
+
+    #[test]
+    fn rejects_moves_and_pools_it_cannot_read() {
+        assert_eq!(reveal_odds(START_FEN, Some("z9")).unwrap_err(), "Invalid move 'z9'.");
+        assert_eq!(
+            reveal_odds(START_FEN, Some("b2b8")).unwrap_err(),
+            "b2b8 does not follow the movement rules of the piece on b2."
+        );
+        assert_eq!(reveal_odds(START_FEN, Some("e9e8")).unwrap_err(), "The piece on e9 is not the side to move's.");
+        assert_eq!(reveal_odds(START_FEN, Some("b2e2")).unwrap_err(), "b2e2 is not a capture.");
+        assert_eq!(
+            reveal_odds("3k5/9/9/9/9/9/9/9/9/3XK4 w - - 0 1", None).unwrap_err(),
+            "The hidden pool has no piece left for Red's dark pieces."
+        );
+        assert!(reveal_odds("not a fen", None).is_err());
+    }
+}
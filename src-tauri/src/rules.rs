@@ -0,0 +1,756 @@
+// src-tauri/src/rules.rs
+// Jieqi board model and move generation, mirroring the rules in `useChessGame.ts`.
+//
+// Row 0 is Black's back rank and row 9 Red's; in UCI notation the file is the column
+// (`a`..`i`) and the rank is `9 - row`. A dark piece always stands on its starting square
+// (it is revealed by its first move) and moves with the role that square starts with.
+
+use crate::fen::{self, Fen};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub const START_FEN: &str =
+    "xxxxkxxxx/9/1x5x1/x1x1x1x1x/9/9/X1X1X1X1X/1X5X1/9/XXXXKXXXX w A2B2N2R2C2P5a2b2n2r2c2p5 - 0 1";
+
+pub const ROWS: usize = 10;
+pub const COLS: usize = 9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Red,
+    Black,
+}
+
+impl Side {
+    pub fn opponent(self) -> Side {
+        match self {
+            Side::Red => Side::Black,
+            Side::Black => Side::Red,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    pub fn fen_char(self) -> char {
+        match self {
+            Side::Red => 'w',
+            Side::Black => 'b',
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Side::Red => "Red",
+            Side::Black => "Black",
+        }
+    }
+}
+
+/// Piece roles in pool order (`RNBAKCP`, as the FEN hidden pool lists them).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    Chariot,
+    Horse,
+    Elephant,
+    Advisor,
+    King,
+    Cannon,
+    Pawn,
+}
+
+impl Role {
+    pub const ALL: [Role; 7] = [
+        Role::Chariot,
+        Role::Horse,
+        Role::Elephant,
+        Role::Advisor,
+        Role::King,
+        Role::Cannon,
+        Role::Pawn,
+    ];
+
+    pub fn index(self) -> usize {
+        self as usize
+    }
+
+    /// FEN letter of the role for the given side (upper case for Red).
+    pub fn fen_char(self, side: Side) -> char {
+        let c = match self {
+            Role::Chariot => 'R',
+            Role::Horse => 'N',
+            Role::Elephant => 'B',
+            Role::Advisor => 'A',
+            Role::King => 'K',
+            Role::Cannon => 'C',
+            Role::Pawn => 'P',
+        };
+        match side {
+            Side::Red => c,
+            Side::Black => c.to_ascii_lowercase(),
+        }
+    }
+
+    pub fn from_fen_char(c: char) -> Option<(Role, Side)> {
+        let role = match c.to_ascii_uppercase() {
+            'R' => Role::Chariot,
+            'N' => Role::Horse,
+            'B' => Role::Elephant,
+            'A' => Role::Advisor,
+            'K' => Role::King,
+            'C' => Role::Cannon,
+            'P' => Role::Pawn,
+            _ => return None,
+        };
+        let side = if c.is_ascii_uppercase() { Side::Red } else { Side::Black };
+        Some((role, side))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Square {
+    pub row: u8,
+    pub col: u8,
+}
+
+impl Square {
+    pub fn new(row: usize, col: usize) -> Self {
+        Square { row: row as u8, col: col as u8 }
+    }
+
+    /// Parse a UCI square such as `e0`.
+    pub fn from_uci(s: &str) -> Option<Square> {
+        let mut chars = s.chars();
+        let file = chars.next()?;
+        let rank = chars.next()?.to_digit(10)? as usize;
+        if chars.next().is_some() || !('a'..='i').contains(&file) {
+            return None;
+        }
+        Some(Square::new(9 - rank, file as usize - 'a' as usize))
+    }
+
+    fn offset(self, d_row: i32, d_col: i32) -> Option<Square> {
+        let row = self.row as i32 + d_row;
+        let col = self.col as i32 + d_col;
+        ((0..ROWS as i32).contains(&row) && (0..COLS as i32).contains(&col)).then(|| Square::new(row as usize, col as usize))
+    }
+}
+
+impl fmt::Display for Square {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.col) as char, 9 - self.row)
+    }
+}
+
+/// Role a dark piece on this square moves as (`getRoleByPosition`).
+pub fn start_role(square: Square) -> Option<Role> {
+    match (square.row, square.col) {
+        (0 | 9, 0 | 8) => Some(Role::Chariot),
+        (0 | 9, 1 | 7) => Some(Role::Horse),
+        (0 | 9, 2 | 6) => Some(Role::Elephant),
+        (0 | 9, 3 | 5) => Some(Role::Advisor),
+        (0 | 9, 4) => Some(Role::King),
+        (2 | 7, 1 | 7) => Some(Role::Cannon),
+        (3 | 6, 0 | 2 | 4 | 6 | 8) => Some(Role::Pawn),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Piece {
+    pub side: Side,
+    /// The piece's identity, or `None` while it is still face down.
+    pub role: Option<Role>,
+}
+
+impl Piece {
+    pub fn is_dark(&self) -> bool {
+        self.role.is_none()
+    }
+}
+
+/// Piece counts per side and role, used for the hidden and captured pools.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Pool {
+    counts: [[u8; 7]; 2],
+}
+
+impl Pool {
+    pub fn get(&self, side: Side, role: Role) -> u8 {
+        self.counts[side.index()][role.index()]
+    }
+
+    pub fn set(&mut self, side: Side, role: Role, n: u8) {
+        self.counts[side.index()][role.index()] = n;
+    }
+
+    pub fn add(&mut self, side: Side, role: Role, n: u8) {
+        self.counts[side.index()][role.index()] += n;
+    }
+
+    /// Remove one piece; fails when there is none left.
+    pub fn take(&mut self, side: Side, role: Role) -> Result<(), String> {
+        let count = &mut self.counts[side.index()][role.index()];
+        if *count == 0 {
+            return Err(format!("No {} left in the pool.", role.fen_char(side)));
+        }
+        *count -= 1;
+        Ok(())
+    }
+
+    /// Every piece of a side, one entry per copy.
+    pub fn pieces(&self, side: Side) -> Vec<Role> {
+        Role::ALL
+            .iter()
+            .flat_map(|&role| std::iter::repeat_n(role, self.get(side, role) as usize))
+            .collect()
+    }
+
+    /// Pool field in FEN order (`RNBAKCP`, red before black per role), `-` when empty.
+    pub fn to_fen(self) -> String {
+        let mut out = String::new();
+        for role in Role::ALL {
+            for side in [Side::Red, Side::Black] {
+                let count = self.get(side, role);
+                if count > 0 {
+                    out.push(role.fen_char(side));
+                    out.push_str(&count.to_string());
+                }
+            }
+        }
+        if out.is_empty() {
+            out.push('-');
+        }
+        out
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub from: Square,
+    pub to: Square,
+}
+
+impl Move {
+    /// Parse the square part of a UCI move; any reveal/capture suffix is ignored.
+    pub fn from_uci(s: &str) -> Option<Move> {
+        Some(Move {
+            from: Square::from_uci(s.get(0..2)?)?,
+            to: Square::from_uci(s.get(2..4)?)?,
+        })
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.from, self.to)
+    }
+}
+
+/// What `Board::play` changed, for writing the extended UCI move (`a3a4R`, `b7b0Nr`, ...).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveOutcome {
+    pub revealed: Option<Role>,
+    pub captured: Option<Piece>,
+    /// Identity of a dark piece captured by the move.
+    pub captured_identity: Option<Role>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Board {
+    squares: [[Option<Piece>; COLS]; ROWS],
+    pub side_to_move: Side,
+    /// Identities of the dark pieces still on the board.
+    pub hidden: Pool,
+    /// Identities of dark pieces captured before they were revealed.
+    pub captured: Pool,
+    pub halfmove_clock: u32,
+    pub fullmove_number: u32,
+}
+
+impl From<Fen> for Board {
+    fn from(fen: Fen) -> Self {
+        Board {
+            squares: fen.squares,
+            side_to_move: fen.side_to_move,
+            hidden: fen.hidden,
+            captured: fen.captured,
+            halfmove_clock: fen.halfmove_clock,
+            fullmove_number: fen.fullmove_number,
+        }
+    }
+}
+
+impl Board {
+    /// Parse a FEN in the new (`board side hidden captured half full`) or old
+    /// (`board hidden side - - half full`) layout. Missing trailing fields take their defaults.
+    pub fn from_fen(fen: &str) -> Result<Board, String> {
+        Ok(Board::from(Fen::parse(fen)?))
+    }
+
+    /// FEN in the new layout.
+    pub fn to_fen(&self) -> String {
+        format!(
+            "{} {} {} {} {} {}",
+            self.board_fen(),
+            self.side_to_move.fen_char(),
+            self.hidden.to_fen(),
+            self.captured.to_fen(),
+            self.halfmove_clock,
+            self.fullmove_number
+        )
+    }
+
+    /// Piece placement field of the FEN.
+    pub fn board_fen(&self) -> String {
+        fen::board_field(&self.squares)
+    }
+
+    pub fn piece_at(&self, square: Square) -> Option<Piece> {
+        self.squares[square.row as usize][square.col as usize]
+    }
+
+    fn set(&mut self, square: Square, piece: Option<Piece>) {
+        self.squares[square.row as usize][square.col as usize] = piece;
+    }
+
+    /// All occupied squares with their pieces.
+    pub fn pieces(&self) -> impl Iterator<Item = (Square, Piece)> + '_ {
+        (0..ROWS).flat_map(move |row| {
+            (0..COLS).filter_map(move |col| {
+                let square = Square::new(row, col);
+                self.piece_at(square).map(|piece| (square, piece))
+            })
+        })
+    }
+
+    pub fn king_square(&self, side: Side) -> Option<Square> {
+        self.pieces()
+            .find(|(_, p)| p.side == side && p.role == Some(Role::King))
+            .map(|(square, _)| square)
+    }
+
+    /// Role the piece on `square` moves as: its identity, or the starting-square role while dark.
+    fn moving_role(&self, square: Square) -> Option<Role> {
+        let piece = self.piece_at(square)?;
+        piece.role.or_else(|| start_role(square))
+    }
+
+    fn count_between(&self, from: Square, to: Square) -> usize {
+        if from.row == to.row {
+            let (lo, hi) = (from.col.min(to.col), from.col.max(to.col));
+            (lo + 1..hi).filter(|&c| self.piece_at(Square { row: from.row, col: c }).is_some()).count()
+        } else {
+            let (lo, hi) = (from.row.min(to.row), from.row.max(to.row));
+            (lo + 1..hi).filter(|&r| self.piece_at(Square { row: r, col: from.col }).is_some()).count()
+        }
+    }
+
+    /// Whether the piece on `from` may move to `to` by its movement rules alone
+    /// (`isMoveMechanicallyValid`); own-king safety is not considered.
+    pub fn is_mechanically_valid(&self, from: Square, to: Square) -> bool {
+        let Some(piece) = self.piece_at(from) else {
+            return false;
+        };
+        let Some(role) = self.moving_role(from) else {
+            return false;
+        };
+        let target = self.piece_at(to);
+        if from == to || target.is_some_and(|t| t.side == piece.side) {
+            return false;
+        }
+        let d_row = (to.row as i32 - from.row as i32).abs();
+        let d_col = (to.col as i32 - from.col as i32).abs();
+
+        match role {
+            Role::King => {
+                let rows = match piece.side {
+                    Side::Red => 7..=9,
+                    Side::Black => 0..=2,
+                };
+                d_row + d_col == 1 && (3..=5).contains(&to.col) && rows.contains(&to.row)
+            }
+            Role::Advisor => {
+                // A dark advisor may not step towards the board edge
+                let forbidden = piece.is_dark()
+                    && matches!(
+                        (from.row, from.col, to.row, to.col),
+                        (0, 3, 1, 2) | (0, 5, 1, 6) | (9, 3, 8, 2) | (9, 5, 8, 6)
+                    );
+                !forbidden && d_row == 1 && d_col == 1
+            }
+            Role::Elephant => {
+                let eye = Square {
+                    row: (from.row + to.row) / 2,
+                    col: (from.col + to.col) / 2,
+                };
+                d_row == 2 && d_col == 2 && self.piece_at(eye).is_none()
+            }
+            Role::Horse => {
+                if !((d_row == 2 && d_col == 1) || (d_row == 1 && d_col == 2)) {
+                    return false;
+                }
+                let leg = if d_row == 2 {
+                    Square { row: (from.row + to.row) / 2, col: from.col }
+                } else {
+                    Square { row: from.row, col: (from.col + to.col) / 2 }
+                };
+                self.piece_at(leg).is_none()
+            }
+            Role::Chariot => (d_row == 0 || d_col == 0) && self.count_between(from, to) == 0,
+            Role::Cannon => {
+                let screens = if target.is_some() { 1 } else { 0 };
+                (d_row == 0 || d_col == 0) && self.count_between(from, to) == screens
+            }
+            Role::Pawn => {
+                let (forward, crossed) = match piece.side {
+                    Side::Red => (-1, from.row <= 4),
+                    Side::Black => (1, from.row >= 5),
+                };
+                let d = to.row as i32 - from.row as i32;
+                (d == forward && d_col == 0) || (crossed && d_row == 0 && d_col == 1)
+            }
+        }
+    }
+
+    /// Squares the piece on `from` could reach by movement pattern, before blocking checks.
+    fn candidate_targets(&self, from: Square, role: Role) -> Vec<Square> {
+        const KING: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+        const ADVISOR: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+        const ELEPHANT: [(i32, i32); 4] = [(2, 2), (2, -2), (-2, 2), (-2, -2)];
+        const HORSE: [(i32, i32); 8] = [(2, 1), (2, -1), (-2, 1), (-2, -1), (1, 2), (1, -2), (-1, 2), (-1, -2)];
+
+        let steps: &[(i32, i32)] = match role {
+            Role::King | Role::Pawn => &KING,
+            Role::Advisor => &ADVISOR,
+            Role::Elephant => &ELEPHANT,
+            Role::Horse => &HORSE,
+            Role::Chariot | Role::Cannon => {
+                let rank = (0..COLS).map(|col| Square::new(from.row as usize, col));
+                let file = (0..ROWS).map(|row| Square::new(row, from.col as usize));
+                return rank.chain(file).filter(|&s| s != from).collect();
+            }
+        };
+        steps.iter().filter_map(|&(dr, dc)| from.offset(dr, dc)).collect()
+    }
+
+    /// Moves of the side to move that follow the movement rules, ignoring king safety.
+    pub fn pseudo_legal_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for (from, piece) in self.pieces() {
+            if piece.side != self.side_to_move {
+                continue;
+            }
+            let Some(role) = self.moving_role(from) else {
+                continue;
+            };
+            for to in self.candidate_targets(from, role) {
+                if self.is_mechanically_valid(from, to) {
+                    moves.push(Move { from, to });
+                }
+            }
+        }
+        moves
+    }
+
+    /// Moves of the side to move that do not leave its own king in check.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.pseudo_legal_moves()
+            .into_iter()
+            .filter(|&mv| !self.leaves_king_in_check(mv))
+            .collect()
+    }
+
+    pub fn is_legal(&self, mv: Move) -> bool {
+        self.check_move(mv).is_ok()
+    }
+
+    /// Why `mv` is not legal for the side to move, if it is not.
+    pub fn check_move(&self, mv: Move) -> Result<(), String> {
+        let piece = self.piece_at(mv.from).ok_or_else(|| format!("No piece on {}.", mv.from))?;
+        if piece.side != self.side_to_move {
+            return Err(format!("The piece on {} is not the side to move's.", mv.from));
+        }
+        if !self.is_mechanically_valid(mv.from, mv.to) {
+            return Err(format!("{} does not follow the movement rules of the piece on {}.", mv, mv.from));
+        }
+        if self.leaves_king_in_check(mv) {
+            return Err(format!("{} leaves the king in check.", mv));
+        }
+        Ok(())
+    }
+
+    fn leaves_king_in_check(&self, mv: Move) -> bool {
+        let mut after = self.clone();
+        let piece = after.piece_at(mv.from);
+        after.set(mv.from, None);
+        after.set(mv.to, piece);
+        after.in_check(self.side_to_move)
+    }
+
+    /// Whether `side`'s king is attacked (`isInCheck`). Dark pieces never give check,
+    /// and facing kings on an open file count as check. A side without a king is not in check.
+    pub fn in_check(&self, side: Side) -> bool {
+        let Some(king) = self.king_square(side) else {
+            return false;
+        };
+        self.pieces().any(|(from, piece)| {
+            if piece.side == side || piece.is_dark() {
+                return false;
+            }
+            if piece.role == Some(Role::King) {
+                from.col == king.col && self.count_between(from, king) == 0
+            } else {
+                self.is_mechanically_valid(from, king)
+            }
+        })
+    }
+
+    /// Play a legal move. A dark piece that moves is revealed as `reveal`; a dark piece that is
+    /// captured turns out to be `captured_identity`. Both are taken out of the hidden pool and a
+    /// captured identity is added to the captured pool. Without a captured identity (free flip
+    /// mode) the pools are left as they are.
+    pub fn play(&mut self, mv: Move, reveal: Option<Role>, captured_identity: Option<Role>) -> Result<MoveOutcome, String> {
+        if !self.is_legal(mv) {
+            return Err(format!("Illegal move {} in position {}.", mv, self.to_fen()));
+        }
+        let mut piece = self.piece_at(mv.from).expect("legal move has a piece");
+        let captured = self.piece_at(mv.to);
+
+        let revealed = if piece.is_dark() {
+            let role = reveal.ok_or_else(|| format!("Move {} reveals a dark piece but no identity was given.", mv))?;
+            self.hidden.take(piece.side, role)?;
+            piece.role = Some(role);
+            Some(role)
+        } else {
+            None
+        };
+        let captured_identity = match (captured, captured_identity) {
+            (Some(target), Some(role)) if target.is_dark() => {
+                self.hidden.take(target.side, role)?;
+                self.captured.add(target.side, role, 1);
+                Some(role)
+            }
+            _ => None,
+        };
+
+        self.set(mv.from, None);
+        self.set(mv.to, Some(piece));
+        if captured.is_some() {
+            self.halfmove_clock = 0;
+        } else {
+            self.halfmove_clock += 1;
+        }
+        if self.side_to_move == Side::Black {
+            self.fullmove_number += 1;
+        }
+        self.side_to_move = self.side_to_move.opponent();
+
+        Ok(MoveOutcome {
+            revealed,
+            captured,
+            captured_identity,
+        })
+    }
+}
+
+/// Check a move, in plain or extended UCI, against a position given as FEN.
+pub fn validate_move(fen: &str, uci: &str) -> Result<Move, String> {
+    let board = Board::from_fen(fen)?;
+    let mv = Move::from_uci(uci).ok_or_else(|| format!("'{}' is not a UCI move.", uci))?;
+    board.check_move(mv)?;
+    Ok(mv)
+}
+
+/// Side to move, check status and legal moves of a position, as the frontend and CLI show them.
+#[derive(Clone, Debug, Serialize)]
+pub struct PositionSummary {
+    pub side_to_move: Side,
+    pub in_check: bool,
+    pub legal_moves: Vec<String>,
+}
+
+impl PositionSummary {
+    pub fn of(board: &Board) -> Self {
+        PositionSummary {
+            side_to_move: board.side_to_move,
+            in_check: board.in_check(board.side_to_move),
+            legal_moves: board.legal_moves().iter().map(Move::to_string).collect(),
+        }
+    }
+}
+
+/// Extended UCI notation of a played move: the revealed identity in the mover's case,
+/// then the identity of a captured dark piece in the opponent's case.
+pub fn extended_uci(mv: Move, mover: Side, outcome: &MoveOutcome) -> String {
+    let mut uci = mv.to_string();
+    if let Some(role) = outcome.revealed {
+        uci.push(role.fen_char(mover));
+    }
+    if let Some(role) = outcome.captured_identity {
+        uci.push(role.fen_char(mover.opponent()));
+    }
+    uci
+}
+
+/// Split an extended UCI move into the move, the revealed identity and the identity of a
+/// captured dark piece (`parseUciExtended`). A single extra letter is a reveal when it has
+/// the mover's case and a captured identity otherwise.
+pub fn parse_extended_uci(uci: &str, mover: Side) -> Option<(Move, Option<Role>, Option<Role>)> {
+    let mv = Move::from_uci(uci)?;
+    let extension = uci
+        .get(4..)?
+        .chars()
+        .map(Role::from_fen_char)
+        .collect::<Option<Vec<_>>>()?;
+    match extension.as_slice() {
+        [] => Some((mv, None, None)),
+        [(role, side)] if *side == mover => Some((mv, Some(*role), None)),
+        [(role, _)] => Some((mv, None, Some(*role))),
+        [(reveal, _), (captured, _)] => Some((mv, Some(*reveal), Some(*captured))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board(fen: &str) -> Board {
+        Board::from_fen(fen).unwrap()
+    }
+
+    fn mv(uci: &str) -> Move {
+        Move::from_uci(uci).unwrap()
+    }
+
+    fn legal(fen: &str, uci: &str) -> bool {
+        board(fen).is_legal(mv(uci))
+    }
+
+    #[test]
+    fn kings_may_not_face_each_other() {
+        assert!(board("4k4/9/9/9/9/9/9/9/9/4K4 w - - 0 1").in_check(Side::Red));
+        assert!(board("4k4/9/9/9/9/9/9/9/9/4K4 w - - 0 1").in_check(Side::Black));
+        assert!(!board("4k4/9/9/9/4p4/9/9/9/9/4K4 w - - 0 1").in_check(Side::Red));
+        // Stepping onto the open file, or moving the only piece between the kings away
+        assert!(!legal("3k5/9/9/9/9/9/9/9/9/4K4 w - - 0 1", "e0d0"));
+        assert!(legal("3k5/9/9/9/9/9/9/9/9/4K4 w - - 0 1", "e0e1"));
+        assert!(!legal("4k4/9/9/9/4P4/9/9/9/9/4K4 w - - 0 1", "e5d5"));
+        assert!(legal("4k4/9/9/9/4P4/9/9/9/9/4K4 w - - 0 1", "e5e6"));
+    }
+
+    #[test]
+    fn dark_pieces_move_by_their_start_square() {
+        // Advisor on d0: a dark one may not step towards the edge, a revealed one may
+        assert!(legal(START_FEN, "d0e1"));
+        assert!(!legal(START_FEN, "d0c1"));
+        assert!(!legal(START_FEN, "f0g1"));
+        assert!(legal("3k5/9/9/9/9/9/9/9/9/3AK4 w - - 0 1", "d0c1"));
+        // Elephant on c0, horse on b0, chariot on a0
+        assert!(legal(START_FEN, "c0e2"));
+        assert!(legal(START_FEN, "c0a2"));
+        assert!(!legal(START_FEN, "c0c1"));
+        assert!(legal(START_FEN, "b0c2"));
+        assert!(legal(START_FEN, "a0a2"));
+        assert!(!legal(START_FEN, "a0a4"));
+        assert!(legal(START_FEN, "a3a4"));
+        assert!(!legal(START_FEN, "a3b3"));
+    }
+
+    #[test]
+    fn elephant_eye_and_horse_leg_block() {
+        let fen = "3k5/9/9/9/9/9/9/9/3P5/2B1K4 w - - 0 1";
+        assert!(!legal(fen, "c0e2"));
+        assert!(legal(fen, "c0a2"));
+        // The horse's leg on c0 is taken at the start; the one on b1 is free
+        assert!(!legal(START_FEN, "b0d1"));
+        assert!(legal(START_FEN, "b0a2"));
+        let fen = "3k5/9/9/9/9/9/9/1P7/1N7/4K4 w - - 0 1";
+        assert!(!legal(fen, "b1c3"));
+        assert!(!legal(fen, "b1a3"));
+        assert!(legal(fen, "b1d2"));
+    }
+
+    #[test]
+    fn cannons_need_exactly_one_screen_to_capture() {
+        // The dark cannon on b2 jumps the black cannon on b7 to capture on b9
+        assert!(legal(START_FEN, "b2b9"));
+        assert!(legal(START_FEN, "b2b6"));
+        assert!(!legal(START_FEN, "b2b7"));
+        assert!(!legal(START_FEN, "b2b8"));
+        assert!(!legal(START_FEN, "b2b0"));
+    }
+
+    #[test]
+    fn pawns_move_sideways_after_the_river() {
+        let fen = "3k5/9/9/9/9/9/2P6/9/9/4K4 w - - 0 1";
+        assert!(legal(fen, "c3c4"));
+        assert!(!legal(fen, "c3b3"));
+        assert!(!legal(fen, "c3c2"));
+        let fen = "3k5/9/9/9/2P6/9/9/9/9/4K4 w - - 0 1";
+        assert!(legal(fen, "c5b5"));
+        assert!(legal(fen, "c5d5"));
+        assert!(legal(fen, "c5c6"));
+        assert!(!legal(fen, "c5c4"));
+        let fen = "3k5/9/9/9/9/2p6/9/9/9/4K4 b - - 0 1";
+        assert!(legal(fen, "c4b4"));
+        assert!(legal(fen, "c4c3"));
+        assert!(!legal(fen, "c4c5"));
+    }
+
+    #[test]
+    fn detects_check_and_discovered_check() {
+        assert!(board("3k5/9/9/9/9/9/9/9/9/3RK4 b - - 0 1").in_check(Side::Black));
+        let mut position = board("3k5/9/9/3N5/9/9/9/9/9/3RK4 w - - 0 1");
+        assert!(!position.in_check(Side::Black));
+        position.play(mv("d6e8"), None, None).unwrap();
+        assert!(position.in_check(Side::Black));
+        // Dark pieces never give check
+        assert!(!board("9/9/9/9/9/9/9/9/k8/X3K4 w A1 - 0 1").in_check(Side::Black));
+    }
+
+    #[test]
+    fn validate_move_explains_rejections() {
+        assert!(validate_move("9/9 w", "a0a1").unwrap_err().contains("the board has 2 rows"));
+        assert_eq!(validate_move(START_FEN, "e0").unwrap_err(), "'e0' is not a UCI move.");
+        assert_eq!(validate_move(START_FEN, "e5e6").unwrap_err(), "No piece on e5.");
+        assert_eq!(validate_move(START_FEN, "e9e8").unwrap_err(), "The piece on e9 is not the side to move's.");
+        assert_eq!(
+            validate_move(START_FEN, "e0e2").unwrap_err(),
+            "e0e2 does not follow the movement rules of the piece on e0."
+        );
+        assert_eq!(
+            validate_move("3k5/9/9/9/9/9/9/9/9/4K4 w - - 0 1", "e0d0").unwrap_err(),
+            "e0d0 leaves the king in check."
+        );
+        assert_eq!(validate_move(START_FEN, "h2e2C"), Ok(mv("h2e2")));
+    }
+
+    #[test]
+    fn parses_extended_uci() {
+        assert_eq!(parse_extended_uci("h2e2", Side::Red), Some((mv("h2e2"), None, None)));
+        assert_eq!(parse_extended_uci("h2e2C", Side::Red), Some((mv("h2e2"), Some(Role::Cannon), None)));
+        assert_eq!(parse_extended_uci("b2b9r", Side::Red), Some((mv("b2b9"), None, Some(Role::Chariot))));
+        assert_eq!(parse_extended_uci("b2b9Cn", Side::Red), Some((mv("b2b9"), Some(Role::Cannon), Some(Role::Horse))));
+        assert_eq!(parse_extended_uci("h7e7c", Side::Black), Some((mv("h7e7"), Some(Role::Cannon), None)));
+        assert_eq!(parse_extended_uci("h2e2Q", Side::Red), None);
+        assert_eq!(parse_extended_uci("h2e2CNR", Side::Red), None);
+        assert_eq!(parse_extended_uci("h2", Side::Red), None);
+        assert_eq!(parse_extended_uci("j2e2", Side::Red), None);
+    }
+
+    #[test]
+    fn play_rejects_missing_or_unavailable_identities() {
+        let mut start = board(START_FEN);
+        assert_eq!(
+            start.play(mv("h2e2"), None, None).unwrap_err(),
+            "Move h2e2 reveals a dark piece but no identity was given."
+        );
+        assert!(start.play(mv("e0e2"), None, None).unwrap_err().starts_with("Illegal move e0e2 in position"));
+        let mut position = board("xxxxkxxxx/9/1x5x1/x1x1x1x1x/9/9/X1X1X1X1X/1X5X1/9/XXXXKXXXX w A2B2N2R2P5a2b2n2r2c2p5 - 0 1");
+        assert_eq!(position.play(mv("h2e2"), Some(Role::Cannon), None).unwrap_err(), "No C left in the pool.");
+    }
+}
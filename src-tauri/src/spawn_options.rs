@@ -0,0 +1,130 @@
+// src-tauri/src/spawn_options.rs
+// Process-level settings of an engine launch: environment, working directory, scheduling
+// priority and CPU pinning, mostly for reproducible benchmarks.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+use std::collections::HashSet;
+use std::path::Path;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SpawnOptions {
+    /// Variables added to the inherited environment (e.g. `OMP_NUM_THREADS`).
+    pub env: BTreeMap<String, String>,
+    /// Working directory; the engine's own directory when unset.
+    pub cwd: Option<String>,
+    /// Nice level, from -20 (highest priority) to 19. Raising priority needs privileges.
+    pub nice: Option<i32>,
+    /// CPUs the engine may run on.
+    pub cpu_affinity: Option<Vec<usize>>,
+}
+
+impl SpawnOptions {
+    /// Working directory for an engine at `engine_path`.
+    pub fn working_dir(&self, engine_path: &str) -> Result<String, String> {
+        if let Some(cwd) = &self.cwd {
+            return Ok(cwd.clone());
+        }
+        Path::new(engine_path)
+            .parent()
+            .ok_or_else(|| "Failed to get engine directory".to_string())?
+            .to_str()
+            .ok_or_else(|| "Failed to convert engine directory to string".to_string())
+            .map(str::to_string)
+    }
+
+    /// Apply the nice level and CPU set to a process that was just started. On Linux both are
+    /// per thread, and the engine may already have started threads (its search pool) by now,
+    /// so every thread in `/proc/<pid>/task` is set, rereading the list until it shows no thread
+    /// left unset. Threads created later inherit the settings of the thread creating them.
+    /// The shell plugin owns the spawn, so there is no `pre_exec` hook to set them before exec.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn apply_to_process(&self, pid: u32) -> Result<(), String> {
+        if self.nice.is_none() && self.cpu_affinity.is_none() {
+            return Ok(());
+        }
+        if let Some(nice) = self.nice {
+            if !(-20..=19).contains(&nice) {
+                return Err(format!("Nice level {} is outside -20..19.", nice));
+            }
+        }
+        let set = match &self.cpu_affinity {
+            Some(cpus) => Some(cpu_set(cpus)?),
+            None => None,
+        };
+        let mut done = HashSet::new();
+        loop {
+            let pending: Vec<u32> = threads(pid).into_iter().filter(|tid| !done.contains(tid)).collect();
+            if pending.is_empty() {
+                return Ok(());
+            }
+            for tid in pending {
+                if let Err((what, e)) = self.apply_to_thread(tid, set.as_ref()) {
+                    // A thread that ended in the meantime needs nothing
+                    if tid == pid || e.raw_os_error() != Some(libc::ESRCH) {
+                        return Err(format!("Failed to set {}: {}", what, e));
+                    }
+                }
+                done.insert(tid);
+            }
+        }
+    }
+
+    /// Set the nice level and CPU set of one thread; on failure, says which of the two failed.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn apply_to_thread(&self, tid: u32, cpu_set: Option<&libc::cpu_set_t>) -> Result<(), (String, std::io::Error)> {
+        if let Some(nice) = self.nice {
+            // SAFETY: plain syscall on a thread of a process we own; no memory is shared with it
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, nice) } != 0 {
+                return Err((format!("nice level {}", nice), std::io::Error::last_os_error()));
+            }
+        }
+        if let Some(set) = cpu_set {
+            // SAFETY: `set` is a valid cpu_set_t of the size passed
+            if unsafe { libc::sched_setaffinity(tid as libc::pid_t, std::mem::size_of::<libc::cpu_set_t>(), set) } != 0 {
+                return Err((format!("CPU affinity {:?}", self.cpu_affinity.as_deref().unwrap_or_default()), std::io::Error::last_os_error()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Priority and CPU pinning are only supported on Linux; elsewhere asking for them is an error
+    /// rather than a silently unpinned benchmark.
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    pub fn apply_to_process(&self, _pid: u32) -> Result<(), String> {
+        if self.nice.is_some() || self.cpu_affinity.is_some() {
+            return Err("Nice level and CPU affinity are only supported on Linux.".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// CPU set holding `cpus`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn cpu_set(cpus: &[usize]) -> Result<libc::cpu_set_t, String> {
+    if cpus.is_empty() {
+        return Err("The CPU affinity list is empty.".to_string());
+    }
+    // SAFETY: cpu_set_t is a plain bit set, valid when zeroed
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &cpu in cpus {
+        if cpu >= libc::CPU_SETSIZE as usize {
+            return Err(format!("CPU {} is out of range.", cpu));
+        }
+        // SAFETY: `cpu` was checked against the size of the set
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+    Ok(set)
+}
+
+/// Thread ids of a process, or just its pid when `/proc` cannot be read.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn threads(pid: u32) -> Vec<u32> {
+    match std::fs::read_dir(format!("/proc/{}/task", pid)) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+            .collect(),
+        Err(_) => vec![pid],
+    }
+}
@@ -0,0 +1,158 @@
+// src-tauri/src/sprt.rs
+// Sequential probability ratio test for engine matches, using the generalized SPRT on
+// game-pair (pentanomial) results with logistic Elo, as in fishtest and fastchess.
+use serde::{Deserialize, Serialize};
+
+/// Count given to empty pentanomial bins so the variance is not zero early in a match.
+const EMPTY_BIN: f64 = 1e-3;
+
+/// Hypotheses and error rates of a test: H0 is "the gain is `elo0`", H1 "the gain is `elo1`".
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct SprtConfig {
+    pub elo0: f64,
+    pub elo1: f64,
+    /// Chance of accepting H1 when H0 holds.
+    pub alpha: f64,
+    /// Chance of accepting H0 when H1 holds.
+    pub beta: f64,
+}
+
+impl Default for SprtConfig {
+    fn default() -> Self {
+        SprtConfig {
+            elo0: 0.0,
+            elo1: 5.0,
+            alpha: 0.05,
+            beta: 0.05,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SprtDecision {
+    /// The lower bound was crossed: the gain is closer to `elo0` than to `elo1`.
+    AcceptH0,
+    /// The upper bound was crossed: the gain is closer to `elo1` than to `elo0`.
+    AcceptH1,
+}
+
+/// Where a test stands after some number of pairs.
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct SprtStatus {
+    pub llr: f64,
+    pub lower_bound: f64,
+    pub upper_bound: f64,
+    pub decision: Option<SprtDecision>,
+}
+
+impl SprtConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.alpha > 0.0 && self.alpha < 1.0 && self.beta > 0.0 && self.beta < 1.0) {
+            return Err("SPRT alpha and beta must be between 0 and 1.".to_string());
+        }
+        if self.elo0 >= self.elo1 {
+            return Err(format!("SPRT elo0 ({}) must be below elo1 ({}).", self.elo0, self.elo1));
+        }
+        Ok(())
+    }
+
+    /// LLR bounds: below the first H0 is accepted, above the second H1.
+    pub fn bounds(&self) -> (f64, f64) {
+        ((self.beta / (1.0 - self.alpha)).ln(), ((1.0 - self.beta) / self.alpha).ln())
+    }
+
+    /// Evaluate pair results, counted by the pair score of 0, 0.5, 1, 1.5 and 2 points.
+    pub fn status(&self, ptnml: &[u32; 5]) -> SprtStatus {
+        let (lower_bound, upper_bound) = self.bounds();
+        let llr = self.llr(ptnml);
+        let decision = if llr <= lower_bound {
+            Some(SprtDecision::AcceptH0)
+        } else if llr >= upper_bound {
+            Some(SprtDecision::AcceptH1)
+        } else {
+            None
+        };
+        SprtStatus {
+            llr,
+            lower_bound,
+            upper_bound,
+            decision,
+        }
+    }
+
+    /// Log-likelihood ratio of H1 against H0, by the normal approximation of the GSPRT.
+    pub fn llr(&self, ptnml: &[u32; 5]) -> f64 {
+        let pairs: u32 = ptnml.iter().sum();
+        if pairs == 0 {
+            return 0.0;
+        }
+        let counts = ptnml.map(|n| if n == 0 { EMPTY_BIN } else { n as f64 });
+        let total: f64 = counts.iter().sum();
+
+        // Pair scores as a fraction of the two games' points
+        let score = |bin: usize| bin as f64 / 4.0;
+        let mean = (0..5).map(|i| counts[i] * score(i)).sum::<f64>() / total;
+        let variance = (0..5).map(|i| counts[i] * (score(i) - mean).powi(2)).sum::<f64>() / total;
+        if variance <= 0.0 {
+            return 0.0;
+        }
+
+        let s0 = expected_score(self.elo0);
+        let s1 = expected_score(self.elo1);
+        pairs as f64 * (s1 - s0) * (2.0 * mean - s0 - s1) / (2.0 * variance)
+    }
+}
+
+/// Expected score of a player `elo` points stronger than the opponent.
+fn expected_score(elo: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-elo / 400.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(elo0: f64, elo1: f64) -> SprtConfig {
+        SprtConfig {
+            elo0,
+            elo1,
+            ..SprtConfig::default()
+        }
+    }
+
+    fn assert_close(found: f64, expected: f64) {
+        assert!((found - expected).abs() < 1e-9, "{} != {}", found, expected);
+    }
+
+    #[test]
+    fn bounds_follow_error_rates() {
+        let (lower, upper) = SprtConfig::default().bounds();
+        assert_close(lower, -(19f64.ln()));
+        assert_close(upper, 19f64.ln());
+    }
+
+    // Expected LLRs worked out by hand from the pentanomial formula fastchess uses:
+    // N (s1 - s0) (2 mean - s0 - s1) / (2 variance), over pair scores 0, 1/4, ..., 1
+    #[test]
+    fn llr_matches_pentanomial_reference_values() {
+        assert_close(config(0.0, 5.0).llr(&[10, 100, 300, 120, 12]), 0.8233313340264142);
+        assert_close(config(0.0, 2.0).llr(&[20, 150, 400, 140, 15]), -0.4698352387752008);
+        assert_close(config(-1.75, 0.25).llr(&[3, 40, 120, 60, 8]), 0.5883937715304067);
+        // Empty bins are counted as 1e-3 pairs
+        assert_close(config(0.0, 5.0).llr(&[0, 5, 20, 5, 0]), -0.03724651228775514);
+    }
+
+    #[test]
+    fn no_pairs_gives_zero() {
+        assert_eq!(config(0.0, 5.0).llr(&[0; 5]), 0.0);
+        assert_eq!(config(0.0, 5.0).status(&[0; 5]).decision, None);
+    }
+
+    #[test]
+    fn decisive_results_end_the_test() {
+        let test = config(0.0, 5.0);
+        assert_eq!(test.status(&[0, 10, 100, 300, 100]).decision, Some(SprtDecision::AcceptH1));
+        assert_eq!(test.status(&[100, 300, 100, 10, 0]).decision, Some(SprtDecision::AcceptH0));
+    }
+}
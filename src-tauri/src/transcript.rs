@@ -0,0 +1,208 @@
+// src-tauri/src/transcript.rs
+// Timestamped record of everything exchanged with one engine session, written to a
+// log file that is rotated by size, so a misbehaving engine can be replayed by hand.
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Transcript settings of a session.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TranscriptConfig {
+    /// Directory holding `<session id>.log` and its rotated predecessors.
+    pub dir: String,
+    /// Size at which the log is rotated.
+    pub max_bytes: u64,
+    /// Rotated logs kept besides the current one (`.log.1` is the newest).
+    pub max_files: u32,
+}
+
+/// Marks the kind of each transcript line.
+#[derive(Clone, Copy)]
+pub enum Direction {
+    /// Command written to the engine.
+    ToEngine,
+    Stdout,
+    Stderr,
+    /// Process lifecycle: spawn, restart, exit.
+    Lifecycle,
+}
+
+impl Direction {
+    fn marker(self) -> &'static str {
+        match self {
+            Direction::ToEngine => ">",
+            Direction::Stdout => "<",
+            Direction::Stderr => "!",
+            Direction::Lifecycle => "#",
+        }
+    }
+}
+
+struct TranscriptFile {
+    file: File,
+    written: u64,
+}
+
+/// An open transcript, shared by a session and the listener of its process.
+pub struct Transcript {
+    path: PathBuf,
+    config: TranscriptConfig,
+    file: Mutex<Option<TranscriptFile>>,
+}
+
+impl Transcript {
+    pub fn open(session_id: &str, config: TranscriptConfig) -> Result<Self, String> {
+        fs::create_dir_all(&config.dir).map_err(|e| format!("Failed to create log directory {}: {}", config.dir, e))?;
+        // Session ids come from the frontend; keep them from escaping the log directory
+        let name: String = session_id
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        let path = Path::new(&config.dir).join(format!("{}.log", name));
+        let file = open_append(&path)?;
+        Ok(Transcript {
+            path,
+            config,
+            file: Mutex::new(Some(file)),
+        })
+    }
+
+    /// Append one line. Failures are ignored: a full disk must not take the engine down.
+    pub fn log(&self, direction: Direction, line: &str) {
+        let mut guard = self.file.lock().unwrap();
+        if guard.as_ref().is_some_and(|f| f.written >= self.config.max_bytes) {
+            *guard = None;
+            self.rotate();
+        }
+        if guard.is_none() {
+            *guard = open_append(&self.path).ok();
+        }
+        if let Some(f) = guard.as_mut() {
+            let entry = format!(
+                "{} {} {}\n",
+                Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                direction.marker(),
+                line
+            );
+            if f.file.write_all(entry.as_bytes()).is_ok() {
+                f.written += entry.len() as u64;
+            }
+        }
+    }
+
+    pub fn log_all(&self, direction: Direction, lines: &[String]) {
+        for line in lines {
+            self.log(direction, line);
+        }
+    }
+
+    /// Shift `x.log` to `x.log.1`, `x.log.1` to `x.log.2` and so on, dropping the oldest.
+    fn rotate(&self) {
+        let rotated = |n: u32| PathBuf::from(format!("{}.{}", self.path.display(), n));
+        if self.config.max_files == 0 {
+            let _ = fs::remove_file(&self.path);
+            return;
+        }
+        let _ = fs::remove_file(rotated(self.config.max_files));
+        for n in (1..self.config.max_files).rev() {
+            let _ = fs::rename(rotated(n), rotated(n + 1));
+        }
+        let _ = fs::rename(&self.path, rotated(1));
+    }
+}
+
+fn open_append(path: &Path) -> Result<TranscriptFile, String> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let written = file.metadata().map_or(0, |m| m.len());
+    Ok(TranscriptFile { file, written })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory under the system temp directory, removed when dropped.
+    struct TempRoot(PathBuf);
+
+    impl TempRoot {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("jieqibox-transcript-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&path);
+            TempRoot(path)
+        }
+
+        fn config(&self, max_bytes: u64, max_files: u32) -> TranscriptConfig {
+            TranscriptConfig {
+                dir: self.0.display().to_string(),
+                max_bytes,
+                max_files,
+            }
+        }
+
+        /// Logged lines of a file, without their timestamps; empty when it does not exist.
+        fn lines(&self, file: &str) -> Vec<String> {
+            let content = fs::read_to_string(self.0.join(file)).unwrap_or_default();
+            content.lines().map(|line| line.splitn(3, ' ').nth(2).unwrap().to_string()).collect()
+        }
+    }
+
+    impl Drop for TempRoot {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn rotates_at_max_bytes_and_drops_the_oldest() {
+        let root = TempRoot::new("rotate");
+        // Room for two lines per file
+        let transcript = Transcript::open("s1", root.config(60, 2)).unwrap();
+        let lines: Vec<String> = (1..=7).map(|n| format!("line {}", n)).collect();
+        transcript.log(Direction::Lifecycle, "spawn");
+        transcript.log_all(Direction::ToEngine, &lines);
+        assert_eq!(root.lines("s1.log"), ["> line 6", "> line 7"]);
+        assert_eq!(root.lines("s1.log.1"), ["> line 4", "> line 5"]);
+        assert_eq!(root.lines("s1.log.2"), ["> line 2", "> line 3"]);
+        assert!(!root.0.join("s1.log.3").exists());
+
+        // A reopened log counts what it already holds
+        drop(transcript);
+        let transcript = Transcript::open("s1", root.config(60, 2)).unwrap();
+        transcript.log(Direction::Stdout, "readyok");
+        transcript.log(Direction::Stderr, "warning");
+        assert_eq!(root.lines("s1.log"), ["< readyok", "! warning"]);
+        assert_eq!(root.lines("s1.log.1"), ["> line 6", "> line 7"]);
+        assert_eq!(root.lines("s1.log.2"), ["> line 4", "> line 5"]);
+    }
+
+    #[test]
+    fn keeps_no_rotated_logs_when_max_files_is_zero() {
+        let root = TempRoot::new("no-rotated");
+        let transcript = Transcript::open("s1", root.config(1, 0)).unwrap();
+        transcript.log_all(Direction::Stdout, &["a".to_string(), "b".to_string()]);
+        assert_eq!(root.lines("s1.log"), ["< b"]);
+        assert_eq!(fs::read_dir(&root.0).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn session_ids_stay_inside_the_log_directory() {
+        let root = TempRoot::new("escape");
+        let dir = root.0.join("logs");
+        let config = TranscriptConfig {
+            dir: dir.display().to_string(),
+            ..root.config(1024, 1)
+        };
+        let transcript = Transcript::open("../evil/a b", config).unwrap();
+        transcript.log(Direction::Lifecycle, "spawn");
+        assert_eq!(root.lines("logs/___evil_a_b.log"), ["# spawn"]);
+        let names: Vec<_> = fs::read_dir(&root.0).unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(names, ["logs"]);
+    }
+}
@@ -0,0 +1,362 @@
+// src-tauri/src/ucci.rs
+// UCCI support. Engines speaking UCCI are driven with the same UCI commands as any other
+// engine: `UcciAdapter` rewrites each command into UCCI on the way in and each reply into
+// UCI on the way out, so sessions, the watchdog and the frontend never see the difference.
+use crate::rules::{self, Board, Move, Side};
+use serde::{Deserialize, Serialize};
+
+/// Protocol dialect an engine speaks, chosen per engine.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EngineDialect {
+    /// UCI or JAI; commands and output pass through unchanged.
+    #[default]
+    Native,
+    Ucci,
+}
+
+impl EngineDialect {
+    pub fn adapter(self) -> Option<UcciAdapter> {
+        match self {
+            EngineDialect::Native => None,
+            EngineDialect::Ucci => Some(UcciAdapter::default()),
+        }
+    }
+}
+
+/// Translation state of one UCCI engine process.
+#[derive(Default)]
+pub struct UcciAdapter {
+    /// The engine offers `usemillisec`; until it is switched on, UCCI times are in seconds.
+    supports_millisec: bool,
+    millisec: bool,
+    /// Position of the last `position` command, once its moves could be followed. `go` needs
+    /// it to tell own from opponent time and to turn `searchmoves` into `banmoves`.
+    board: Option<Board>,
+}
+
+impl UcciAdapter {
+    /// UCCI lines to send for one UCI command; empty for commands UCCI has no use for.
+    pub fn to_engine(&mut self, command: &str) -> Vec<String> {
+        let tokens: Vec<&str> = command.split_whitespace().collect();
+        match tokens.first().copied() {
+            Some("uci") => vec!["ucci".to_string()],
+            // UCCI engines start every game from the next `position`
+            Some("ucinewgame") => Vec::new(),
+            Some("setoption") => vec![translate_setoption(&tokens[1..])],
+            Some("position") => vec![self.translate_position(&tokens[1..])],
+            Some("go") => self.translate_go(&tokens[1..]),
+            _ => vec![command.to_string()],
+        }
+    }
+
+    /// UCI line for one line of UCCI output, or `None` for lines the frontend must not see.
+    pub fn from_engine(&mut self, line: &str) -> Option<String> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.first().copied() {
+            Some("ucciok") => Some("uciok".to_string()),
+            Some("bye") => None,
+            Some("nobestmove") => Some("bestmove (none)".to_string()),
+            // `option <name> type ...`: UCCI has no `name` keyword
+            Some("option") if tokens.len() > 1 && tokens[1] != "name" => {
+                if tokens[1].eq_ignore_ascii_case("usemillisec") {
+                    // Managed here; the frontend keeps sending milliseconds either way
+                    self.supports_millisec = true;
+                    return None;
+                }
+                Some(format!("option name {}", tokens[1..].join(" ")))
+            }
+            // Drop the `draw`/`resign` flags UCCI may append
+            Some("bestmove") => {
+                let mut out = tokens.iter().take(2).copied().collect::<Vec<_>>();
+                if let Some(i) = tokens.iter().position(|t| *t == "ponder") {
+                    out.extend(tokens.get(i..i + 2).unwrap_or_default());
+                }
+                Some(out.join(" "))
+            }
+            // UCCI scores are plain numbers: `score 35` for UCI's `score cp 35`
+            Some("info") => {
+                let mut out = Vec::with_capacity(tokens.len() + 1);
+                for (i, token) in tokens.iter().enumerate() {
+                    out.push(*token);
+                    if *token == "score" && tokens.get(i + 1).is_some_and(|t| t.parse::<i32>().is_ok()) {
+                        out.push("cp");
+                    }
+                    if *token == "pv" || *token == "string" {
+                        out.extend(&tokens[i + 1..]);
+                        break;
+                    }
+                }
+                Some(out.join(" "))
+            }
+            _ => Some(line.to_string()),
+        }
+    }
+
+    /// `position (startpos | fen <fen>) [moves ...]`, where `startpos` is the Jieqi start as
+    /// everywhere else in the app. Reveals and captures of dark pieces cannot be written as
+    /// ICCS moves, so the moves up to the last of them are played here and folded into the
+    /// FEN; only the plain moves after it are passed on. The engine always gets a FEN, as
+    /// UCCI's `startpos` is the revealed Xiangqi start.
+    fn translate_position(&mut self, tokens: &[&str]) -> String {
+        let moves_at = tokens.iter().position(|t| *t == "moves").unwrap_or(tokens.len());
+        let moves = tokens.get(moves_at + 1..).unwrap_or_default();
+        let fen = match tokens.first().copied() {
+            Some("fen") => tokens[1..moves_at].join(" "),
+            _ => rules::START_FEN.to_string(),
+        };
+
+        self.board = None;
+        let Ok(start) = Board::from_fen(&fen) else {
+            return format!("position fen {}{}", fen, moves_suffix(moves.iter().map(|m| plain_move(m))));
+        };
+        let mut anchor = start.clone();
+        let mut board = start.clone();
+        let mut quiet: Vec<Move> = Vec::new();
+        for uci in moves {
+            let mover = board.side_to_move;
+            let played = rules::parse_extended_uci(uci, mover).and_then(|(mv, reveal, captured)| {
+                let dark_capture = board.piece_at(mv.to).is_some_and(|p| p.is_dark());
+                let outcome = board.play(mv, reveal, captured).ok()?;
+                Some((mv, outcome.revealed.is_some() || dark_capture))
+            });
+            match played {
+                Some((_, true)) => {
+                    anchor = board.clone();
+                    quiet.clear();
+                }
+                Some((mv, false)) => quiet.push(mv),
+                // Moves that cannot be followed go out as they are, without their suffixes
+                None => {
+                    return format!(
+                        "position fen {}{}",
+                        ucci_fen(&start),
+                        moves_suffix(moves.iter().map(|m| plain_move(m)))
+                    )
+                }
+            }
+        }
+        self.board = Some(board);
+
+        format!("position fen {}{}", ucci_fen(&anchor), moves_suffix(quiet.iter().map(Move::to_string)))
+    }
+
+    /// `go` with UCCI's single search mode: clock times (own side first) or a move time,
+    /// otherwise a depth, otherwise a node count, otherwise an endless search.
+    fn translate_go(&mut self, tokens: &[&str]) -> Vec<String> {
+        let mut lines = Vec::new();
+        let value = |key: &str| -> Option<u64> {
+            let i = tokens.iter().position(|t| *t == key)?;
+            tokens.get(i + 1)?.parse().ok()
+        };
+        let search_moves: Vec<&str> = tokens
+            .iter()
+            .position(|t| *t == "searchmoves")
+            .map_or_else(Vec::new, |i| tokens[i + 1..].to_vec());
+
+        if self.supports_millisec && !self.millisec {
+            lines.push("setoption usemillisec true".to_string());
+            self.millisec = true;
+        }
+        if let (Some(board), false) = (&self.board, search_moves.is_empty()) {
+            let allowed: Vec<Move> = search_moves.iter().filter_map(|m| Move::from_uci(m)).collect();
+            let banned: Vec<String> = board
+                .legal_moves()
+                .into_iter()
+                .filter(|mv| !allowed.contains(mv))
+                .map(|mv| mv.to_string())
+                .collect();
+            if !banned.is_empty() {
+                lines.push(format!("banmoves {}", banned.join(" ")));
+            }
+        }
+
+        let mut go = vec!["go".to_string()];
+        if tokens.contains(&"ponder") {
+            go.push("ponder".to_string());
+        }
+        let (own, opp) = match self.board.as_ref().map_or(Side::Red, |b| b.side_to_move) {
+            Side::Red => ("w", "b"),
+            Side::Black => ("b", "w"),
+        };
+        let clock = |side: &str| value(&format!("{}time", side)).map(|t| (t, value(&format!("{}inc", side))));
+        if let Some((time, inc)) = clock(own) {
+            go.push(format!("time {}", self.time(time)));
+            match (value("movestogo"), inc) {
+                (Some(n), _) => go.push(format!("movestogo {}", n)),
+                (None, Some(inc)) if inc > 0 => go.push(format!("increment {}", self.time(inc))),
+                _ => {}
+            }
+            if let Some((time, inc)) = clock(opp) {
+                go.push(format!("opptime {}", self.time(time)));
+                match (value("movestogo"), inc) {
+                    (Some(n), _) => go.push(format!("oppmovestogo {}", n)),
+                    (None, Some(inc)) if inc > 0 => go.push(format!("oppincrement {}", self.time(inc))),
+                    _ => {}
+                }
+            }
+        } else if let Some(movetime) = value("movetime") {
+            go.push(format!("time {} movestogo 1", self.time(movetime)));
+        } else if let Some(depth) = value("depth") {
+            go.push(format!("depth {}", depth));
+        } else if let Some(nodes) = value("nodes") {
+            go.push(format!("nodes {}", nodes));
+        } else {
+            go.push("depth infinite".to_string());
+        }
+        lines.push(go.join(" "));
+        lines
+    }
+
+    /// A UCI time in milliseconds in the engine's unit; whole seconds round down but never to 0.
+    fn time(&self, ms: u64) -> u64 {
+        if self.millisec {
+            ms
+        } else {
+            (ms / 1000).max(1)
+        }
+    }
+}
+
+/// `setoption name <name> [value <value>]` becomes `setoption <name> [<value>]`.
+fn translate_setoption(tokens: &[&str]) -> String {
+    let value_at = tokens.iter().position(|t| *t == "value").unwrap_or(tokens.len());
+    let name = match tokens.first() {
+        Some(&"name") => &tokens[1..value_at],
+        _ => &tokens[..value_at],
+    };
+    let mut line = format!("setoption {}", name.join(" "));
+    if let Some(value) = tokens.get(value_at + 1..).filter(|v| !v.is_empty()) {
+        line.push(' ');
+        line.push_str(&value.join(" "));
+    }
+    line
+}
+
+/// FEN in UCCI's layout (`board side - - half full`). Jieqi positions with dark pieces keep
+/// their hidden and captured pools, which UCCI has no fields for but Jieqi engines need.
+fn ucci_fen(board: &Board) -> String {
+    if board.pieces().any(|(_, piece)| piece.is_dark()) {
+        return board.to_fen();
+    }
+    format!(
+        "{} {} - - {} {}",
+        board.board_fen(),
+        board.side_to_move.fen_char(),
+        board.halfmove_clock,
+        board.fullmove_number
+    )
+}
+
+/// The ICCS part of an extended UCI move (`h2e2C` → `h2e2`).
+fn plain_move(uci: &str) -> String {
+    uci.get(..4).unwrap_or(uci).to_string()
+}
+
+fn moves_suffix(moves: impl Iterator<Item = String>) -> String {
+    let moves: Vec<String> = moves.collect();
+    if moves.is_empty() {
+        String::new()
+    } else {
+        format!(" moves {}", moves.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fen::Fen;
+    use crate::rules::{Role, Square};
+
+    const XIANGQI_FEN: &str = "rnbakabnr/9/1c5c1/p1p1p1p1p/9/9/P1P1P1P1P/1C5C1/9/RNBAKABNR w - - 0 1";
+
+    #[test]
+    fn translates_setoption_and_simple_commands() {
+        let mut adapter = UcciAdapter::default();
+        assert_eq!(adapter.to_engine("uci"), ["ucci"]);
+        assert!(adapter.to_engine("ucinewgame").is_empty());
+        assert_eq!(adapter.to_engine("setoption name Hash value 64"), ["setoption Hash 64"]);
+        assert_eq!(adapter.to_engine("setoption name Book File value a b.obk"), ["setoption Book File a b.obk"]);
+        assert_eq!(adapter.to_engine("setoption name Clear Hash"), ["setoption Clear Hash"]);
+        assert_eq!(adapter.to_engine("isready"), ["isready"]);
+    }
+
+    #[test]
+    fn startpos_is_the_jieqi_start() {
+        let mut adapter = UcciAdapter::default();
+        let start = Board::from_fen(rules::START_FEN).unwrap();
+        assert_eq!(adapter.to_engine("position startpos"), [format!("position fen {}", start.to_fen())]);
+
+        let lines = adapter.to_engine("position startpos moves h2e2C");
+        let fen = lines[0].strip_prefix("position fen ").expect("a FEN position");
+        let fen = Fen::parse(fen).unwrap();
+        let e2 = fen.piece_at(Square::new(7, 4)).unwrap();
+        assert_eq!((e2.side, e2.role), (Side::Red, Some(Role::Cannon)));
+        assert_eq!(fen.hidden.get(Side::Red, Role::Cannon), 1);
+        assert_eq!(fen.side_to_move, Side::Black);
+    }
+
+    #[test]
+    fn passes_plain_moves_after_a_fen() {
+        let mut adapter = UcciAdapter::default();
+        assert_eq!(
+            adapter.to_engine(&format!("position fen {} moves h2e2 h9g7", XIANGQI_FEN)),
+            [format!("position fen {} moves h2e2 h9g7", XIANGQI_FEN)]
+        );
+    }
+
+    #[test]
+    fn translates_clock_times_in_seconds_and_milliseconds() {
+        let mut adapter = UcciAdapter::default();
+        adapter.to_engine(&format!("position fen {}", XIANGQI_FEN));
+        assert_eq!(
+            adapter.to_engine("go wtime 60000 btime 30500 winc 1000 binc 1000"),
+            ["go time 60 increment 1 opptime 30 oppincrement 1"]
+        );
+        assert_eq!(
+            adapter.to_engine("go wtime 60000 btime 60000 movestogo 20"),
+            ["go time 60 movestogo 20 opptime 60 oppmovestogo 20"]
+        );
+        assert_eq!(adapter.to_engine("go movetime 500"), ["go time 1 movestogo 1"]);
+        assert_eq!(adapter.to_engine("go"), ["go depth infinite"]);
+
+        assert_eq!(adapter.from_engine("option usemillisec type check default false"), None);
+        assert_eq!(
+            adapter.to_engine("go wtime 60000 btime 30500 winc 1000 binc 1000"),
+            ["setoption usemillisec true", "go time 60000 increment 1000 opptime 30500 oppincrement 1000"]
+        );
+        // Black to move: its own time comes first
+        adapter.to_engine(&format!("position fen {} moves h2e2", XIANGQI_FEN));
+        assert_eq!(adapter.to_engine("go wtime 60000 btime 30500"), ["go time 30500 opptime 60000"]);
+    }
+
+    #[test]
+    fn translates_engine_output() {
+        let mut adapter = UcciAdapter::default();
+        assert_eq!(adapter.from_engine("ucciok").as_deref(), Some("uciok"));
+        assert_eq!(adapter.from_engine("bye"), None);
+        assert_eq!(adapter.from_engine("option Hash type spin default 16").as_deref(), Some("option name Hash type spin default 16"));
+        assert_eq!(
+            adapter.from_engine("info depth 5 score -35 pv h2e2 h9g7").as_deref(),
+            Some("info depth 5 score cp -35 pv h2e2 h9g7")
+        );
+        assert_eq!(adapter.from_engine("info depth 5 score cp 12").as_deref(), Some("info depth 5 score cp 12"));
+        assert_eq!(adapter.from_engine("bestmove h2e2 ponder h9g7 draw").as_deref(), Some("bestmove h2e2 ponder h9g7"));
+        assert_eq!(adapter.from_engine("bestmove h2e2 resign").as_deref(), Some("bestmove h2e2"));
+        assert_eq!(adapter.from_engine("nobestmove").as_deref(), Some("bestmove (none)"));
+    }
+
+    #[test]
+    fn searchmoves_become_banmoves() {
+        let mut adapter = UcciAdapter::default();
+        adapter.to_engine(&format!("position fen {}", XIANGQI_FEN));
+        let lines = adapter.to_engine("go depth 5 searchmoves h2e2 b2e2");
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1], "go depth 5");
+        let banned: Vec<&str> = lines[0].strip_prefix("banmoves ").expect("a banmoves line").split(' ').collect();
+        let legal = Board::from_fen(XIANGQI_FEN).unwrap().legal_moves().len();
+        assert_eq!(banned.len(), legal - 2);
+        assert!(!banned.contains(&"h2e2") && !banned.contains(&"b2e2"));
+        assert!(banned.contains(&"h0g2"));
+    }
+}
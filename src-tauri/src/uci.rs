@@ -0,0 +1,295 @@
+// src-tauri/src/uci.rs
+use serde::{Deserialize, Serialize};
+
+/// `id name` / `id author` line. Only one of the two is set per message.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct UciId {
+    pub name: Option<String>,
+    pub author: Option<String>,
+}
+
+/// `option name ... type ...` line.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct UciOption {
+    pub name: String,
+    pub option_type: String,
+    pub default: Option<String>,
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+    pub vars: Vec<String>,
+}
+
+/// Bound attached to a score in an `info` line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoreBound {
+    Lower,
+    Upper,
+}
+
+/// `info ...` line. Fields the engine did not send are `None` (or empty for `pv`).
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct UciInfo {
+    pub depth: Option<u32>,
+    pub seldepth: Option<u32>,
+    pub multipv: Option<u32>,
+    pub score_cp: Option<i32>,
+    pub mate: Option<i32>,
+    pub bound: Option<ScoreBound>,
+    pub nodes: Option<u64>,
+    pub nps: Option<u64>,
+    pub hashfull: Option<u32>,
+    pub time: Option<u64>,
+    pub currmove: Option<String>,
+    pub pv: Vec<String>,
+    pub string: Option<String>,
+}
+
+/// `bestmove <move> [ponder <move>]` line.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct UciBestMove {
+    pub bestmove: String,
+    pub ponder: Option<String>,
+}
+
+/// A parsed engine-to-GUI UCI message.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum UciMessage {
+    Id(UciId),
+    UciOk,
+    ReadyOk,
+    Option(UciOption),
+    Info(UciInfo),
+    BestMove(UciBestMove),
+}
+
+impl UciMessage {
+    /// Name of the Tauri event this message is emitted as.
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            UciMessage::Id(_) => "engine-id",
+            UciMessage::UciOk => "engine-uciok",
+            UciMessage::ReadyOk => "engine-readyok",
+            UciMessage::Option(_) => "engine-option",
+            UciMessage::Info(_) => "engine-info",
+            UciMessage::BestMove(_) => "engine-bestmove",
+        }
+    }
+}
+
+/// Payload of the typed engine events: the parsed message plus the line it came from.
+#[derive(Clone, Serialize)]
+pub struct UciEvent<'a> {
+    pub session_id: &'a str,
+    pub raw: &'a str,
+    #[serde(flatten)]
+    pub message: &'a UciMessage,
+}
+
+/// Parse one line of engine output. Returns `None` for lines that are not
+/// UCI messages (banners, debug prints, JAI replies, ...).
+pub fn parse_line(line: &str) -> Option<UciMessage> {
+    let mut tokens = line.split_whitespace();
+    match tokens.next()? {
+        "uciok" => Some(UciMessage::UciOk),
+        "readyok" => Some(UciMessage::ReadyOk),
+        "id" => parse_id(tokens),
+        "option" => parse_option(&tokens.collect::<Vec<_>>()).map(UciMessage::Option),
+        "info" => Some(UciMessage::Info(parse_info(&tokens.collect::<Vec<_>>()))),
+        "bestmove" => {
+            let bestmove = tokens.next()?.to_string();
+            let ponder = match tokens.next() {
+                Some("ponder") => tokens.next().map(str::to_string),
+                _ => None,
+            };
+            Some(UciMessage::BestMove(UciBestMove { bestmove, ponder }))
+        }
+        _ => None,
+    }
+}
+
+fn parse_id<'a>(mut tokens: impl Iterator<Item = &'a str>) -> Option<UciMessage> {
+    let field = tokens.next()?;
+    let value = tokens.collect::<Vec<_>>().join(" ");
+    match field {
+        "name" => Some(UciMessage::Id(UciId { name: Some(value), author: None })),
+        "author" => Some(UciMessage::Id(UciId { name: None, author: Some(value) })),
+        _ => None,
+    }
+}
+
+/// Option names and values may contain spaces, so each field runs until the next keyword.
+fn parse_option(tokens: &[&str]) -> Option<UciOption> {
+    const KEYWORDS: [&str; 6] = ["name", "type", "default", "min", "max", "var"];
+
+    let mut option = UciOption::default();
+    let mut has_name = false;
+    let mut i = 0;
+    while i < tokens.len() {
+        let keyword = tokens[i];
+        let end = tokens[i + 1..]
+            .iter()
+            .position(|t| KEYWORDS.contains(t))
+            .map_or(tokens.len(), |p| i + 1 + p);
+        let value = tokens[i + 1..end].join(" ");
+        match keyword {
+            "name" => {
+                option.name = value;
+                has_name = true;
+            }
+            "type" => option.option_type = value,
+            "default" => option.default = Some(value),
+            "min" => option.min = value.parse().ok(),
+            "max" => option.max = value.parse().ok(),
+            "var" => option.vars.push(value),
+            _ => {}
+        }
+        i = end;
+    }
+    has_name.then_some(option)
+}
+
+fn parse_info(tokens: &[&str]) -> UciInfo {
+    let mut info = UciInfo::default();
+    let mut i = 0;
+    let next = |i: usize| tokens.get(i + 1).copied().unwrap_or("");
+    while i < tokens.len() {
+        match tokens[i] {
+            "depth" => info.depth = next(i).parse().ok(),
+            "seldepth" => info.seldepth = next(i).parse().ok(),
+            "multipv" => info.multipv = next(i).parse().ok(),
+            "nodes" => info.nodes = next(i).parse().ok(),
+            "nps" => info.nps = next(i).parse().ok(),
+            "hashfull" => info.hashfull = next(i).parse().ok(),
+            "time" => info.time = next(i).parse().ok(),
+            "currmove" => info.currmove = Some(next(i).to_string()),
+            "score" => {
+                match next(i) {
+                    "cp" => info.score_cp = tokens.get(i + 2).and_then(|v| v.parse().ok()),
+                    "mate" => info.mate = tokens.get(i + 2).and_then(|v| v.parse().ok()),
+                    _ => {}
+                }
+                i += 2;
+                match tokens.get(i + 1).copied() {
+                    Some("lowerbound") => info.bound = Some(ScoreBound::Lower),
+                    Some("upperbound") => info.bound = Some(ScoreBound::Upper),
+                    _ => {}
+                }
+                if info.bound.is_some() {
+                    i += 1;
+                }
+                i += 1;
+                continue;
+            }
+            // Both run to the end of the line
+            "pv" => {
+                info.pv = tokens[i + 1..].iter().map(|t| t.to_string()).collect();
+                break;
+            }
+            "string" => {
+                info.string = Some(tokens[i + 1..].join(" "));
+                break;
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_info_with_bound_and_pv() {
+        let Some(UciMessage::Info(info)) =
+            parse_line("info depth 12 seldepth 18 multipv 2 score cp -35 upperbound nodes 123456 nps 987654 hashfull 42 time 125 pv h2e2 h9g7 b0c2")
+        else {
+            panic!("not an info line");
+        };
+        assert_eq!(info.depth, Some(12));
+        assert_eq!(info.seldepth, Some(18));
+        assert_eq!(info.multipv, Some(2));
+        assert_eq!(info.score_cp, Some(-35));
+        assert_eq!(info.bound, Some(ScoreBound::Upper));
+        assert_eq!(info.nodes, Some(123456));
+        assert_eq!(info.nps, Some(987654));
+        assert_eq!(info.hashfull, Some(42));
+        assert_eq!(info.time, Some(125));
+        assert_eq!(info.pv, ["h2e2", "h9g7", "b0c2"]);
+        assert_eq!(info.mate, None);
+    }
+
+    #[test]
+    fn parses_info_mate_and_string() {
+        let Some(UciMessage::Info(info)) = parse_line("info depth 3 score mate -2 nodes 10") else {
+            panic!("not an info line");
+        };
+        assert_eq!(info.mate, Some(-2));
+        assert_eq!(info.score_cp, None);
+        assert_eq!(info.nodes, Some(10));
+        let Some(UciMessage::Info(info)) = parse_line("info string NNUE evaluation using pikafish.nnue") else {
+            panic!("not an info line");
+        };
+        assert_eq!(info.string.as_deref(), Some("NNUE evaluation using pikafish.nnue"));
+    }
+
+    #[test]
+    fn parses_bestmove() {
+        assert_eq!(
+            parse_line("bestmove h2e2 ponder h9g7"),
+            Some(UciMessage::BestMove(UciBestMove {
+                bestmove: "h2e2".to_string(),
+                ponder: Some("h9g7".to_string()),
+            }))
+        );
+        assert_eq!(
+            parse_line("bestmove a0a1"),
+            Some(UciMessage::BestMove(UciBestMove {
+                bestmove: "a0a1".to_string(),
+                ponder: None,
+            }))
+        );
+        assert_eq!(parse_line("bestmove"), None);
+    }
+
+    #[test]
+    fn parses_options_with_spaces() {
+        assert_eq!(
+            parse_line("option name Skill Level type spin default 20 min 0 max 20"),
+            Some(UciMessage::Option(UciOption {
+                name: "Skill Level".to_string(),
+                option_type: "spin".to_string(),
+                default: Some("20".to_string()),
+                min: Some(0),
+                max: Some(20),
+                vars: Vec::new(),
+            }))
+        );
+        let Some(UciMessage::Option(option)) = parse_line("option name Style type combo default Solid var Solid var Very Risky") else {
+            panic!("not an option line");
+        };
+        assert_eq!(option.vars, ["Solid", "Very Risky"]);
+        assert_eq!(parse_line("option type check default true"), None);
+    }
+
+    #[test]
+    fn parses_simple_messages_and_ignores_others() {
+        assert_eq!(parse_line("uciok"), Some(UciMessage::UciOk));
+        assert_eq!(parse_line("readyok"), Some(UciMessage::ReadyOk));
+        assert_eq!(
+            parse_line("id name Pikafish 2024"),
+            Some(UciMessage::Id(UciId {
+                name: Some("Pikafish 2024".to_string()),
+                author: None,
+            }))
+        );
+        assert_eq!(parse_line("Pikafish by the Pikafish developers"), None);
+        assert_eq!(parse_line(""), None);
+    }
+}
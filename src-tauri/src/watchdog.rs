@@ -0,0 +1,114 @@
+// src-tauri/src/watchdog.rs
+use crate::engine::{self, EngineSession, EngineSessions};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// How often the watchdog looks at a session.
+const TICK: Duration = Duration::from_millis(250);
+
+/// Restarts in a row (without a health check being answered in between) before giving up.
+const MAX_RESTARTS: u32 = 3;
+
+/// Health check settings of a session.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct WatchdogConfig {
+    /// Idle time after which `isready` is sent. 0, the default, turns the watchdog off;
+    /// engines opt in to it one by one.
+    pub interval_ms: u64,
+    /// How long any `isready` may stay unanswered before the engine is restarted.
+    pub timeout_ms: u64,
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        WatchdogConfig {
+            interval_ms: 0,
+            timeout_ms: 20_000,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchdogAction {
+    Restarted,
+    GaveUp,
+}
+
+/// Payload of the `engine-watchdog` event.
+#[derive(Clone, Serialize)]
+pub struct WatchdogEvent {
+    pub session_id: String,
+    pub action: WatchdogAction,
+    pub reason: String,
+    pub attempt: u32,
+}
+
+/// Supervise one engine process until it exits or is replaced.
+/// While the engine is idle it is sent `isready` every `interval_ms`; when an `isready`
+/// stays unanswered for `timeout_ms` the engine is killed and started again.
+pub async fn watch_engine(app: AppHandle, session_id: String, pid: u32) {
+    loop {
+        tokio::time::sleep(TICK).await;
+        let sessions = app.state::<EngineSessions>();
+        let config = sessions.watchdog(&session_id);
+        match sessions.with_current(&session_id, pid, |session| check(session, config)) {
+            // The process exited, was killed or was replaced
+            None => return,
+            Some(None) => {}
+            Some(Some(reason)) => {
+                restart(&app, &session_id, pid, reason);
+                return;
+            }
+        }
+    }
+}
+
+/// Send a health check when one is due. Returns why the engine counts as hung, if it does.
+fn check(session: &mut EngineSession, config: WatchdogConfig) -> Option<String> {
+    if config.interval_ms == 0 {
+        return None;
+    }
+    let timeout = Duration::from_millis(config.timeout_ms);
+    let health = &session.health;
+
+    if health.replaying {
+        return (health.started.elapsed() > timeout)
+            .then(|| format!("no handshake reply within {} ms after restart", config.timeout_ms));
+    }
+    if let Some((sent, _)) = health.pending_ready.front() {
+        return (sent.elapsed() > timeout).then(|| format!("no readyok within {} ms", config.timeout_ms));
+    }
+    if health.handshake_done && !health.busy && health.last_activity.elapsed() >= Duration::from_millis(config.interval_ms) {
+        // A failed write means the process is going away; its listener reports the exit
+        let _ = session.ping();
+    }
+    None
+}
+
+/// Kill a hung engine and start it again with the same path, arguments, handshake and options.
+fn restart(app: &AppHandle, session_id: &str, pid: u32, reason: String) {
+    let Some(session) = app.state::<EngineSessions>().remove_if_current(session_id, pid) else {
+        return;
+    };
+    let _ = session.child.kill();
+
+    let mut launch = session.launch;
+    launch.restarts += 1;
+    let attempt = launch.restarts;
+    let (action, reason) = if attempt > MAX_RESTARTS {
+        (WatchdogAction::GaveUp, reason)
+    } else {
+        match engine::spawn_session(app, session_id, launch, true) {
+            Ok(()) => (WatchdogAction::Restarted, reason),
+            Err(e) => (WatchdogAction::GaveUp, e),
+        }
+    };
+    let _ = app.emit("engine-watchdog", WatchdogEvent {
+        session_id: session_id.to_string(),
+        action,
+        reason,
+        attempt,
+    });
+}
@@ -11,6 +11,7 @@ import { isAndroidPlatform as checkAndroidPlatform } from '../utils/platform'
 import { useInterfaceSettings } from './useInterfaceSettings'
 import { useGameSettings } from './useGameSettings'
 import { useHumanVsAiSettings } from './useHumanVsAiSettings'
+import { useGameClock } from './useGameClock'
 import { convertXQFToJieqiNotation } from '@/utils/xqf'
 import { useOpeningBook } from './useOpeningBook'
 import { useSoundEffects } from './useSoundEffects'
@@ -134,6 +135,26 @@ export function useChessGame() {
   // Game end dialog state
   const isGameEndDialogVisible = ref(false)
   const gameEndResult = ref<'human_wins' | 'ai_wins' | null>(null)
+  // Why the game ended: no legal moves left, or a flag fell
+  const gameEndReason = ref<'no_moves' | 'time'>('no_moves')
+
+  const { clockState, pressClock, restartClock, onClockFlag } = useGameClock()
+
+  // A flag ends a human vs AI game like running out of moves does
+  onClockFlag(side => {
+    if (!isHumanVsAiMode.value) return
+    const aiFlagged = side === aiSide.value
+    gameEndResult.value = aiFlagged ? 'human_wins' : 'ai_wins'
+    gameEndReason.value = 'time'
+    console.log('[DEBUG] GAME_END: Flag fell for', side)
+    window.dispatchEvent(
+      new CustomEvent('force-stop-ai', {
+        detail: { reason: 'flag' },
+      })
+    )
+    playSound(aiFlagged ? 'win' : 'loss')
+    isGameEndDialogVisible.value = true
+  })
 
   // Schedule resetting z-indexes back to positional values after move animation completes
   const scheduleZIndexResetAfterAnimation = () => {
@@ -1006,6 +1027,7 @@ export function useChessGame() {
         // Play win sound (checkmate sound already played in recordAndFinalize)
         setTimeout(() => playSound('win'), 300)
       }
+      gameEndReason.value = 'no_moves'
 
       // Show the game end dialog
       isGameEndDialogVisible.value = true
@@ -1022,6 +1044,13 @@ export function useChessGame() {
 
     // For 'move' type, update side to move before generating FEN for the resulting position.
     if (type === 'move') {
+      // The mover's clock stops; the backend ignores this for an engine
+      // whose bestmove has already pressed it
+      if (clockState.value) {
+        pressClock(sideToMove.value).catch(error =>
+          console.error('Failed to press game clock:', error)
+        )
+      }
       sideToMove.value = sideToMove.value === 'red' ? 'black' : 'red'
     }
 
@@ -1473,6 +1502,15 @@ export function useChessGame() {
     // Trigger arrow clear event
     triggerArrowClear()
 
+    // A running game clock starts over with the new game
+    if (clockState.value) {
+      try {
+        await restartClock(sideToMove.value)
+      } catch (error) {
+        console.error('Failed to restart game clock:', error)
+      }
+    }
+
     // Force stop engine analysis and AI to ensure engine doesn't continue thinking when starting new game
     // Trigger custom event to notify AnalysisSidebar component to close AI
     window.dispatchEvent(
@@ -3252,6 +3290,7 @@ export function useChessGame() {
     confirmFenInput,
     isGameEndDialogVisible,
     gameEndResult,
+    gameEndReason,
     isAnimating,
     lastMovePositions,
     initialFen,
@@ -2,6 +2,12 @@ import { ref, computed } from 'vue'
 import { invoke } from '@tauri-apps/api/core'
 import Ini from 'ini'
 import { isAndroidPlatform as checkAndroidPlatform } from '../utils/platform'
+import type {
+  EngineDialect,
+  EngineEncoding,
+  RemoteEngine,
+  SpawnOptions,
+} from '../types/engine'
 
 // Add this new interface and export it
 export interface ManagedEngine {
@@ -9,6 +15,16 @@ export interface ManagedEngine {
   name: string
   path: string
   args: string
+  // Encoding of the engine's input and output; 'system' when unset
+  encoding?: EngineEncoding
+  // Environment, working directory, nice level and CPU affinity
+  spawnOptions?: SpawnOptions
+  // Set for engines reached through an engine bridge instead of a local path
+  remote?: RemoteEngine
+  // Protocol the engine speaks; 'native' (UCI/JAI) when unset
+  dialect?: EngineDialect
+  // Restart the engine when it stops answering health checks; off when unset
+  watchdog?: boolean
 }
 
 // Configuration data structure
@@ -351,6 +367,24 @@ export function useConfigManager() {
       }
     }
   }
+  // Move engines copied to internal storage before the Android engine store into it, once,
+  // and point configured engines at their new paths. Imported engines nothing is configured
+  // to use are deleted again right away.
+  const importLegacyAndroidEngines = async () => {
+    if (!isAndroidPlatform.value) return
+    const paths = await invoke<Record<string, string>>(
+      'import_legacy_android_engines'
+    )
+    if (Object.keys(paths).length === 0) return
+    const engines = getEngines().map(engine =>
+      paths[engine.path] ? { ...engine, path: paths[engine.path] } : engine
+    )
+    await saveEngines(engines)
+    await invoke('prune_android_engines', {
+      inUse: engines.map(engine => engine.path),
+    })
+  }
+
 
   const getLastSelectedEngineId = (): string | null => {
     return configData.value.Settings?.lastSelectedEngineId || null
@@ -509,6 +543,7 @@ export function useConfigManager() {
     updateHumanVsAiSettings,
     getEngines,
     saveEngines,
+    importLegacyAndroidEngines,
     getLastSelectedEngineId,
     saveLastSelectedEngineId,
     clearLastSelectedEngineId,
@@ -0,0 +1,110 @@
+import { ref } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
+import type {
+  ClockControl,
+  ClockFlagEvent,
+  ClockSide,
+  ClockState,
+} from '@/types/clock'
+
+// The clock itself runs in the backend (clock.rs); this only mirrors its state.
+// Engine session whose bestmove replies press the clock for the AI side
+const ENGINE_SESSION_ID = 'analysis'
+
+const clockState = ref<ClockState | null>(null)
+// Side played by the engine, whose clock the backend presses itself
+const engineSide = ref<ClockSide | null>(null)
+const lastControl = ref<ClockControl | null>(null)
+const flagHandlers = new Set<(side: ClockSide) => void>()
+let listening = false
+
+const ensureListening = () => {
+  if (listening) return
+  listening = true
+  listen<ClockState>('clock-tick', event => {
+    if (lastControl.value) clockState.value = event.payload
+  })
+  listen<ClockFlagEvent>('clock-flag', event => {
+    flagHandlers.forEach(handler => handler(event.payload.side))
+  })
+}
+
+export function useGameClock() {
+  ensureListening()
+
+  // Start a new clock; `aiSide` is the side the analysis engine plays
+  const startClock = async (
+    control: ClockControl,
+    sideToMove: ClockSide,
+    aiSide: ClockSide | null
+  ) => {
+    lastControl.value = control
+    engineSide.value = aiSide
+    clockState.value = await invoke<ClockState>('start_clock', {
+      control,
+      sideToMove,
+      engine: aiSide ? { session_id: ENGINE_SESSION_ID, side: aiSide } : null,
+    })
+  }
+
+  // Start the last clock again from its initial time, e.g. for a new game
+  const restartClock = async (sideToMove: ClockSide) => {
+    if (lastControl.value) {
+      await startClock(lastControl.value, sideToMove, engineSide.value)
+    }
+  }
+
+  const stopClock = async () => {
+    lastControl.value = null
+    engineSide.value = null
+    clockState.value = null
+    await invoke('stop_clock')
+  }
+
+  // `side` finished its move; ignored by the backend if it is not on move
+  const pressClock = async (side: ClockSide) => {
+    if (!lastControl.value) return
+    const state = await invoke<ClockState | null>('press_clock', { side })
+    if (state) clockState.value = state
+  }
+
+  const pauseClock = async () => {
+    if (!lastControl.value) return
+    clockState.value = await invoke<ClockState | null>('pause_clock')
+  }
+
+  const resumeClock = async () => {
+    if (!lastControl.value) return
+    clockState.value = await invoke<ClockState | null>('resume_clock')
+  }
+
+  // `go` arguments from the clock, or null without one
+  const engineGoParams = () => invoke<string | null>('get_clock_go_params')
+
+  // Whether a search for `sideToMove` is the engine's move on the clock.
+  // Takes the side from the caller, as `clockState` may still wait for
+  // the press of the move just played.
+  const isEngineOnClock = (sideToMove: ClockSide) =>
+    !!clockState.value &&
+    !clockState.value.flagged &&
+    sideToMove === engineSide.value
+
+  const onClockFlag = (handler: (side: ClockSide) => void) => {
+    flagHandlers.add(handler)
+    return () => flagHandlers.delete(handler)
+  }
+
+  return {
+    clockState,
+    startClock,
+    restartClock,
+    stopClock,
+    pressClock,
+    pauseClock,
+    resumeClock,
+    engineGoParams,
+    isEngineOnClock,
+    onClockFlag,
+  }
+}
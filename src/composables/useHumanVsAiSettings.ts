@@ -1,4 +1,5 @@
 import { ref, watch } from 'vue'
+import { invoke } from '@tauri-apps/api/core'
 import { useConfigManager } from './useConfigManager'
 
 // Config manager instance
@@ -53,6 +54,18 @@ watch(
   }
 )
 
+// In human vs AI mode, have the backend keep the analysis engine's positions
+// to what the AI side could know, whatever code path sends them. The setting
+// is dropped with the engine session, so it is sent again for each new engine
+const applyFairPlay = () =>
+  invoke('set_engine_fair_play', {
+    sessionId: 'analysis',
+    enabled: isHumanVsAiMode.value,
+    side: aiSide.value,
+  }).catch(error => console.error('Failed to set fair play mode:', error))
+
+watch([isHumanVsAiMode, aiSide], applyFairPlay, { immediate: true })
+
 // Human vs AI settings composable
 export function useHumanVsAiSettings() {
   // Load configuration and update reactive refs
@@ -96,5 +109,6 @@ export function useHumanVsAiSettings() {
     toggleHumanVsAiMode,
     setAiSide,
     toggleShowEngineAnalysis,
+    applyFairPlay,
   }
 }
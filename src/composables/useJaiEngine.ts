@@ -5,6 +5,17 @@ import { useI18n } from 'vue-i18n'
 import { useConfigManager, type ManagedEngine } from './useConfigManager'
 import { useInterfaceSettings } from './useInterfaceSettings'
 import { useSoundEffects } from './useSoundEffects'
+import {
+  formatExitStatus,
+  hasProtocolLine,
+  isOutputForSession,
+  type EngineExitedEvent,
+  type EngineOutputEvent,
+  type EngineWatchdogEvent,
+} from '@/types/engine'
+
+// Engine session used by the JAI match engine
+const ENGINE_SESSION_ID = 'match'
 
 export interface JaiEngineLine {
   text: string
@@ -52,36 +63,15 @@ export function useJaiEngine(_generateFen: () => string, gameState: any) {
   const redEngine = ref('')
   const blackEngine = ref('')
 
-  // Throttling mechanism for engine output processing
-  let outputThrottleTimer: ReturnType<typeof setTimeout> | null = null
-  let pendingOutputLines: string[] = []
-  let lastProcessedTime = 0
-  const OUTPUT_THROTTLE_DELAY = 50 // Process output every 50ms maximum
-
   let unlisten: (() => void) | null = null
-
-  /* ---------- Output Throttling Functions ---------- */
-  // Process pending output lines with throttling
-  const processPendingOutput = () => {
-    if (pendingOutputLines.length === 0) return
-
-    const currentTime = Date.now()
-
-    // Check if enough time has passed since last processing
-    if (currentTime - lastProcessedTime < OUTPUT_THROTTLE_DELAY) {
-      // Schedule processing for later
-      if (outputThrottleTimer) {
-        clearTimeout(outputThrottleTimer)
-      }
-      outputThrottleTimer = setTimeout(
-        processPendingOutput,
-        OUTPUT_THROTTLE_DELAY - (currentTime - lastProcessedTime)
-      )
-      return
-    }
-
-    // Process all pending lines with parsing logic
-    pendingOutputLines.forEach(raw_ln => {
+  let unlistenExited: (() => void) | null = null
+  let unlistenWatchdog: (() => void) | null = null
+
+  /* ---------- Output Processing ---------- */
+  // Process a batch of output lines. The backend already gathers them into
+  // batches and drops analysis lines that newer ones have replaced.
+  const processOutputLines = (lines: string[]) => {
+    lines.forEach(raw_ln => {
       engineOutput.value.push({ text: raw_ln, kind: 'recv' })
 
       // Aggressive cleanup: limit engine output to prevent memory issues
@@ -257,33 +247,6 @@ export function useJaiEngine(_generateFen: () => string, gameState: any) {
         }
       }
     })
-
-    pendingOutputLines = []
-    lastProcessedTime = currentTime
-    outputThrottleTimer = null
-  }
-
-  // Add line to pending output queue
-  const queueOutputLine = (line: string) => {
-    pendingOutputLines.push(line)
-
-    // If no timer is running, start processing
-    if (!outputThrottleTimer) {
-      outputThrottleTimer = setTimeout(
-        processPendingOutput,
-        OUTPUT_THROTTLE_DELAY
-      )
-    }
-  }
-
-  // Clear throttling state when match starts/stops
-  const resetThrottling = () => {
-    if (outputThrottleTimer) {
-      clearTimeout(outputThrottleTimer)
-      outputThrottleTimer = null
-    }
-    pendingOutputLines = []
-    lastProcessedTime = 0
   }
 
   /* ---------- JAI Option Parsing ---------- */
@@ -369,7 +332,7 @@ export function useJaiEngine(_generateFen: () => string, gameState: any) {
 
     // Teardown previous engine if any
     if (isMatchRunning.value) stopMatch()
-    await invoke('kill_engine').catch(e =>
+    await invoke('kill_engine', { sessionId: ENGINE_SESSION_ID }).catch(e =>
       console.warn('Failed to kill previous engine:', e)
     )
 
@@ -387,8 +350,9 @@ export function useJaiEngine(_generateFen: () => string, gameState: any) {
       }, validationTimeout.value)
 
       // Listen specifically for the jaiok signal
-      listen<string>('engine-output', event => {
-        if (event.payload.trim() === 'jaiok') {
+      listen<EngineOutputEvent>('engine-output', event => {
+        if (!isOutputForSession(event.payload, ENGINE_SESSION_ID)) return
+        if (hasProtocolLine(event.payload, 'jaiok')) {
           console.log(
             `[DEBUG] Received jaiok for ${engine.name}. Validation successful.`
           )
@@ -409,10 +373,24 @@ export function useJaiEngine(_generateFen: () => string, gameState: any) {
         `[DEBUG] Spawning JAI engine: ${engine.name}, Path: ${engine.path}, Args: ${engine.args}`
       )
       await invoke('spawn_engine', {
+        sessionId: ENGINE_SESSION_ID,
         path: engine.path,
         args: engine.args.split(' ').filter(Boolean),
+        encoding: engine.encoding ?? 'system',
+        spawnOptions: engine.spawnOptions ?? null,
+        remote: engine.remote ?? null,
+        dialect: engine.dialect ?? 'native',
       })
 
+      // Health checks are opt-in per engine
+      if (engine.watchdog) {
+        await invoke('set_engine_watchdog', {
+          sessionId: ENGINE_SESSION_ID,
+          intervalMs: 30000,
+          timeoutMs: 20000,
+        })
+      }
+
       // Send 'jai' to start validation
       send('jai')
 
@@ -461,7 +439,7 @@ export function useJaiEngine(_generateFen: () => string, gameState: any) {
       // Clear the last selected engine ID if loading fails
       const configManager = useConfigManager()
       await configManager.clearLastSelectedEngineId()
-      await invoke('kill_engine').catch(err =>
+      await invoke('kill_engine', { sessionId: ENGINE_SESSION_ID }).catch(err =>
         console.warn('Failed to kill invalid JAI engine:', err)
       )
     } finally {
@@ -473,7 +451,10 @@ export function useJaiEngine(_generateFen: () => string, gameState: any) {
   const send = (cmd: string) => {
     engineOutput.value.push({ text: cmd, kind: 'sent' })
 
-    invoke('send_to_engine', { command: cmd }).catch(e => {
+    invoke('send_to_engine', {
+      sessionId: ENGINE_SESSION_ID,
+      command: cmd,
+    }).catch(e => {
       console.warn('Failed to send to JAI engine:', e)
     })
   }
@@ -484,12 +465,9 @@ export function useJaiEngine(_generateFen: () => string, gameState: any) {
 
     isMatchRunning.value = true
     isMatchStopping.value = false
-    resetThrottling()
 
     // Clear any accumulated data before starting match
     engineOutput.value = []
-    pendingOutputLines = []
-    lastProcessedTime = 0
 
     // Reset WLD statistics for new match
     matchWins.value = 0
@@ -510,7 +488,6 @@ export function useJaiEngine(_generateFen: () => string, gameState: any) {
 
     console.log('[DEBUG] STOP_MATCH: Stopping JAI match')
     isMatchStopping.value = true
-    resetThrottling()
     send('stop')
 
     // Reset match state
@@ -605,7 +582,7 @@ export function useJaiEngine(_generateFen: () => string, gameState: any) {
       await new Promise(resolve => setTimeout(resolve, 100))
 
       // As a fallback, also kill the engine process
-      await invoke('kill_engine')
+      await invoke('kill_engine', { sessionId: ENGINE_SESSION_ID })
       console.log(
         '[DEBUG] UNLOAD_JAI_ENGINE: Engine process terminated successfully'
       )
@@ -652,10 +629,48 @@ export function useJaiEngine(_generateFen: () => string, gameState: any) {
   /* ---------- Listen to Output ---------- */
   onMounted(async () => {
     // Central listener for all engine output for logging/display
-    unlisten = await listen<string>('engine-output', ev => {
-      const raw_ln = ev.payload
-      console.log(`[DEBUG] JAI_ENGINE_RAW_OUTPUT: ${raw_ln}`)
-      queueOutputLine(raw_ln)
+    unlisten = await listen<EngineOutputEvent>('engine-output', ev => {
+      if (!isOutputForSession(ev.payload, ENGINE_SESSION_ID)) return
+      ev.payload.lines.forEach(raw_ln => {
+        console.log(`[DEBUG] JAI_ENGINE_RAW_OUTPUT: ${raw_ln}`)
+      })
+      processOutputLines(ev.payload.lines)
+    })
+
+    // Stop the match when the engine process dies on its own (crash or quit)
+    unlistenExited = await listen<EngineExitedEvent>('engine-exited', ev => {
+      if (ev.payload.session_id !== ENGINE_SESSION_ID || ev.payload.killed)
+        return
+      console.warn('[DEBUG] JAI_ENGINE_EXITED:', ev.payload)
+      isMatchRunning.value = false
+      isMatchStopping.value = false
+      isEngineLoaded.value = false
+      currentEngine.value = null
+      matchEngineInfo.value = t('jai.engineExited', {
+        status: formatExitStatus(ev.payload),
+      })
+
+    // The watchdog restarts a hung engine, or gives up after repeated failures
+    unlistenWatchdog = await listen<EngineWatchdogEvent>(
+      'engine-watchdog',
+      ev => {
+        if (ev.payload.session_id !== ENGINE_SESSION_ID) return
+        console.warn('[DEBUG] JAI_ENGINE_WATCHDOG:', ev.payload)
+        isMatchRunning.value = false
+        isMatchStopping.value = false
+        if (ev.payload.action === 'restarted') {
+          matchEngineInfo.value = t('jai.engineRestarted', {
+            reason: ev.payload.reason,
+          })
+        } else {
+          isEngineLoaded.value = false
+          currentEngine.value = null
+          matchEngineInfo.value = t('jai.engineUnresponsive', {
+            reason: ev.payload.reason,
+          })
+        }
+      }
+    )
     })
 
     // Set up periodic cleanup for match mode
@@ -688,8 +703,10 @@ export function useJaiEngine(_generateFen: () => string, gameState: any) {
 
   onUnmounted(() => {
     unlisten?.()
-    invoke('kill_engine') // Kill engine on component unmount
-    resetThrottling()
+    unlistenExited?.()
+    unlistenWatchdog?.()
+    // Kill engine on component unmount
+    invoke('kill_engine', { sessionId: ENGINE_SESSION_ID })
 
     // Clean up periodic cleanup interval
     if ((window as any).__JAI_CLEANUP_INTERVAL__) {
@@ -5,12 +5,25 @@ import { useI18n } from 'vue-i18n'
 import { useConfigManager, type ManagedEngine } from './useConfigManager' // Import new types
 import { useInterfaceSettings } from './useInterfaceSettings'
 import { useSoundEffects } from './useSoundEffects'
+import { useGameClock } from './useGameClock'
+import { useHumanVsAiSettings } from './useHumanVsAiSettings'
 import { uciToChineseMoves } from '@/utils/chineseNotation'
+import {
+  formatExitStatus,
+  hasProtocolLine,
+  isOutputForSession,
+  type EngineExitedEvent,
+  type EngineOutputEvent,
+  type EngineWatchdogEvent,
+} from '@/types/engine'
 import {
   evaluateAdvancedScript,
   type PrevContext,
 } from '@/utils/advancedScriptInterpreter'
 
+// Engine session used by the analysis engine
+const ENGINE_SESSION_ID = 'analysis'
+
 export interface EngineLine {
   text: string
   kind: 'sent' | 'recv'
@@ -20,6 +33,8 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
   const { t } = useI18n()
   const { useNewFenFormat, validationTimeout } = useInterfaceSettings()
   const { playSoundLoop, stopSoundLoop } = useSoundEffects()
+  const { isEngineOnClock, engineGoParams } = useGameClock()
+  const { applyFairPlay } = useHumanVsAiSettings()
   const { convertFenFormat } = gameState
   const engineOutput = ref<EngineLine[]>([])
   const isEngineLoaded = ref(false)
@@ -66,14 +81,9 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
   // Chinese notation setting
   const showChineseNotation = ref(false)
 
-  // Throttling mechanism for engine output processing
-  let outputThrottleTimer: ReturnType<typeof setTimeout> | null = null
-  let pendingOutputLines: string[] = []
-  let lastProcessedTime = 0
-  const OUTPUT_THROTTLE_DELAY = 50 // Process output every 50ms maximum
-  const MATE_OUTPUT_THROTTLE_DELAY = 300 // Slower processing for mate situations
-
   let unlisten: (() => void) | null = null
+  let unlistenExited: (() => void) | null = null
+  let unlistenWatchdog: (() => void) | null = null
 
   /* ---------- Helper Functions ---------- */
   const isDarkPieceMove = (uciMove: string): boolean => {
@@ -114,39 +124,11 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
     return result
   }
 
-  /* ---------- Output Throttling Functions ---------- */
-  // Check if current analysis contains mate score
-  const hasMateScore = () => {
-    return analysisLines.some(line => line.includes('score mate'))
-  }
-
-  // Get appropriate throttle delay based on analysis content
-  const getThrottleDelay = () => {
-    return hasMateScore() ? MATE_OUTPUT_THROTTLE_DELAY : OUTPUT_THROTTLE_DELAY
-  }
-
-  // Process pending output lines with throttling
-  const processPendingOutput = () => {
-    if (pendingOutputLines.length === 0) return
-
-    const currentTime = Date.now()
-    const throttleDelay = getThrottleDelay()
-
-    // Check if enough time has passed since last processing
-    if (currentTime - lastProcessedTime < throttleDelay) {
-      // Schedule processing for later
-      if (outputThrottleTimer) {
-        clearTimeout(outputThrottleTimer)
-      }
-      outputThrottleTimer = setTimeout(
-        processPendingOutput,
-        throttleDelay - (currentTime - lastProcessedTime)
-      )
-      return
-    }
-
-    // Process all pending lines with parsing logic
-    pendingOutputLines.forEach(raw_ln => {
+  /* ---------- Output Processing ---------- */
+  // Process a batch of output lines. The backend already gathers them into
+  // batches and drops analysis lines that newer ones have replaced.
+  const processOutputLines = (lines: string[]) => {
+    lines.forEach(raw_ln => {
       engineOutput.value.push({ text: raw_ln, kind: 'recv' })
 
       // Aggressive cleanup: limit engine output to prevent memory issues
@@ -329,30 +311,6 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
         uciOptionsText.value += ln + '\n'
       }
     })
-
-    pendingOutputLines = []
-    lastProcessedTime = currentTime
-    outputThrottleTimer = null
-  }
-
-  // Add line to pending output queue
-  const queueOutputLine = (line: string) => {
-    pendingOutputLines.push(line)
-
-    // If no timer is running, start processing
-    if (!outputThrottleTimer) {
-      outputThrottleTimer = setTimeout(processPendingOutput, getThrottleDelay())
-    }
-  }
-
-  // Clear throttling state when analysis starts/stops
-  const resetThrottling = () => {
-    if (outputThrottleTimer) {
-      clearTimeout(outputThrottleTimer)
-      outputThrottleTimer = null
-    }
-    pendingOutputLines = []
-    lastProcessedTime = 0
   }
 
   /* ---------- Engine Loading and Validation ---------- */
@@ -374,7 +332,7 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
     // Teardown previous engine if any
     if (isThinking.value) stopAnalysis({ playBestMoveOnStop: false })
     if (isPondering.value) stopPonder({ playBestMoveOnStop: false })
-    await invoke('kill_engine').catch(e =>
+    await invoke('kill_engine', { sessionId: ENGINE_SESSION_ID }).catch(e =>
       console.warn('Failed to kill previous engine:', e)
     )
 
@@ -392,8 +350,9 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
       }, validationTimeout.value)
 
       // Listen specifically for the uciok signal
-      listen<string>('engine-output', event => {
-        if (event.payload.trim() === 'uciok') {
+      listen<EngineOutputEvent>('engine-output', event => {
+        if (!isOutputForSession(event.payload, ENGINE_SESSION_ID)) return
+        if (hasProtocolLine(event.payload, 'uciok')) {
           console.log(
             `[DEBUG] Received uciok for ${engine.name}. Validation successful.`
           )
@@ -414,10 +373,27 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
         `[DEBUG] Spawning engine: ${engine.name}, Path: ${engine.path}, Args: ${engine.args}`
       )
       await invoke('spawn_engine', {
+        sessionId: ENGINE_SESSION_ID,
         path: engine.path,
         args: engine.args.split(' ').filter(Boolean),
+        encoding: engine.encoding ?? 'system',
+        spawnOptions: engine.spawnOptions ?? null,
+        remote: engine.remote ?? null,
+        dialect: engine.dialect ?? 'native',
       })
 
+      // Session settings do not outlive the previous engine
+      await applyFairPlay()
+
+      // Health checks are opt-in per engine
+      if (engine.watchdog) {
+        await invoke('set_engine_watchdog', {
+          sessionId: ENGINE_SESSION_ID,
+          intervalMs: 30000,
+          timeoutMs: 20000,
+        })
+      }
+
       // Send 'uci' to start validation
       send('uci')
 
@@ -481,7 +457,7 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
       // Clear the last selected engine ID if loading fails
       const configManager = useConfigManager()
       await configManager.clearLastSelectedEngineId()
-      await invoke('kill_engine').catch(err =>
+      await invoke('kill_engine', { sessionId: ENGINE_SESSION_ID }).catch(err =>
         console.warn('Failed to kill invalid engine:', err)
       )
     } finally {
@@ -540,7 +516,10 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
       )
     }
 
-    invoke('send_to_engine', { command: cmd }).catch(e => {
+    invoke('send_to_engine', {
+      sessionId: ENGINE_SESSION_ID,
+      command: cmd,
+    }).catch(e => {
       // Don't alert here, it can be noisy during initial load failure
       console.warn('Failed to send to engine:', e)
     })
@@ -572,8 +551,9 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
       }, 5000)
 
       // Listen for readyok response
-      listen<string>('engine-output', event => {
-        if (event.payload.trim() === 'readyok') {
+      listen<EngineOutputEvent>('engine-output', event => {
+        if (!isOutputForSession(event.payload, ENGINE_SESSION_ID)) return
+        if (hasProtocolLine(event.payload, 'readyok')) {
           console.log(
             '[DEBUG] UCI_NEWGAME: Received readyok, new game initialized'
           )
@@ -615,9 +595,6 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
       analysisStartTime.value
     )
 
-    // Reset throttling state for new analysis
-    resetThrottling()
-
     // Clear analysis lines and multiPvMoves for new analysis
     analysisLines.length = 0
     multiPvMoves.value = []
@@ -810,6 +787,20 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
           }
           break
       }
+    // In a timed human vs AI game the clock sets the search limits instead
+    if (isEngineOnClock(gameState.sideToMove?.value)) {
+      engineGoParams()
+        .catch(error => {
+          console.error('Failed to get clock go parameters:', error)
+          return null
+        })
+        .then(params => {
+          const command = params ? `go ${params}${searchMovesStr}` : goCommand
+          console.log(`[DEBUG] START_ANALYSIS: Go command: ${command}`)
+          send(command)
+        })
+      return
+    }
     console.log(`[DEBUG] START_ANALYSIS: Go command: ${goCommand}`)
     send(goCommand)
   }
@@ -830,9 +821,6 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
     playOnStop.value = options.playBestMoveOnStop // Set flag for how to handle the resulting bestmove
     isInfinitePondering.value = false // Reset infinite pondering flag when stopping analysis
 
-    // Reset throttling state when stopping analysis
-    resetThrottling()
-
     send('stop')
   }
 
@@ -1044,7 +1032,7 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
       await new Promise(resolve => setTimeout(resolve, 100))
 
       // As a fallback, also kill the engine process
-      await invoke('kill_engine')
+      await invoke('kill_engine', { sessionId: ENGINE_SESSION_ID })
       console.log(
         '[DEBUG] UNLOAD_ENGINE: Engine process terminated successfully'
       )
@@ -1122,12 +1110,56 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
   /* ---------- Listen to Output ---------- */
   onMounted(async () => {
     // Central listener for all engine output for logging/display
-    unlisten = await listen<string>('engine-output', ev => {
-      const raw_ln = ev.payload
-      console.log(`[DEBUG] ENGINE_RAW_OUTPUT: ${raw_ln}`)
-      queueOutputLine(raw_ln)
+    unlisten = await listen<EngineOutputEvent>('engine-output', ev => {
+      if (!isOutputForSession(ev.payload, ENGINE_SESSION_ID)) return
+      ev.payload.lines.forEach(raw_ln => {
+        console.log(`[DEBUG] ENGINE_RAW_OUTPUT: ${raw_ln}`)
+      })
+      processOutputLines(ev.payload.lines)
     })
 
+    // Forget any search in progress; its bestmove will never come
+    const clearSearchState = () => {
+      isThinking.value = false
+      isStopping.value = false
+      isPondering.value = false
+      isInfinitePondering.value = false
+    }
+
+    // Reset engine state when the process dies on its own (crash or quit)
+    unlistenExited = await listen<EngineExitedEvent>('engine-exited', ev => {
+      if (ev.payload.session_id !== ENGINE_SESSION_ID || ev.payload.killed)
+        return
+      console.warn('[DEBUG] ENGINE_EXITED:', ev.payload)
+      clearSearchState()
+      isEngineLoaded.value = false
+      currentEngine.value = null
+      analysis.value = t('uci.engineExited', {
+        status: formatExitStatus(ev.payload),
+      })
+    })
+
+    // The watchdog restarts a hung engine, or gives up after repeated failures
+    unlistenWatchdog = await listen<EngineWatchdogEvent>(
+      'engine-watchdog',
+      ev => {
+        if (ev.payload.session_id !== ENGINE_SESSION_ID) return
+        console.warn('[DEBUG] ENGINE_WATCHDOG:', ev.payload)
+        clearSearchState()
+        if (ev.payload.action === 'restarted') {
+          analysis.value = t('uci.engineRestarted', {
+            reason: ev.payload.reason,
+          })
+        } else {
+          isEngineLoaded.value = false
+          currentEngine.value = null
+          analysis.value = t('uci.engineUnresponsive', {
+            reason: ev.payload.reason,
+          })
+        }
+      }
+    )
+
     // Check if engine list is empty and clear last selected engine ID if needed
     const configManager = useConfigManager()
     await configManager.loadConfig()
@@ -1174,8 +1206,10 @@ export function useUciEngine(generateFen: () => string, gameState: any) {
   })
   onUnmounted(() => {
     unlisten?.()
-    invoke('kill_engine') // Kill engine on component unmount
-    resetThrottling()
+    unlistenExited?.()
+    unlistenWatchdog?.()
+    // Kill engine on component unmount
+    invoke('kill_engine', { sessionId: ENGINE_SESSION_ID })
   })
 
   return {
@@ -215,6 +215,7 @@ prev.prev - Previous-previous move (supports infinite nesting)`,
       tooManyTotalPieces: 'Error: Total pieces exceed 16',
       darkPieceInvalidPosition: 'Error: Dark piece in invalid position',
       duplicatePosition: 'Error: Duplicate piece positions',
+      poolMismatch: 'Error: {reason}',
     },
     cancel: 'Cancel',
     applyChanges: 'Apply Changes',
@@ -246,6 +247,7 @@ prev.prev - Previous-previous move (supports infinite nesting)`,
     placeholder: 'Please input FEN string...',
     confirm: 'Confirm',
     cancel: 'Cancel',
+    rebuildPool: 'Rebuild Hidden Pool',
   },
 
   // Notation JSON dialog
@@ -379,10 +381,18 @@ prev.prev - Previous-previous move (supports infinite nesting)`,
     title: 'Engine Manager',
     addEngine: 'Add Engine',
     addEngineAndroid: 'Add Engine (SAF)',
+    addRemoteEngine: 'Add Remote Engine',
+    remoteAddress: 'Bridge Address (host:port)',
+    remoteToken: 'Bridge Token',
     editEngine: 'Edit Engine',
     engineName: 'Engine Name',
     enginePath: 'Engine Path',
     arguments: 'Command-line Arguments',
+    encoding: 'Text Encoding',
+    encodingSystem: 'System Default (GBK on Windows, UTF-8 elsewhere)',
+    encodingAuto: 'Detect Automatically',
+    dialect: 'Protocol',
+    watchdog: 'Restart When Unresponsive',
     actions: 'Actions',
     confirmDeleteTitle: 'Confirm Deletion',
     confirmDeleteMessage:
@@ -394,6 +404,15 @@ prev.prev - Previous-previous move (supports infinite nesting)`,
     promptNnueFile: 'Please select the NNUE file for the engine:',
     nameExists: 'This name already exists. Please use a unique name.',
     engineAddedSuccess: 'Engine {name} was added successfully!',
+    discoverEngines: 'Discover Engines',
+    discoveryDirs: 'Directories to scan',
+    discoveryDirsHint:
+      'One directory per line; subdirectories one level deep are scanned too.',
+    scan: 'Scan',
+    networksFound: 'Networks: {networks}',
+    addDiscovered: 'Add',
+    alreadyAdded: 'Added',
+    noEnginesFound: 'No engines found in these directories.',
   },
 
   // UCI saved options editor inside Engine Manager
@@ -466,6 +485,10 @@ prev.prev - Previous-previous move (supports infinite nesting)`,
   // JAI messages
   jai: {
     engineReady: 'Match engine is ready',
+    engineExited: 'Match engine exited unexpectedly ({status})',
+    engineRestarted:
+      'Match engine stopped responding and was restarted ({reason})',
+    engineUnresponsive: 'Match engine stopped responding ({reason})',
     matchStarted: 'Match started',
     matchStopped: 'Match stopped',
     gameProgress: 'Game {current} of {total}',
@@ -520,6 +543,7 @@ prev.prev - Previous-previous move (supports infinite nesting)`,
     failedToOpenFileSelector: 'Failed to open file selector',
     failedToProcessEngine: 'Failed to process engine file',
     invalidFenFormat: 'Invalid FEN format',
+    invalidFenDetail: 'Invalid FEN at character {position}: {reason}',
   },
 
   // Chessboard bottom
@@ -609,6 +633,9 @@ prev.prev - Previous-previous move (supports infinite nesting)`,
     bestMove: 'Best Move: {move}',
     noMoves: 'No moves available',
     engineReady: 'Engine is ready',
+    engineExited: 'Engine exited unexpectedly ({status})',
+    engineRestarted: 'Engine stopped responding and was restarted ({reason})',
+    engineUnresponsive: 'Engine stopped responding ({reason})',
   },
 
   // Game operation confirmation
@@ -629,6 +656,8 @@ prev.prev - Previous-previous move (supports infinite nesting)`,
     aiWinsMessage:
       'The AI has won this game. You have no legal moves remaining.',
     ok: 'OK',
+    humanWinsOnTimeMessage: 'You win on time! The AI ran out of time.',
+    aiWinsOnTimeMessage: 'The AI wins on time. You ran out of time.',
   },
 
   // Human vs AI mode
@@ -650,6 +679,27 @@ prev.prev - Previous-previous move (supports infinite nesting)`,
     rule3: 'AI can only see the dark pieces it captures from you',
     rule4: 'Limited information battle according to standard Jieqi rules',
     startGame: 'Start Game',
+    timeControl: 'Time Control',
+    timeControlHint:
+      'The engine searches with the remaining clock time. Running out of time loses the game.',
+    clockKinds: {
+      none: 'No clock',
+      sudden_death: 'Sudden death',
+      fischer: 'Fischer (increment)',
+      moves_per_session: 'Moves per session',
+      byo_yomi: 'Byo-yomi',
+      move_time: 'Fixed time per move',
+      depth: 'Fixed depth',
+      nodes: 'Fixed nodes',
+    },
+    baseMinutes: 'Base time (minutes)',
+    incrementSeconds: 'Increment (seconds)',
+    sessionMoves: 'Moves per session',
+    periodSeconds: 'Period (seconds)',
+    periods: 'Periods',
+    moveTimeSeconds: 'Time per move (seconds)',
+    depth: 'Depth',
+    nodes: 'Nodes',
   },
 
   // Opening Book
@@ -214,6 +214,7 @@ prev.prev - 前々の手（無限ネスト対応）`,
       tooManyTotalPieces: 'エラー: 総駒数が16を超えています',
       darkPieceInvalidPosition: 'エラー: 暗子の位置が不正です',
       duplicatePosition: 'エラー: 駒の位置が重複しています',
+      poolMismatch: 'エラー：{reason}',
     },
     cancel: 'キャンセル',
     applyChanges: '変更を適用',
@@ -245,6 +246,7 @@ prev.prev - 前々の手（無限ネスト対応）`,
     placeholder: 'FEN文字列を入力してください...',
     confirm: '確認',
     cancel: 'キャンセル',
+    rebuildPool: '暗子プールを再構築',
   },
 
   // 棋譜JSONダイアログ
@@ -378,10 +380,18 @@ prev.prev - 前々の手（無限ネスト対応）`,
     title: 'エンジンマネージャー',
     addEngine: 'エンジンを追加',
     addEngineAndroid: 'エンジンを追加 (SAF)',
+    addRemoteEngine: 'リモートエンジンを追加',
+    remoteAddress: 'ブリッジアドレス (ホスト:ポート)',
+    remoteToken: 'ブリッジトークン',
     editEngine: 'エンジンを編集',
     engineName: 'エンジン名',
     enginePath: 'エンジンパス',
     arguments: 'コマンドライン引数',
+    encoding: '文字エンコーディング',
+    encodingSystem: 'システム既定（Windows では GBK、その他は UTF-8）',
+    encodingAuto: '自動検出',
+    dialect: 'プロトコル',
+    watchdog: '応答がなければ再起動',
     actions: '操作',
     confirmDeleteTitle: '削除の確認',
     confirmDeleteMessage:
@@ -394,6 +404,15 @@ prev.prev - 前々の手（無限ネスト対応）`,
     nameExists:
       'この名前は既に使用されています。別の一意な名前を使用してください。',
     engineAddedSuccess: 'エンジン{name}が正常に追加されました！',
+    discoverEngines: 'エンジンを検出',
+    discoveryDirs: 'スキャンするディレクトリ',
+    discoveryDirsHint:
+      '1行に1ディレクトリ。1階層下のサブディレクトリもスキャンします。',
+    scan: 'スキャン',
+    networksFound: 'ネットワーク：{networks}',
+    addDiscovered: '追加',
+    alreadyAdded: '追加済み',
+    noEnginesFound: 'これらのディレクトリにエンジンは見つかりませんでした。',
   },
 
   // エンジンマネージャー内の保存済み UCI オプション編集
@@ -431,6 +450,7 @@ prev.prev - 前々の手（無限ネスト対応）`,
       'ファイル選択ダイアログを開くことができませんでした',
     failedToProcessEngine: 'エンジンファイルの処理に失敗しました',
     invalidFenFormat: '無効なFEN形式',
+    invalidFenDetail: '無効なFEN（{position}文字目）：{reason}',
   },
 
   // 盤下部
@@ -520,6 +540,9 @@ prev.prev - 前々の手（無限ネスト対応）`,
     bestMove: '最善手: {move}',
     noMoves: '利用可能な動きがありません',
     engineReady: 'エンジンは準備ができました',
+    engineExited: 'エンジンが予期せず終了しました（{status}）',
+    engineRestarted: 'エンジンが応答しなくなったため再起動しました（{reason}）',
+    engineUnresponsive: 'エンジンが応答しません（{reason}）',
   },
 
   // JAIオプションダイアログ
@@ -569,6 +592,10 @@ prev.prev - 前々の手（無限ネスト対応）`,
   // JAIメッセージ
   jai: {
     engineReady: 'マッチエンジンが準備できました',
+    engineExited: 'マッチエンジンが予期せず終了しました（{status}）',
+    engineRestarted:
+      'マッチエンジンが応答しなくなったため再起動しました（{reason}）',
+    engineUnresponsive: 'マッチエンジンが応答しません（{reason}）',
     matchStarted: 'マッチが開始されました',
     matchStopped: 'マッチが停止されました',
     gameProgress: '第 {current} 局、全 {total} 局',
@@ -623,6 +650,8 @@ prev.prev - 前々の手（無限ネスト対応）`,
     humanWinsMessage: 'AIを破りました！AIに合法手がありません。',
     aiWinsMessage: 'AIがこの対局で勝利しました。あなたに合法手がありません。',
     ok: 'OK',
+    humanWinsOnTimeMessage: 'AIが時間切れになりました。あなたの勝ちです！',
+    aiWinsOnTimeMessage: 'あなたが時間切れになりました。AIの勝ちです。',
   },
 
   // 人対AIモード
@@ -644,6 +673,27 @@ prev.prev - 前々の手（無限ネスト対応）`,
     rule3: 'AIはあなたから取った暗子のみを見ることができます',
     rule4: '標準揭棋ルールに従った限定情報戦',
     startGame: 'ゲーム開始',
+    timeControl: '持ち時間',
+    timeControlHint:
+      'エンジンは残り時間に応じて思考します。時間切れは負けです。',
+    clockKinds: {
+      none: '時計なし',
+      sudden_death: '切れ負け',
+      fischer: 'フィッシャー（加算）',
+      moves_per_session: '手数制',
+      byo_yomi: '秒読み',
+      move_time: '1手ごとの固定時間',
+      depth: '固定深さ',
+      nodes: '固定ノード数',
+    },
+    baseMinutes: '持ち時間（分）',
+    incrementSeconds: '加算（秒）',
+    sessionMoves: '区切りの手数',
+    periodSeconds: '秒読み（秒）',
+    periods: '秒読み回数',
+    moveTimeSeconds: '1手の時間（秒）',
+    depth: '深さ',
+    nodes: 'ノード数',
   },
 
   // 開局庫
@@ -215,6 +215,7 @@ prev.prev - Nước đi trước-trước (hỗ trợ lồng nhau vô hạn)`,
       tooManyTotalPieces: 'Lỗi: Tổng số quân vượt quá 16',
       darkPieceInvalidPosition: 'Lỗi: Quân úp ở vị trí không hợp lệ',
       duplicatePosition: 'Lỗi: Vị trí quân trùng lặp',
+      poolMismatch: 'Lỗi: {reason}',
     },
     cancel: 'Hủy',
     applyChanges: 'Áp dụng thay đổi',
@@ -246,6 +247,7 @@ prev.prev - Nước đi trước-trước (hỗ trợ lồng nhau vô hạn)`,
     placeholder: 'Vui lòng nhập chuỗi FEN...',
     confirm: 'Xác nhận',
     cancel: 'Hủy',
+    rebuildPool: 'Dựng lại kho quân úp',
   },
 
   // Hộp thoại biên bản JSON
@@ -379,10 +381,18 @@ prev.prev - Nước đi trước-trước (hỗ trợ lồng nhau vô hạn)`,
     title: 'Trình quản lý động cơ',
     addEngine: 'Thêm động cơ',
     addEngineAndroid: 'Thêm động cơ (SAF)',
+    addRemoteEngine: 'Thêm động cơ từ xa',
+    remoteAddress: 'Địa chỉ cầu nối (máy:cổng)',
+    remoteToken: 'Mã xác thực cầu nối',
     editEngine: 'Chỉnh sửa động cơ',
     engineName: 'Tên động cơ',
     enginePath: 'Đường dẫn động cơ',
     arguments: 'Tham số dòng lệnh',
+    encoding: 'Mã hóa văn bản',
+    encodingSystem: 'Mặc định hệ thống (GBK trên Windows, UTF-8 nơi khác)',
+    encodingAuto: 'Tự động phát hiện',
+    dialect: 'Giao thức',
+    watchdog: 'Khởi động lại khi không phản hồi',
     actions: 'Hành động',
     confirmDeleteTitle: 'Xác nhận xóa',
     confirmDeleteMessage:
@@ -394,6 +404,15 @@ prev.prev - Nước đi trước-trước (hỗ trợ lồng nhau vô hạn)`,
     promptNnueFile: 'Vui lòng chọn file NNUE cho động cơ:',
     nameExists: 'Tên này đã tồn tại. Vui lòng sử dụng một tên khác.',
     engineAddedSuccess: 'Đã thêm thành công động cơ {name}!',
+    discoverEngines: 'Tìm engine',
+    discoveryDirs: 'Thư mục cần quét',
+    discoveryDirsHint:
+      'Mỗi dòng một thư mục; các thư mục con một cấp cũng được quét.',
+    scan: 'Quét',
+    networksFound: 'Mạng nơ-ron: {networks}',
+    addDiscovered: 'Thêm',
+    alreadyAdded: 'Đã thêm',
+    noEnginesFound: 'Không tìm thấy engine nào trong các thư mục này.',
   },
 
   // Trình sửa tùy chọn UCI đã lưu trong Trình quản lý động cơ
@@ -463,6 +482,10 @@ prev.prev - Nước đi trước-trước (hỗ trợ lồng nhau vô hạn)`,
   // Tin nhắn JAI
   jai: {
     engineReady: 'động cơ trận đấu đã sẵn sàng',
+    engineExited: 'Động cơ trận đấu đã thoát bất ngờ ({status})',
+    engineRestarted:
+      'Động cơ trận đấu ngừng phản hồi và đã được khởi động lại ({reason})',
+    engineUnresponsive: 'Động cơ trận đấu ngừng phản hồi ({reason})',
     matchStarted: 'Trận đấu đã bắt đầu',
     matchStopped: 'Trận đấu đã dừng',
     gameProgress: 'Ván {current} của {total}',
@@ -517,6 +540,7 @@ prev.prev - Nước đi trước-trước (hỗ trợ lồng nhau vô hạn)`,
     failedToOpenFileSelector: 'Không thể mở trình chọn tệp',
     failedToProcessEngine: 'Không thể xử lý tệp động cơ',
     invalidFenFormat: 'Định dạng FEN không hợp lệ',
+    invalidFenDetail: 'FEN không hợp lệ tại ký tự {position}: {reason}',
   },
 
   // Phần dưới bàn cờ
@@ -606,6 +630,10 @@ prev.prev - Nước đi trước-trước (hỗ trợ lồng nhau vô hạn)`,
     bestMove: 'Nước đi tốt nhất: {move}',
     noMoves: 'Không có nước đi nào',
     engineReady: 'Động cơ đã sẵn sàng',
+    engineExited: 'Động cơ đã thoát bất ngờ ({status})',
+    engineRestarted:
+      'Động cơ ngừng phản hồi và đã được khởi động lại ({reason})',
+    engineUnresponsive: 'Động cơ ngừng phản hồi ({reason})',
   },
 
   // Xác nhận thao tác trò chơi
@@ -624,6 +652,8 @@ prev.prev - Nước đi trước-trước (hỗ trợ lồng nhau vô hạn)`,
     humanWinsMessage: 'Bạn đã đánh bại AI! AI không còn nước đi hợp lệ.',
     aiWinsMessage: 'AI đã thắng trận đấu này. Bạn không còn nước đi hợp lệ.',
     ok: 'OK',
+    humanWinsOnTimeMessage: 'AI đã hết giờ. Bạn thắng!',
+    aiWinsOnTimeMessage: 'Bạn đã hết giờ. AI thắng trận đấu này.',
   },
 
   // Chế độ người chơi vs AI
@@ -645,6 +675,27 @@ prev.prev - Nước đi trước-trước (hỗ trợ lồng nhau vô hạn)`,
     rule3: 'AI chỉ có thể thấy quân úp mà nó ăn từ bạn',
     rule4: 'Trận đấu thông tin hạn chế theo luật Cờ úp tiêu chuẩn',
     startGame: 'Bắt đầu trò chơi',
+    timeControl: 'Kiểm soát thời gian',
+    timeControlHint:
+      'Engine suy nghĩ theo thời gian còn lại. Hết giờ sẽ bị xử thua.',
+    clockKinds: {
+      none: 'Không tính giờ',
+      sudden_death: 'Đột tử',
+      fischer: 'Fischer (cộng giờ)',
+      moves_per_session: 'Số nước mỗi chặng',
+      byo_yomi: 'Byo-yomi',
+      move_time: 'Thời gian cố định mỗi nước',
+      depth: 'Độ sâu cố định',
+      nodes: 'Số nút cố định',
+    },
+    baseMinutes: 'Thời gian cơ bản (phút)',
+    incrementSeconds: 'Cộng giờ (giây)',
+    sessionMoves: 'Số nước mỗi chặng',
+    periodSeconds: 'Thời gian mỗi lượt (giây)',
+    periods: 'Số lượt',
+    moveTimeSeconds: 'Thời gian mỗi nước (giây)',
+    depth: 'Độ sâu',
+    nodes: 'Số nút',
   },
 
   // Sách khai cuộc
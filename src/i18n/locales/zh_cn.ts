@@ -210,6 +210,7 @@ prev.prev - 上上步（支持无限嵌套）`,
       tooManyTotalPieces: '错误: 总棋子数超过16',
       darkPieceInvalidPosition: '错误: 暗子位置不合法',
       duplicatePosition: '错误: 棋子位置重复',
+      poolMismatch: '错误：{reason}',
     },
     cancel: '取消',
     applyChanges: '应用更改',
@@ -241,6 +242,7 @@ prev.prev - 上上步（支持无限嵌套）`,
     placeholder: '请输入FEN字符串...',
     confirm: '确认',
     cancel: '取消',
+    rebuildPool: '重建暗子池',
   },
 
   // 棋谱JSON对话框
@@ -374,10 +376,18 @@ prev.prev - 上上步（支持无限嵌套）`,
     title: '引擎管理器',
     addEngine: '添加引擎',
     addEngineAndroid: '添加引擎 (SAF)',
+    addRemoteEngine: '添加远程引擎',
+    remoteAddress: '桥接地址 (主机:端口)',
+    remoteToken: '桥接令牌',
     editEngine: '编辑引擎',
     engineName: '引擎名称',
     enginePath: '引擎路径',
     arguments: '命令行参数',
+    encoding: '文本编码',
+    encodingSystem: '系统默认（Windows 上为 GBK，其他为 UTF-8）',
+    encodingAuto: '自动检测',
+    dialect: '协议',
+    watchdog: '无响应时重启',
     actions: '操作',
     confirmDeleteTitle: '确认删除',
     confirmDeleteMessage: '您确定要删除引擎“{name}”吗？此操作无法撤销。',
@@ -387,6 +397,14 @@ prev.prev - 上上步（支持无限嵌套）`,
     promptNnueFile: '请选择引擎的NNUE文件：',
     nameExists: '该名称已存在，请使用唯一的名称。',
     engineAddedSuccess: '引擎 {name} 添加成功!',
+    discoverEngines: '查找引擎',
+    discoveryDirs: '扫描目录',
+    discoveryDirsHint: '每行一个目录，也会扫描下一级子目录。',
+    scan: '扫描',
+    networksFound: '神经网络：{networks}',
+    addDiscovered: '添加',
+    alreadyAdded: '已添加',
+    noEnginesFound: '这些目录中没有找到引擎。',
   },
 
   // 引擎管理器中的已保存 UCI 选项编辑器
@@ -452,6 +470,9 @@ prev.prev - 上上步（支持无限嵌套）`,
   // JAI 消息
   jai: {
     engineReady: '比赛引擎已就绪',
+    engineExited: '比赛引擎意外退出（{status}）',
+    engineRestarted: '比赛引擎无响应，已重新启动（{reason}）',
+    engineUnresponsive: '比赛引擎无响应（{reason}）',
     matchStarted: '比赛已开始',
     matchStopped: '比赛已停止',
     gameProgress: '第 {current} 局，共 {total} 局',
@@ -504,6 +525,7 @@ prev.prev - 上上步（支持无限嵌套）`,
     failedToOpenFileSelector: '打开文件选择器失败',
     failedToProcessEngine: '处理引擎文件失败',
     invalidFenFormat: '无效的FEN格式',
+    invalidFenDetail: '无效的FEN（第{position}个字符）：{reason}',
   },
 
   // 棋盘底部
@@ -593,6 +615,9 @@ prev.prev - 上上步（支持无限嵌套）`,
     bestMove: '最佳着法: {move}',
     noMoves: '无着可走',
     engineReady: '引擎已就绪',
+    engineExited: '引擎意外退出（{status}）',
+    engineRestarted: '引擎无响应，已重新启动（{reason}）',
+    engineUnresponsive: '引擎无响应（{reason}）',
   },
 
   // 游戏操作确认
@@ -611,6 +636,8 @@ prev.prev - 上上步（支持无限嵌套）`,
     humanWinsMessage: '您击败了AI！AI已无合法走法。',
     aiWinsMessage: 'AI获得了这局胜利。您已无合法走法。',
     ok: '确定',
+    humanWinsOnTimeMessage: 'AI超时，您获得了胜利！',
+    aiWinsOnTimeMessage: '您已超时，AI获得了这局胜利。',
   },
 
   // 人机对战模式
@@ -631,6 +658,26 @@ prev.prev - 上上步（支持无限嵌套）`,
     rule3: 'AI只能看到自己吃掉您的暗子',
     rule4: '符合标准揭棋规则的有限信息对战',
     startGame: '开始游戏',
+    timeControl: '用时设置',
+    timeControlHint: '引擎按剩余时间思考，超时判负。',
+    clockKinds: {
+      none: '不计时',
+      sudden_death: '包干制',
+      fischer: '加秒制',
+      moves_per_session: '分段计时',
+      byo_yomi: '读秒制',
+      move_time: '每步固定时间',
+      depth: '固定深度',
+      nodes: '固定节点数',
+    },
+    baseMinutes: '基本用时（分钟）',
+    incrementSeconds: '每步加秒（秒）',
+    sessionMoves: '每段步数',
+    periodSeconds: '读秒时长（秒）',
+    periods: '读秒次数',
+    moveTimeSeconds: '每步用时（秒）',
+    depth: '深度',
+    nodes: '节点数',
   },
 
   // 开局库
@@ -209,6 +209,7 @@ prev.prev - 上上步（支援無限巢狀）`,
       tooManyTotalPieces: '錯誤: 總棋子數超過16',
       darkPieceInvalidPosition: '錯誤: 暗子位置不合法',
       duplicatePosition: '錯誤: 棋子位置重複',
+      poolMismatch: '錯誤：{reason}',
     },
     cancel: '取消',
     applyChanges: '套用變更',
@@ -239,6 +240,7 @@ prev.prev - 上上步（支援無限巢狀）`,
     placeholder: '請輸入FEN字串...',
     confirm: '確認',
     cancel: '取消',
+    rebuildPool: '重建暗子池',
   },
 
   // 棋譜JSON對話框
@@ -372,10 +374,18 @@ prev.prev - 上上步（支援無限巢狀）`,
     title: '引擎管理器',
     addEngine: '新增引擎',
     addEngineAndroid: '新增引擎 (SAF)',
+    addRemoteEngine: '新增遠端引擎',
+    remoteAddress: '橋接位址 (主機:連接埠)',
+    remoteToken: '橋接權杖',
     editEngine: '編輯引擎',
     engineName: '引擎名稱',
     enginePath: '引擎路徑',
     arguments: '命令列參數',
+    encoding: '文字編碼',
+    encodingSystem: '系統預設（Windows 上為 GBK，其他為 UTF-8）',
+    encodingAuto: '自動偵測',
+    dialect: '協定',
+    watchdog: '無回應時重新啟動',
     actions: '操作',
     confirmDeleteTitle: '確認刪除',
     confirmDeleteMessage: '您確定要刪除引擎「{name}」嗎？此操作無法復原。',
@@ -385,6 +395,14 @@ prev.prev - 上上步（支援無限巢狀）`,
     promptNnueFile: '請選擇引擎的NNUE檔案：',
     nameExists: '該名稱已存在，請使用唯一的名稱。',
     engineAddedSuccess: '引擎 {name} 新增成功！',
+    discoverEngines: '尋找引擎',
+    discoveryDirs: '掃描目錄',
+    discoveryDirsHint: '每行一個目錄，也會掃描下一級子目錄。',
+    scan: '掃描',
+    networksFound: '神經網路：{networks}',
+    addDiscovered: '新增',
+    alreadyAdded: '已新增',
+    noEnginesFound: '這些目錄中沒有找到引擎。',
   },
 
   // 引擎管理器中的已保存 UCI 選項編輯器
@@ -420,6 +438,7 @@ prev.prev - 上上步（支援無限巢狀）`,
     failedToOpenFileSelector: '開啟檔案選擇器失敗',
     failedToProcessEngine: '處理引擎檔案失敗',
     invalidFenFormat: '無效的FEN格式',
+    invalidFenDetail: '無效的FEN（第{position}個字元）：{reason}',
   },
 
   // 棋盤底部
@@ -509,6 +528,9 @@ prev.prev - 上上步（支援無限巢狀）`,
     bestMove: '最佳著法: {move}',
     noMoves: '無著可走',
     engineReady: '引擎已就緒',
+    engineExited: '引擎意外退出（{status}）',
+    engineRestarted: '引擎無回應，已重新啟動（{reason}）',
+    engineUnresponsive: '引擎無回應（{reason}）',
   },
 
   // JAI選項對話框
@@ -556,6 +578,9 @@ prev.prev - 上上步（支援無限巢狀）`,
   // JAI訊息
   jai: {
     engineReady: '比賽引擎已就緒',
+    engineExited: '比賽引擎意外退出（{status}）',
+    engineRestarted: '比賽引擎無回應，已重新啟動（{reason}）',
+    engineUnresponsive: '比賽引擎無回應（{reason}）',
     matchStarted: '比賽已開始',
     matchStopped: '比賽已停止',
     gameProgress: '第 {current} 局，共 {total} 局',
@@ -609,6 +634,8 @@ prev.prev - 上上步（支援無限巢狀）`,
     humanWinsMessage: '您擊敗了AI！AI已無合法走法。',
     aiWinsMessage: 'AI獲得了這局勝利。您已無合法走法。',
     ok: '確定',
+    humanWinsOnTimeMessage: 'AI超時，您獲得了勝利！',
+    aiWinsOnTimeMessage: '您已超時，AI獲得了這局勝利。',
   },
 
   // 人機對戰模式
@@ -629,6 +656,26 @@ prev.prev - 上上步（支援無限巢狀）`,
     rule3: 'AI只能看到自己吃掉您的暗子',
     rule4: '符合標準揭棋規則的有限信息對戰',
     startGame: '開始遊戲',
+    timeControl: '用時設定',
+    timeControlHint: '引擎按剩餘時間思考，超時判負。',
+    clockKinds: {
+      none: '不計時',
+      sudden_death: '包乾制',
+      fischer: '加秒制',
+      moves_per_session: '分段計時',
+      byo_yomi: '讀秒制',
+      move_time: '每步固定時間',
+      depth: '固定深度',
+      nodes: '固定節點數',
+    },
+    baseMinutes: '基本用時（分鐘）',
+    incrementSeconds: '每步加秒（秒）',
+    sessionMoves: '每段步數',
+    periodSeconds: '讀秒時長（秒）',
+    periods: '讀秒次數',
+    moveTimeSeconds: '每步用時（秒）',
+    depth: '深度',
+    nodes: '節點數',
   },
 
   // 開局庫
@@ -0,0 +1,55 @@
+// Game clock types, mirroring clock.rs
+
+// How a game is timed, see `ClockControl` in clock.rs
+export type ClockControl =
+  | { kind: 'sudden_death'; base_ms: number }
+  | { kind: 'fischer'; base_ms: number; increment_ms: number }
+  | {
+      kind: 'moves_per_session'
+      base_ms: number
+      moves: number
+      increment_ms?: number
+    }
+  | { kind: 'byo_yomi'; base_ms: number; period_ms: number; periods: number }
+  | { kind: 'move_time'; movetime_ms: number }
+  | { kind: 'depth'; depth: number }
+  | { kind: 'nodes'; nodes: number }
+
+export type ClockControlKind = ClockControl['kind']
+
+export type ClockSide = 'red' | 'black'
+
+export interface SideClock {
+  // Main time, or the current period once in byo-yomi
+  time_ms: number
+  // Byo-yomi periods left, including the current one
+  periods: number
+  in_byoyomi: boolean
+  moves: number
+}
+
+// Payload of the `clock-tick` event
+export interface ClockState {
+  control: ClockControl
+  red: SideClock
+  black: SideClock
+  to_move: ClockSide
+  running: boolean
+  flagged: ClockSide | null
+}
+
+// Payload of the `clock-flag` event
+export interface ClockFlagEvent {
+  side: ClockSide
+}
+
+// m:ss, or h:mm:ss from an hour up; tenths under ten seconds
+export const formatClockTime = (ms: number): string => {
+  const clamped = Math.max(0, ms)
+  if (clamped < 10_000) return (clamped / 1000).toFixed(1)
+  const total = Math.floor(clamped / 1000)
+  const h = Math.floor(total / 3600)
+  const m = Math.floor((total % 3600) / 60)
+  const s = String(total % 60).padStart(2, '0')
+  return h > 0 ? `${h}:${String(m).padStart(2, '0')}:${s}` : `${m}:${s}`
+}
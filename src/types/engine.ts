@@ -0,0 +1,161 @@
+// Engine session types shared by the engine composables
+
+// Stream a batch of engine output lines came from. 'debug' carries
+// diagnostics produced by JieqiBox itself rather than by the engine.
+export type EngineStream = 'stdout' | 'stderr' | 'debug'
+
+// Payload of the `engine-output` event emitted by the Rust engine layer.
+// Every entry of `lines` is one complete line without its terminator.
+// `session_id` is null for diagnostics that do not belong to a session.
+export interface EngineOutputEvent {
+  session_id: string | null
+  stream: EngineStream
+  lines: string[]
+}
+
+// Returns true if the event should be handled by the given session.
+export const isOutputForSession = (
+  payload: EngineOutputEvent,
+  sessionId: string
+): boolean => payload.session_id === null || payload.session_id === sessionId
+
+// Returns true if the engine printed the given protocol line on stdout.
+export const hasProtocolLine = (
+  payload: EngineOutputEvent,
+  line: string
+): boolean =>
+  payload.stream === 'stdout' && payload.lines.some(l => l.trim() === line)
+
+// Fields shared by the typed events parsed from UCI output
+// (`engine-id`, `engine-uciok`, `engine-readyok`, `engine-option`,
+// `engine-info`, `engine-bestmove`). `raw` is the original line.
+interface UciEventBase {
+  session_id: string
+  raw: string
+}
+
+export interface EngineIdEvent extends UciEventBase {
+  kind: 'id'
+  name: string | null
+  author: string | null
+}
+
+export interface EngineOptionEvent extends UciEventBase {
+  kind: 'option'
+  name: string
+  option_type: string
+  default: string | null
+  min: number | null
+  max: number | null
+  vars: string[]
+}
+
+export interface EngineInfoEvent extends UciEventBase {
+  kind: 'info'
+  depth: number | null
+  seldepth: number | null
+  multipv: number | null
+  score_cp: number | null
+  mate: number | null
+  bound: 'lower' | 'upper' | null
+  nodes: number | null
+  nps: number | null
+  hashfull: number | null
+  time: number | null
+  currmove: string | null
+  pv: string[]
+  string: string | null
+}
+
+export interface EngineBestMoveEvent extends UciEventBase {
+  kind: 'bestmove'
+  bestmove: string
+  ponder: string | null
+}
+
+// Text encoding of an engine's pipes, see `EngineEncoding` in encoding.rs
+export type EngineEncoding =
+  | 'system'
+  | 'utf8'
+  | 'gbk'
+  | 'gb18030'
+  | 'big5'
+  | 'auto'
+
+// Protocol an engine speaks, see `EngineDialect` in ucci.rs. UCCI engines are
+// driven with UCI commands that the backend translates.
+export type EngineDialect = 'native' | 'ucci'
+
+// Process settings of an engine launch, see `SpawnOptions` in spawn_options.rs
+export interface SpawnOptions {
+  env?: Record<string, string>
+  cwd?: string | null
+  // Nice level, -20..19 (Linux only)
+  nice?: number | null
+  // CPUs to pin the engine to (Linux only)
+  cpu_affinity?: number[] | null
+}
+
+// Engine served by `jieqibox-cli serve`, see `RemoteEngine` in engine_bridge.rs
+export interface RemoteEngine {
+  // host:port of the bridge
+  address: string
+  token: string
+}
+
+// Payload of the `engine-exited` event, sent once per engine process.
+// `killed` is true when JieqiBox killed or replaced the process itself.
+export interface EngineExitedEvent {
+  session_id: string
+  code: number | null
+  signal: number | null
+  error: string | null
+  killed: boolean
+  stderr_tail: string[]
+}
+
+// Short description of how an engine process ended, for status messages.
+export const formatExitStatus = (payload: EngineExitedEvent): string => {
+  if (payload.signal !== null) return `signal ${payload.signal}`
+  if (payload.code !== null) return `code ${payload.code}`
+  return payload.error ?? 'unknown'
+}
+
+// Payload of the `engine-watchdog` event, sent when a session's engine stopped
+// answering health checks and was restarted, or given up on.
+export interface EngineWatchdogEvent {
+  session_id: string
+  action: 'restarted' | 'gave_up'
+  reason: string
+  attempt: number
+}
+
+// Result of the `probe_engine` command, as stored in the engine registry
+export interface EngineProfile {
+  hash: string
+  path: string
+  protocol: 'uci' | 'ucci' | 'jai'
+  name: string | null
+  author: string | null
+  options: Omit<EngineOptionEvent, 'kind' | keyof UciEventBase>[]
+  probed_at: number
+}
+
+// Engine found by the `discover_engines` command, see engine_discovery.rs
+export interface EngineCandidate {
+  path: string
+  // Name the engine reported, or its file name
+  name: string
+  profile: EngineProfile
+  // Network files next to the engine
+  networks: string[]
+  // Option to set so the engine finds its network, if its default does not
+  network_option: { name: string; value: string } | null
+}
+
+export interface DiscoveryResult {
+  // Directories that were scanned, after defaults were applied
+  dirs: string[]
+  candidates: EngineCandidate[]
+  skipped: { path: string; reason: string }[]
+}